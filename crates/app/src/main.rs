@@ -2,12 +2,13 @@ use anyhow::Result;
 use boxer_core::services::backends::BackendConfiguration;
 use boxer_core::services::observability::composed_logger::ComposedLogger;
 use boxer_core::services::observability::open_telemetry;
-use boxer_core::services::observability::open_telemetry::metrics::init_metrics;
-use boxer_core::services::observability::open_telemetry::tracing::init_tracer;
 use boxer_validator_nginx_http::services::backends;
 use boxer_validator_nginx_http::services::configuration::models::AppSettings;
+use boxer_validator_nginx_http::services::shutdown::{ShutdownHooks, run_until_shutdown, shutdown_signal};
+use boxer_validator_nginx_http::services::telemetry::Telemetry;
 use env_filter::Builder;
 use log::info;
+use std::sync::Arc;
 
 const ROOT_METRICS_NAMESPACE: &str = "boxer-validator";
 
@@ -40,20 +41,36 @@ async fn main() -> Result<()> {
 
     info!("Configuration manager started");
 
-    if cm.opentelemetry.tracing_settings.enabled {
-        info!("Tracing is enabled, starting tracer...");
-        init_tracer()?;
-    }
+    let shutdown_hooks = ShutdownHooks::default();
+    let telemetry = Arc::new(Telemetry::install(&cm.opentelemetry, &cm.prometheus)?);
+    shutdown_hooks.register("telemetry", {
+        let telemetry = telemetry.clone();
+        async move {
+            // Exporting blocks until the export completes, which needs the runtime to be free
+            actix_web::rt::task::spawn_blocking(move || telemetry.shutdown()).await?
+        }
+    });
 
-    if cm.opentelemetry.metrics_settings.enabled {
-        info!("Metrics is enabled, starting metrics...");
-        init_metrics()?;
-    }
     let current_backend = backends::new()
         .configure(&cm.backend.kubernetes, cm.instance_name.clone())
         .await?;
 
-    let server = boxer_validator_nginx_http::start_api_server(current_backend, cm, ROOT_METRICS_NAMESPACE)?;
+    let shutdown_settings = cm.shutdown.clone();
+    let server = boxer_validator_nginx_http::start_api_server(
+        current_backend.clone(),
+        cm,
+        ROOT_METRICS_NAMESPACE,
+        &telemetry,
+        &shutdown_hooks,
+    )
+    .await?;
 
-    server.await.map_err(anyhow::Error::from)
+    run_until_shutdown(
+        server,
+        current_backend,
+        &shutdown_hooks,
+        &shutdown_settings,
+        shutdown_signal(),
+    )
+    .await
 }
@@ -1,7 +1,10 @@
+use crate::http::middleware::client_certificate::require_admin_client;
+use crate::http::plane::Plane;
+use crate::http::signing_keys::SigningKeys;
 use actix_web::dev::HttpServiceFactory;
+use actix_web::middleware::from_fn;
 use actix_web::web;
 use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
-use boxer_core::services::token_decryption_service::TokenDecryptionService;
 use std::sync::Arc;
 use utoipa::openapi::security::{Http, HttpAuthScheme, SecurityScheme};
 use utoipa::{Modify, OpenApi};
@@ -9,23 +12,34 @@ use utoipa::{Modify, OpenApi};
 pub mod action_set;
 pub mod policy_set;
 pub mod resource_set;
+pub mod route_table;
 pub mod schema;
+pub mod simulation;
 pub mod token_review;
+pub mod validation;
 
 #[derive(OpenApi)]
 #[openapi(paths(
         schema::get_schema,
         schema::post_schema,
         schema::delete_schema,
+        schema::list_schemas,
         action_set::get_action_set,
         action_set::post_action_set,
         action_set::delete_action_set,
+        action_set::list_action_sets,
         resource_set::get_resource_set,
         resource_set::post_resource_set,
         resource_set::delete_resource_set,
+        resource_set::list_resource_sets,
         policy_set::get_policy_set,
         policy_set::post_policy_set,
         policy_set::delete_policy_set,
+        policy_set::list_policy_sets,
+        route_table::get_route_tables,
+        route_table::get_route_table,
+        route_table::resolve_route,
+        simulation::simulate_policy_set,
         token_review::token_review,
     ),
     modifiers(&SecurityAddon)
@@ -40,11 +54,28 @@ impl Modify for SecurityAddon {
         components.add_security_scheme("internal", SecurityScheme::Http(Http::new(HttpAuthScheme::Bearer)));
     }
 }
-pub fn urls(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<TokenDecryptionService>) -> impl HttpServiceFactory {
-    web::scope("/api/v1")
-        .service(schema::crud())
-        .service(action_set::crud())
-        .service(resource_set::crud())
-        .service(policy_set::crud())
-        .service(token_review::routes(audit_writer, decryptor))
+/// The v1 API endpoints of the plane.
+pub fn urls(
+    audit_writer: Arc<dyn AuditWriter>,
+    signing_keys: Arc<SigningKeys>,
+    plane: Plane,
+) -> impl HttpServiceFactory {
+    web::scope("/api/v1").configure(|cfg| {
+        if plane.serves_data() {
+            cfg.service(token_review::routes(audit_writer.clone(), signing_keys.clone()));
+        }
+        if plane.serves_admin() {
+            cfg.service(
+                // Registered after the token scope, so it only handles the admin API
+                web::scope("")
+                    .wrap(from_fn(require_admin_client))
+                    .service(schema::crud(audit_writer.clone(), signing_keys.clone()))
+                    .service(action_set::crud(audit_writer.clone(), signing_keys.clone()))
+                    .service(resource_set::crud(audit_writer.clone(), signing_keys.clone()))
+                    .service(policy_set::crud(audit_writer.clone(), signing_keys.clone()))
+                    .service(route_table::routes(audit_writer.clone(), signing_keys.clone()))
+                    .service(simulation::routes(audit_writer.clone(), signing_keys.clone())),
+            );
+        }
+    })
 }
@@ -1,15 +1,25 @@
 pub mod models;
 
 use crate::http::controllers::v1::action_set::models::ActionSetRegistration;
-use crate::services::repositories::action_repository::read_write::ActionDataRepository;
+use crate::http::controllers::v1::validation::{InvalidRegistration, ValidateRegistration, load_schema_catalog};
+use crate::http::middleware::admin_authorization::{admin_api_scope, authorize_publication};
+use crate::http::signing_keys::SigningKeys;
+use crate::services::audit_trail::{AuditOperation, AuditTrail, AuditTrailEvent};
+use crate::services::repositories::action_repository::read_write::{ActionDataRepository, ActionSetListRepository};
+use crate::services::repositories::list_repository::{ListPage, ListQuery, ListedItem};
 use actix_web::dev::HttpServiceFactory;
-use actix_web::web::{Data, Json, Path};
-use actix_web::{HttpResponse, Responder, Result, delete, get, post, web};
+use actix_web::web::{Data, Json, Path, Query, ReqData, ServiceConfig};
+use actix_web::{HttpRequest, HttpResponse, Responder, Result, delete, get, post};
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
 use std::sync::Arc;
 
 #[utoipa::path(context_path = "/action_set/",
     responses(
-        (status = OK)
+        (status = OK),
+        (status = BAD_REQUEST, body = InvalidRegistration, description = "Registration contains invalid routes"),
+        (status = FORBIDDEN, description = "Registration contains public routes and the principal may not publish them")
     ),
     request_body = ActionSetRegistration,
     security(
@@ -20,11 +30,31 @@ use std::sync::Arc;
 async fn post_action_set(
     id: Path<(String, String)>,
     request: Json<ActionSetRegistration>,
+    schemas: Data<Arc<SchemaRepository>>,
     data: Data<Arc<ActionDataRepository>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
+    http_request: HttpRequest,
 ) -> Result<impl Responder> {
     let (schema, id) = id.into_inner();
-    data.upsert((schema.clone(), id), request.into_inner().with_schema(schema))
+    request.validate()?;
+    if request.routes.iter().any(|route| route.public) {
+        authorize_publication(&http_request).await?;
+    }
+    let catalog = load_schema_catalog(&schema, schemas.get_ref()).await?;
+    request.validate_against(&catalog)?;
+    let before = data.get((schema.clone(), id.clone())).await.ok();
+    let after = data
+        .upsert(
+            (schema.clone(), id.clone()),
+            request.into_inner().with_key(schema.clone(), id.clone()),
+        )
         .await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Upsert, "action_set", &schema, &id)
+            .with_before(before.as_ref())
+            .with_after(Some(&after)),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -55,14 +85,48 @@ async fn get_action_set(id: Path<(String, String)>, data: Data<Arc<ActionDataRep
 async fn delete_action_set(
     id: Path<(String, String)>,
     data: Data<Arc<ActionDataRepository>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
 ) -> Result<impl Responder> {
-    data.delete(id.into_inner()).await?;
+    let (schema, id) = id.into_inner();
+    let before = data.get((schema.clone(), id.clone())).await.ok();
+    data.delete((schema.clone(), id.clone())).await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Delete, "action_set", &schema, &id)
+            .with_before(before.as_ref()),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
-pub fn crud() -> impl HttpServiceFactory {
-    web::scope("/action_set")
+#[utoipa::path(context_path = "/action_set/",
+    params(ListQuery),
+    responses(
+        (status = OK, body = ListPage<ListedItem<ActionSetRegistration>>),
+        (status = BAD_REQUEST, description = "Invalid label selector")
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("{schema}")]
+async fn list_action_sets(
+    schema: Path<String>,
+    query: Query<ListQuery>,
+    data: Data<Arc<ActionSetListRepository>>,
+) -> Result<impl Responder> {
+    let page = data.list(Some(schema.into_inner()), query.into_inner()).await?;
+    let page: ListPage<ListedItem<ActionSetRegistration>> = page.map(|item| item.map(Into::into));
+    Ok(Json(page))
+}
+
+pub fn crud(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<SigningKeys>) -> impl HttpServiceFactory {
+    admin_api_scope("/action_set", audit_writer, decryptor, services)
+}
+
+pub(crate) fn services(config: &mut ServiceConfig) {
+    config
         .service(post_action_set)
         .service(get_action_set)
-        .service(delete_action_set)
+        .service(list_action_sets)
+        .service(delete_action_set);
 }
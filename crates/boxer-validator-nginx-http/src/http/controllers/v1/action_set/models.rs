@@ -1,22 +1,45 @@
+use crate::http::controllers::v1::validation::{InvalidRegistration, ValidateRegistration};
 use crate::services::repositories::action_repository::action_discovery_document::{
-    ActionDiscoveryDocument, ActionDiscoveryDocumentSpec,
+    ActionDiscoveryDocument, ActionDiscoveryDocumentSpec, ActionRouteMethod,
 };
+use crate::services::route_template::parse_route_template;
+use crate::services::schema_catalog::SchemaCatalog;
 use boxer_core::services::audit::audit_facade::to_audit_record::ToAuditRecord;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::to_resource::ToResource;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::try_from_resource::TryFromResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
+use cedar_policy::EntityUid;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
+/// A single HTTP method (or `"*"` for any method) or a list of methods.
+#[derive(ToSchema, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum ActionMethodRegistration {
+    Single(String),
+    List(Vec<String>),
+}
+
+impl From<&str> for ActionMethodRegistration {
+    fn from(method: &str) -> Self {
+        ActionMethodRegistration::Single(method.to_string())
+    }
+}
+
 #[derive(ToSchema, Serialize, Deserialize)]
 #[schema(rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub struct ActionRouteRegistration {
-    pub method: String,
+    pub method: ActionMethodRegistration,
     pub route_template: String,
     pub action_uid: String,
+    /// Allow matching requests without a token, unless a more specific route is protected
+    /// or another schema also registers routes on the hostname.
+    #[serde(default)]
+    pub public: bool,
 }
 
 #[derive(ToSchema, Serialize, Deserialize)]
@@ -28,12 +51,45 @@ pub struct ActionSetRegistration {
 }
 
 impl ActionSetRegistration {
-    pub fn with_schema(self, schema: String) -> SchemaBoundActionSetRegistration {
+    pub fn with_key(self, schema: String, id: String) -> SchemaBoundActionSetRegistration {
         SchemaBoundActionSetRegistration {
             hostname: self.hostname,
             routes: self.routes,
             schema,
+            id,
+        }
+    }
+}
+
+impl ValidateRegistration for ActionSetRegistration {
+    fn validate(&self) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        errors.check_hostname(&self.hostname);
+        for (i, route) in self.routes.iter().enumerate() {
+            if let Err(e) = ActionRouteMethod::try_from(&route.method) {
+                errors.push(format!("routes[{i}].method"), e);
+            }
+            if let Err(e) = parse_route_template(&route.route_template) {
+                errors.push(format!("routes[{i}].routeTemplate"), e);
+            }
+            if let Err(e) = EntityUid::from_str(&route.action_uid) {
+                errors.push(format!("routes[{i}].actionUid"), e);
+            }
+        }
+        errors.into_result()
+    }
+
+    fn validate_against(&self, catalog: &SchemaCatalog) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        for (i, route) in self.routes.iter().enumerate() {
+            let checked = EntityUid::from_str(&route.action_uid)
+                .map_err(anyhow::Error::from)
+                .and_then(|uid| catalog.check_action(&uid));
+            if let Err(e) = checked {
+                errors.push(format!("routes[{i}].actionUid"), e);
+            }
         }
+        errors.into_result()
     }
 }
 
@@ -42,6 +98,7 @@ pub struct SchemaBoundActionSetRegistration {
     pub hostname: String,
     pub routes: Vec<ActionRouteRegistration>,
     pub schema: String,
+    pub id: String,
 }
 
 impl TryFromResource<ActionDiscoveryDocument> for SchemaBoundActionSetRegistration {
@@ -1,16 +1,25 @@
 pub mod models;
 
 use crate::http::controllers::v1::policy_set::models::PolicySetRegistration;
-use crate::services::repositories::policy_repository::read_write::PolicyDataRepository;
+use crate::http::controllers::v1::validation::{InvalidRegistration, ValidateRegistration, load_schema_catalog};
+use crate::http::middleware::admin_authorization::admin_api_scope;
+use crate::http::signing_keys::SigningKeys;
+use crate::services::audit_trail::{AuditOperation, AuditTrail, AuditTrailEvent};
+use crate::services::repositories::list_repository::{ListPage, ListQuery, ListedItem};
+use crate::services::repositories::policy_repository::read_write::{PolicyDataRepository, PolicySetListRepository};
 use actix_web::Result;
 use actix_web::dev::HttpServiceFactory;
-use actix_web::web::{Data, Json, Path};
-use actix_web::{HttpResponse, Responder, delete, get, post, web};
+use actix_web::web::{Data, Json, Path, Query, ReqData, ServiceConfig};
+use actix_web::{HttpResponse, Responder, delete, get, post};
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
 use std::sync::Arc;
 
 #[utoipa::path(context_path = "/policy_set/",
     responses(
-        (status = OK)
+        (status = OK),
+        (status = BAD_REQUEST, body = InvalidRegistration, description = "Registration is not a valid policy, template or link")
     ),
     request_body = PolicySetRegistration,
     security(
@@ -21,11 +30,30 @@ use std::sync::Arc;
 async fn post_policy_set(
     id: Path<(String, String)>,
     request: Json<PolicySetRegistration>,
+    schemas: Data<Arc<SchemaRepository>>,
     data: Data<Arc<PolicyDataRepository>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
 ) -> Result<impl Responder> {
     let (schema, id) = id.into_inner();
-    data.upsert((schema.clone(), id), request.into_inner().with_schema(schema))
+    request.validate()?;
+    // Only template links refer to schema entities
+    if request.link.is_some() {
+        let catalog = load_schema_catalog(&schema, schemas.get_ref()).await?;
+        request.validate_against(&catalog)?;
+    }
+    let before = data.get((schema.clone(), id.clone())).await.ok();
+    let after = data
+        .upsert(
+            (schema.clone(), id.clone()),
+            request.into_inner().with_key(schema.clone(), id.clone()),
+        )
         .await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Upsert, "policy_set", &schema, &id)
+            .with_before(before.as_ref())
+            .with_after(Some(&after)),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -56,14 +84,48 @@ async fn get_policy_set(id: Path<(String, String)>, data: Data<Arc<PolicyDataRep
 async fn delete_policy_set(
     id: Path<(String, String)>,
     data: Data<Arc<PolicyDataRepository>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
 ) -> Result<impl Responder> {
-    data.delete(id.into_inner()).await?;
+    let (schema, id) = id.into_inner();
+    let before = data.get((schema.clone(), id.clone())).await.ok();
+    data.delete((schema.clone(), id.clone())).await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Delete, "policy_set", &schema, &id)
+            .with_before(before.as_ref()),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
-pub fn crud() -> impl HttpServiceFactory {
-    web::scope("/policy_set")
+#[utoipa::path(context_path = "/policy_set/",
+    params(ListQuery),
+    responses(
+        (status = OK, body = ListPage<ListedItem<PolicySetRegistration>>),
+        (status = BAD_REQUEST, description = "Invalid label selector")
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("{schema}")]
+async fn list_policy_sets(
+    schema: Path<String>,
+    query: Query<ListQuery>,
+    data: Data<Arc<PolicySetListRepository>>,
+) -> Result<impl Responder> {
+    let page = data.list(Some(schema.into_inner()), query.into_inner()).await?;
+    let page: ListPage<ListedItem<PolicySetRegistration>> = page.map(|item| item.map(Into::into));
+    Ok(Json(page))
+}
+
+pub fn crud(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<SigningKeys>) -> impl HttpServiceFactory {
+    admin_api_scope("/policy_set", audit_writer, decryptor, services)
+}
+
+pub(crate) fn services(config: &mut ServiceConfig) {
+    config
         .service(post_policy_set)
         .service(get_policy_set)
-        .service(delete_policy_set)
+        .service(list_policy_sets)
+        .service(delete_policy_set);
 }
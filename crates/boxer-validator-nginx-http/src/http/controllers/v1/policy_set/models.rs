@@ -1,10 +1,19 @@
-use crate::services::repositories::policy_repository::policy_document::{PolicyDocument, PolicyDocumentSpec};
+#[cfg(test)]
+mod tests;
+
+use crate::http::controllers::v1::validation::{InvalidRegistration, ValidateRegistration};
+use crate::services::repositories::policy_repository::policy_document::{
+    PolicyDocument, PolicyDocumentSpec, PolicyKind, PolicyMode, TemplateLink,
+};
+use crate::services::schema_catalog::SchemaCatalog;
 use boxer_core::services::audit::audit_facade::to_audit_record::ToAuditRecord;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::to_resource::ToResource;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::try_from_resource::TryFromResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
+use cedar_policy::{EntityUid, Template};
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
@@ -12,15 +21,81 @@ use utoipa::ToSchema;
 #[schema(rename_all = "camelCase")]
 #[serde(rename_all = "camelCase")]
 pub struct PolicySetRegistration {
+    /// Cedar policies, or the policy template of a `template` registration. Empty for `link` registrations.
+    #[serde(default)]
     pub policy: String,
+    /// `shadow` policies never affect token reviews; their would-be decisions are only recorded.
+    #[serde(default)]
+    pub mode: PolicyMode,
+    #[serde(default)]
+    pub kind: PolicyKind,
+    /// The template and slot values of a `link` registration. Links follow the mode of their template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<TemplateLink>,
 }
 
 impl PolicySetRegistration {
-    pub fn with_schema(self, schema: String) -> SchemaBoundPolicySetRegistration {
+    pub fn with_key(self, schema: String, id: String) -> SchemaBoundPolicySetRegistration {
         SchemaBoundPolicySetRegistration {
             policy: self.policy.clone(),
             schema,
+            id,
+            mode: self.mode,
+            kind: self.kind,
+            link: self.link,
+        }
+    }
+}
+
+impl ValidateRegistration for PolicySetRegistration {
+    fn validate(&self) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        match (self.kind, &self.link) {
+            (PolicyKind::Link, None) => errors.push("link".to_string(), "Link registrations must name a template"),
+            (PolicyKind::Link, Some(link)) => {
+                if !self.policy.trim().is_empty() {
+                    errors.push("policy".to_string(), "Link registrations must not contain policies");
+                }
+                if link.template.trim().is_empty() {
+                    errors.push("link.template".to_string(), "Template must not be empty");
+                }
+                if link.principal.is_none() && link.resource.is_none() {
+                    errors.push(
+                        "link".to_string(),
+                        "A link must bind the principal or the resource slot",
+                    );
+                }
+                for (field, uid) in [("link.principal", &link.principal), ("link.resource", &link.resource)] {
+                    if let Some(Err(e)) = uid.as_deref().map(EntityUid::from_str) {
+                        errors.push(field.to_string(), e);
+                    }
+                }
+            }
+            (_, Some(_)) => errors.push("link".to_string(), "Only link registrations can refer to a template"),
+            (PolicyKind::Template, None) => {
+                if let Err(e) = Template::from_str(&self.policy) {
+                    errors.push("policy".to_string(), e);
+                }
+            }
+            (PolicyKind::Static, None) => {}
+        }
+        errors.into_result()
+    }
+
+    fn validate_against(&self, catalog: &SchemaCatalog) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        if let Some(link) = &self.link {
+            for (field, uid) in [("link.principal", &link.principal), ("link.resource", &link.resource)] {
+                let checked = uid
+                    .as_deref()
+                    .map(|uid| EntityUid::from_str(uid).map_err(anyhow::Error::from))
+                    .map(|uid| uid.and_then(|uid| catalog.check_entity(&uid)));
+                if let Some(Err(e)) = checked {
+                    errors.push(field.to_string(), e);
+                }
+            }
         }
+        errors.into_result()
     }
 }
 
@@ -28,14 +103,28 @@ impl PolicySetRegistration {
 pub struct SchemaBoundPolicySetRegistration {
     pub policy: String,
     pub schema: String,
+    pub id: String,
+    pub mode: PolicyMode,
+    #[serde(skip_serializing_if = "is_static")]
+    pub kind: PolicyKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link: Option<TemplateLink>,
+}
+
+fn is_static(kind: &PolicyKind) -> bool {
+    *kind == PolicyKind::Static
 }
 
 impl ToResource<PolicyDocument> for SchemaBoundPolicySetRegistration {
     fn to_resource(&self, object_meta: &ObjectMeta) -> Result<PolicyDocument, Status> {
         let spec = PolicyDocumentSpec {
+            id: Some(self.id.clone()),
             active: true,
             policies: self.policy.clone(),
             schema: self.schema.clone(),
+            mode: self.mode,
+            kind: self.kind,
+            link: self.link.clone(),
         };
         Ok(PolicyDocument {
             metadata: object_meta.clone(),
@@ -48,6 +137,9 @@ impl Default for PolicySetRegistration {
     fn default() -> Self {
         PolicySetRegistration {
             policy: Default::default(),
+            mode: Default::default(),
+            kind: Default::default(),
+            link: None,
         }
     }
 }
@@ -64,7 +156,12 @@ impl TryFromResource<PolicyDocument> for SchemaBoundPolicySetRegistration {
 
 impl Into<PolicySetRegistration> for SchemaBoundPolicySetRegistration {
     fn into(self) -> PolicySetRegistration {
-        PolicySetRegistration { policy: self.policy }
+        PolicySetRegistration {
+            policy: self.policy,
+            mode: self.mode,
+            kind: self.kind,
+            link: self.link,
+        }
     }
 }
 
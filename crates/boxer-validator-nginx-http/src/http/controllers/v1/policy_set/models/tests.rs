@@ -0,0 +1,83 @@
+use super::*;
+use test_case::test_case;
+
+fn link(template: &str, principal: Option<&str>, resource: Option<&str>) -> TemplateLink {
+    TemplateLink {
+        template: template.to_string(),
+        principal: principal.map(str::to_string),
+        resource: resource.map(str::to_string),
+    }
+}
+
+fn violations(registration: PolicySetRegistration) -> Vec<String> {
+    match registration.validate() {
+        Ok(()) => Vec::new(),
+        Err(errors) => errors.violations.into_iter().map(|v| v.field).collect(),
+    }
+}
+
+#[test_case(link("photo-owner", Some("PhotoApp::User::\"alice\""), Some("PhotoApp::Photo::\"1\"")) => Vec::<String>::new(); "both slots")]
+#[test_case(link("photo-owner", None, Some("PhotoApp::Photo::\"1\"")) => Vec::<String>::new(); "resource slot only")]
+#[test_case(link("photo-owner", None, None) => vec!["link".to_string()]; "no slots")]
+#[test_case(link("", Some("PhotoApp::User::\"alice\""), None) => vec!["link.template".to_string()]; "empty template")]
+#[test_case(link("photo-owner", Some("alice"), Some("1")) => vec!["link.principal".to_string(), "link.resource".to_string()]; "invalid uids")]
+fn test_validate_link(link: TemplateLink) -> Vec<String> {
+    violations(PolicySetRegistration {
+        kind: PolicyKind::Link,
+        link: Some(link),
+        ..Default::default()
+    })
+}
+
+#[test]
+fn test_link_without_template_is_rejected() {
+    let registration = PolicySetRegistration {
+        kind: PolicyKind::Link,
+        ..Default::default()
+    };
+
+    assert_eq!(violations(registration), vec!["link".to_string()]);
+}
+
+#[test]
+fn test_link_with_policies_is_rejected() {
+    let registration = PolicySetRegistration {
+        policy: "permit(principal, action, resource);".to_string(),
+        kind: PolicyKind::Link,
+        link: Some(link("photo-owner", Some("PhotoApp::User::\"alice\""), None)),
+        ..Default::default()
+    };
+
+    assert_eq!(violations(registration), vec!["policy".to_string()]);
+}
+
+#[test]
+fn test_static_policy_with_link_is_rejected() {
+    let registration = PolicySetRegistration {
+        policy: "permit(principal, action, resource);".to_string(),
+        link: Some(link("photo-owner", Some("PhotoApp::User::\"alice\""), None)),
+        ..Default::default()
+    };
+
+    assert_eq!(violations(registration), vec!["link".to_string()]);
+}
+
+#[test]
+fn test_deserialize_link_registration() {
+    let registration: PolicySetRegistration = serde_json::from_value(serde_json::json!({
+        "kind": "link",
+        "link": {
+            "template": "photo-owner",
+            "principal": "PhotoApp::User::\"alice\"",
+        },
+    }))
+    .unwrap();
+
+    assert_eq!(registration.kind, PolicyKind::Link);
+    assert_eq!(registration.policy, "");
+    assert_eq!(registration.mode, PolicyMode::Enforce);
+    assert_eq!(
+        registration.link,
+        Some(link("photo-owner", Some("PhotoApp::User::\"alice\""), None))
+    );
+}
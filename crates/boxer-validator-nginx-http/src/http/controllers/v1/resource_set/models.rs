@@ -1,12 +1,17 @@
+use crate::http::controllers::v1::validation::{InvalidRegistration, ValidateRegistration};
 use crate::services::repositories::resource_repository::resource_discovery_document::{
     ResourceDiscoveryDocument, ResourceDiscoveryDocumentSpec,
 };
+use crate::services::route_template::parse_route_template;
+use crate::services::schema_catalog::SchemaCatalog;
 use boxer_core::services::audit::audit_facade::to_audit_record::ToAuditRecord;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::to_resource::ToResource;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::try_from_resource::TryFromResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
+use cedar_policy::EntityUid;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 use utoipa::ToSchema;
 
@@ -27,20 +32,51 @@ pub struct ResourceSetRegistration {
 }
 
 impl ResourceSetRegistration {
-    pub fn with_schema(self, schema: String) -> SchemaBoundResourceSetRegistration {
+    pub fn with_key(self, schema: String, id: String) -> SchemaBoundResourceSetRegistration {
         SchemaBoundResourceSetRegistration {
             hostname: self.hostname,
             routes: self.routes,
             schema,
+            id,
         }
     }
 }
 
+impl ValidateRegistration for ResourceSetRegistration {
+    fn validate(&self) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        errors.check_hostname(&self.hostname);
+        for (i, route) in self.routes.iter().enumerate() {
+            if let Err(e) = parse_route_template(&route.route_template) {
+                errors.push(format!("routes[{i}].routeTemplate"), e);
+            }
+            if let Err(e) = EntityUid::from_str(&route.resource_uid) {
+                errors.push(format!("routes[{i}].resourceUid"), e);
+            }
+        }
+        errors.into_result()
+    }
+
+    fn validate_against(&self, catalog: &SchemaCatalog) -> Result<(), InvalidRegistration> {
+        let mut errors = InvalidRegistration::default();
+        for (i, route) in self.routes.iter().enumerate() {
+            let checked = EntityUid::from_str(&route.resource_uid)
+                .map_err(anyhow::Error::from)
+                .and_then(|uid| catalog.check_entity(&uid));
+            if let Err(e) = checked {
+                errors.push(format!("routes[{i}].resourceUid"), e);
+            }
+        }
+        errors.into_result()
+    }
+}
+
 #[derive(Serialize)]
 pub struct SchemaBoundResourceSetRegistration {
     pub hostname: String,
     pub routes: Vec<ResourceRouteRegistration>,
     pub schema: String,
+    pub id: String,
 }
 
 impl TryFromResource<ResourceDiscoveryDocument> for SchemaBoundResourceSetRegistration {
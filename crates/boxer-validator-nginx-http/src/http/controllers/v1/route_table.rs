@@ -0,0 +1,96 @@
+pub mod models;
+
+use crate::http::controllers::v1::route_table::models::{ResolveQuery, ResolveResponse, SchemaRouteTable};
+use crate::http::middleware::admin_authorization::admin_api_scope;
+use crate::http::signing_keys::SigningKeys;
+use crate::services::repositories::lookup_trie::route_table::RouteTables;
+use actix_web::dev::HttpServiceFactory;
+use actix_web::web::{Data, Json, Path, Query, ServiceConfig};
+use actix_web::{Responder, Result, get};
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use std::sync::Arc;
+
+async fn schema_route_table(schema: String, tables: &RouteTables) -> SchemaRouteTable {
+    SchemaRouteTable {
+        actions: tables.actions.routes(&schema).await,
+        resources: tables.resources.routes(&schema).await,
+        schema,
+    }
+}
+
+#[utoipa::path(context_path = "/route_table",
+    responses(
+        (status = OK, body = Vec<SchemaRouteTable>)
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("")]
+async fn get_route_tables(tables: Data<Arc<RouteTables>>) -> Result<impl Responder> {
+    let mut schemas = tables.actions.schemas().await;
+    schemas.extend(tables.resources.schemas().await);
+    schemas.sort();
+    schemas.dedup();
+
+    let mut result = Vec::with_capacity(schemas.len());
+    for schema in schemas {
+        result.push(schema_route_table(schema, &tables).await);
+    }
+    Ok(Json(result))
+}
+
+#[utoipa::path(context_path = "/route_table/",
+    responses(
+        (status = OK, body = SchemaRouteTable)
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("{schema}")]
+async fn get_route_table(schema: Path<String>, tables: Data<Arc<RouteTables>>) -> Result<impl Responder> {
+    Ok(Json(schema_route_table(schema.into_inner(), &tables).await))
+}
+
+#[utoipa::path(context_path = "/route_table/",
+    params(ResolveQuery),
+    responses(
+        (status = OK, body = ResolveResponse),
+        (status = BAD_REQUEST, description = "URL or method cannot be converted to a lookup key")
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("{schema}/resolve")]
+async fn resolve_route(
+    schema: Path<String>,
+    query: Query<ResolveQuery>,
+    tables: Data<Arc<RouteTables>>,
+) -> Result<impl Responder> {
+    let request = || RequestContext::new(query.url.clone(), query.method.clone());
+    let action = tables
+        .actions
+        .resolve(&schema, request())
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    let resource = tables
+        .resources
+        .resolve(&schema, request())
+        .await
+        .map_err(actix_web::error::ErrorBadRequest)?;
+    Ok(Json(ResolveResponse { action, resource }))
+}
+
+pub fn routes(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<SigningKeys>) -> impl HttpServiceFactory {
+    admin_api_scope("/route_table", audit_writer, decryptor, services)
+}
+
+pub(crate) fn services(config: &mut ServiceConfig) {
+    config
+        .service(get_route_tables)
+        .service(resolve_route)
+        .service(get_route_table);
+}
@@ -0,0 +1,25 @@
+use crate::services::repositories::lookup_trie::route_table::{ResolvedRoute, RouteTableEntry};
+use serde::{Deserialize, Serialize};
+use utoipa::{IntoParams, ToSchema};
+
+#[derive(ToSchema, Serialize)]
+pub struct SchemaRouteTable {
+    pub schema: String,
+    pub actions: Vec<RouteTableEntry>,
+    pub resources: Vec<RouteTableEntry>,
+}
+
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ResolveQuery {
+    /// Full request URL, e.g. `https://www.example.com/api/v1/photos/1`.
+    pub url: String,
+    /// HTTP method of the request.
+    pub method: String,
+}
+
+#[derive(ToSchema, Serialize)]
+pub struct ResolveResponse {
+    pub action: ResolvedRoute,
+    pub resource: ResolvedRoute,
+}
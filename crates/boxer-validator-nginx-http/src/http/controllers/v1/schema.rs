@@ -1,14 +1,52 @@
+use crate::http::controllers::v1::validation::SchemaConflict;
+use crate::http::middleware::admin_authorization::admin_api_scope;
+use crate::http::signing_keys::SigningKeys;
+use crate::services::admin_authorization::ADMIN_SCHEMA_ID;
+use crate::services::audit_trail::{AuditOperation, AuditTrail, AuditTrailEvent};
+use crate::services::repositories::list_repository::{ListPage, ListQuery, ListedItem, SchemaListRepository};
+use crate::services::schema_catalog::{SchemaCatalog, SchemaUsageIndex};
 use actix_web::dev::HttpServiceFactory;
-use actix_web::web::{Data, Json, Path};
-use actix_web::{HttpResponse, Responder, Result, delete, get, post, web};
+use actix_web::web::{Data, Json, Path, Query, ReqData, ServiceConfig};
+use actix_web::{HttpResponse, Responder, Result, delete, get, post};
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::audit_facade::to_audit_record::ToAuditRecord;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
 use cedar_policy::SchemaFragment;
+use log::warn;
+use serde::Deserialize;
 use serde_json::Value;
 use std::sync::Arc;
 
+#[derive(Deserialize)]
+struct SchemaUpdateOptions {
+    #[serde(default)]
+    force: bool,
+}
+
+/// Schema content as recorded in the audit trail.
+struct SchemaRecord(Value);
+
+impl ToAuditRecord for SchemaRecord {
+    fn to_audit_record(&self) -> String {
+        serde_json::to_string_pretty(&self.0).unwrap_or_default()
+    }
+}
+
+/// The current schema content for the audit trail, if the schema exists.
+async fn schema_record(data: &Arc<SchemaRepository>, id: &str) -> Option<SchemaRecord> {
+    let schema = data.get(id.to_string()).await.ok()?;
+    schema.to_json_value().ok().map(SchemaRecord)
+}
+
 #[utoipa::path(context_path = "/schema/",
+    params(
+        ("force" = Option<bool>, Query, description = "Apply the schema even if it orphans registered action or resource sets")
+    ),
     responses(
         (status = OK),
+        (status = BAD_REQUEST, description = "Schema is invalid or reserved"),
+        (status = CONFLICT, body = SchemaConflict, description = "Schema update would orphan registered action or resource sets"),
     ),
     request_body = Value,
     security(
@@ -19,11 +57,40 @@ use std::sync::Arc;
 async fn post_schema(
     id: Path<String>,
     schema_json: Json<Value>,
+    options: Query<SchemaUpdateOptions>,
     data: Data<Arc<SchemaRepository>>,
+    usage: Data<Arc<SchemaUsageIndex>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
 ) -> Result<impl Responder> {
-    let schema = SchemaFragment::from_json_value(schema_json.into_inner())
-        .map_err(actix_web::error::ErrorInternalServerError)?;
+    if id.as_str() == ADMIN_SCHEMA_ID {
+        return Err(actix_web::error::ErrorBadRequest(format!(
+            "Schema {} is built in and cannot be replaced",
+            ADMIN_SCHEMA_ID
+        )));
+    }
+    let schema_json = schema_json.into_inner();
+    let catalog = SchemaCatalog::from_json(&schema_json).map_err(actix_web::error::ErrorBadRequest)?;
+    let orphans = usage.orphans(&id, &catalog).await;
+    if !orphans.is_empty() {
+        if !options.force {
+            return Err(SchemaConflict { orphans }.into());
+        }
+        warn!(
+            "Schema {} was forced to update, orphaning registrations: {:?}",
+            id, orphans
+        );
+    }
+
+    let after = SchemaRecord(schema_json.clone());
+    let schema = SchemaFragment::from_json_value(schema_json).map_err(actix_web::error::ErrorInternalServerError)?;
+    let before = schema_record(data.get_ref(), &id).await;
     data.upsert(id.to_string(), schema).await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Upsert, "schema", &id, &id)
+            .with_before(before.as_ref())
+            .with_after(Some(&after)),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
@@ -46,22 +113,70 @@ async fn get_schema(id: Path<String>, data: Data<Arc<SchemaRepository>>) -> Resu
 }
 
 #[utoipa::path(context_path = "/schema/",
+    params(
+        ("force" = Option<bool>, Query, description = "Delete the schema even if it orphans registered action or resource sets")
+    ),
     responses(
-        (status = OK)
+        (status = OK),
+        (status = CONFLICT, body = SchemaConflict, description = "Schema deletion would orphan registered action or resource sets"),
     ),
     security(
         ("internal" = [])
     )
 )]
 #[delete("{id}")]
-async fn delete_schema(id: Path<String>, data: Data<Arc<SchemaRepository>>) -> Result<impl Responder> {
+async fn delete_schema(
+    id: Path<String>,
+    options: Query<SchemaUpdateOptions>,
+    data: Data<Arc<SchemaRepository>>,
+    usage: Data<Arc<SchemaUsageIndex>>,
+    boxer_claims: ReqData<BoxerClaims>,
+    audit: Data<Arc<AuditTrail>>,
+) -> Result<impl Responder> {
+    // Every registration of a deleted schema is orphaned, as if the schema declared nothing
+    let orphans = usage.orphans(&id, &SchemaCatalog::default()).await;
+    if !orphans.is_empty() {
+        if !options.force {
+            return Err(SchemaConflict { orphans }.into());
+        }
+        warn!(
+            "Schema {} was forced to delete, orphaning registrations: {:?}",
+            id, orphans
+        );
+    }
+
+    let before = schema_record(data.get_ref(), &id).await;
     data.delete(id.to_string()).await?;
+    audit.record(
+        AuditTrailEvent::by(&boxer_claims, AuditOperation::Delete, "schema", &id, &id).with_before(before.as_ref()),
+    );
     Ok(HttpResponse::Ok().finish())
 }
 
-pub fn crud() -> impl HttpServiceFactory {
-    web::scope("/schema")
+#[utoipa::path(context_path = "/schema",
+    params(ListQuery),
+    responses(
+        (status = OK, body = ListPage<ListedItem<Value>>),
+        (status = BAD_REQUEST, description = "Invalid label selector")
+    ),
+    security(
+        ("internal" = [])
+    )
+)]
+#[get("")]
+async fn list_schemas(query: Query<ListQuery>, data: Data<Arc<SchemaListRepository>>) -> Result<impl Responder> {
+    let page = data.list(None, query.into_inner()).await?;
+    Ok(Json(page))
+}
+
+pub fn crud(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<SigningKeys>) -> impl HttpServiceFactory {
+    admin_api_scope("/schema", audit_writer, decryptor, services)
+}
+
+pub(crate) fn services(config: &mut ServiceConfig) {
+    config
         .service(post_schema)
         .service(get_schema)
-        .service(delete_schema)
+        .service(list_schemas)
+        .service(delete_schema);
 }
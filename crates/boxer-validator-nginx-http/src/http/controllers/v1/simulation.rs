@@ -0,0 +1,52 @@
+pub mod models;
+
+use crate::http::controllers::v1::simulation::models::SimulationRequest;
+use crate::http::middleware::admin_authorization::admin_api_scope;
+use crate::http::signing_keys::SigningKeys;
+use crate::services::policy_simulation::{CandidatePolicies, PolicySimulator, SimulationReport};
+use actix_web::dev::HttpServiceFactory;
+use actix_web::web::{Data, Json, Path, ServiceConfig};
+use actix_web::{Responder, Result, post};
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
+use std::sync::Arc;
+
+#[utoipa::path(context_path = "/simulation/",
+    responses(
+        (status = OK, body = SimulationReport),
+        (status = BAD_REQUEST, description = "Candidate policy set or schema is invalid, or the schema does not exist")
+    ),
+    request_body = SimulationRequest,
+    security(
+        ("internal" = [])
+    )
+)]
+#[post("{schema}")]
+async fn simulate_policy_set(
+    schema: Path<String>,
+    request: Json<SimulationRequest>,
+    schemas: Data<Arc<SchemaRepository>>,
+    simulator: Data<Arc<PolicySimulator>>,
+) -> Result<impl Responder> {
+    let request = request.into_inner();
+    let schema_json = match request.schema {
+        Some(schema_json) => schema_json,
+        None => schemas
+            .get(schema.to_string())
+            .await
+            .map_err(actix_web::error::ErrorBadRequest)?
+            .to_json_value()
+            .map_err(actix_web::error::ErrorInternalServerError)?,
+    };
+    let candidate =
+        CandidatePolicies::new(&schema, &schema_json, &request.policy).map_err(actix_web::error::ErrorBadRequest)?;
+    Ok(Json(simulator.simulate(&request.decision_log, candidate).await))
+}
+
+pub fn routes(audit_writer: Arc<dyn AuditWriter>, decryptor: Arc<SigningKeys>) -> impl HttpServiceFactory {
+    admin_api_scope("/simulation", audit_writer, decryptor, services)
+}
+
+pub(crate) fn services(config: &mut ServiceConfig) {
+    config.service(simulate_policy_set);
+}
@@ -0,0 +1,16 @@
+use serde::Deserialize;
+use serde_json::Value;
+use utoipa::ToSchema;
+
+#[derive(ToSchema, Deserialize)]
+#[schema(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationRequest {
+    /// Candidate policy set in the Cedar policy language.
+    pub policy: String,
+    /// Candidate schema fragment in the Cedar JSON format; the registered schema is used if omitted.
+    #[serde(default)]
+    pub schema: Option<Value>,
+    /// Captured decision log in the JSON-lines format.
+    pub decision_log: String,
+}
@@ -1,15 +1,30 @@
-use actix_web::error::ErrorUnauthorized;
+use crate::http::middleware::client_certificate::require_token_review_client;
+use crate::http::middleware::public_routes::allow_public_routes;
+use crate::http::middleware::review_timing::{ReviewReceived, mark_review_received};
+use crate::http::middleware::token_sources::find_token;
+use crate::http::original_request::OriginalRequest;
+use crate::http::signing_keys::SigningKeys;
+use crate::services::decision_log::{Decision, DecisionLog, ReviewedRequest, determining_policies};
+use crate::services::enforcement_mode::Enforcement;
+use crate::services::metrics::TokenReviewMetrics;
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
+use crate::services::repositories::lookup_trie::route_table::describe_request;
+use crate::services::review_stages::{ReviewStage, ReviewTrace};
+use crate::services::shadow_policies::ShadowEvaluator;
+use crate::services::unmatched_routes::{RouteFallback, UnmatchedRoutes};
+use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
+use actix_web::middleware::from_fn;
 use actix_web::web::{Data, ReqData};
 use actix_web::{HttpMessage, HttpRequest, HttpResponse, get, web};
 use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
 use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
 use boxer_core::http::middleware::audit::audit_scope::AuditScope;
 use boxer_core::services::audit::chained::audit_event::AuditEvent;
-use boxer_core::services::token_decryption_service::TokenDecryptionService;
 use boxer_core::services::validation_service::ValidationService;
-use boxer_core::services::validation_service::request_context::RequestContext;
+use boxer_core::services::validation_service::required_claims::RequiredClaims;
 use log::error;
 use std::sync::Arc;
+use std::time::Instant;
 
 #[utoipa::path(
     context_path = "/token",
@@ -19,29 +34,108 @@ use std::sync::Arc;
     )
 )]
 #[get("/review")]
+#[allow(clippy::too_many_arguments)]
 async fn token_review(
     boxer_claims: ReqData<BoxerClaims>,
-    request_context: RequestContext,
     cedar_validation_service: Data<Arc<dyn ValidationService<BoxerClaims>>>,
+    shadow_evaluator: Data<Arc<ShadowEvaluator>>,
+    enforcement: Data<Arc<Enforcement>>,
+    unmatched_routes: Data<Arc<UnmatchedRoutes>>,
+    decision_log: Data<Arc<DecisionLog>>,
+    metrics: Data<Arc<TokenReviewMetrics>>,
+    original_request: Data<Arc<OriginalRequest>>,
     http_request: HttpRequest,
 ) -> Result<HttpResponse, actix_web::Error> {
     let mut extensions = http_request.extensions_mut();
+    let received = extensions.get::<ReviewReceived>().copied();
     let event = extensions.get_mut::<AuditEvent>().ok_or_else(|| {
         error!("AuditEvent not found in request extensions");
         ErrorUnauthorized("No audit event found in request extensions")
     })?;
-    cedar_validation_service
-        .validate(boxer_claims.into_inner(), request_context, event)
+    let boxer_claims = boxer_claims.into_inner();
+    let schema = boxer_claims.get_validator_schema_id().clone();
+    let review = ReviewTrace::start(&schema);
+    if let Some(ReviewReceived(received)) = received {
+        review.record_since(ReviewStage::TokenDecryption, received);
+    }
+    let request_context = review
+        .measure(ReviewStage::RequestContext, async {
+            original_request.extract(&http_request)
+        })
         .await
-        .map_err(ErrorUnauthorized)?;
+        .inspect_err(|_| {
+            review.finish(Decision::Deny);
+        })?;
+    let mode = enforcement.mode(&schema).await;
+    let request = describe_request::<ActionSegment>(request_context.clone()).unwrap_or_default();
+    let unmatched = unmatched_routes.start(&schema, request.hostname.as_deref());
+    let reviewed = decision_log.is_enabled().then(|| ReviewedRequest {
+        principal: boxer_claims.get_principal().uid().to_string(),
+        claims: decision_log.claims_of(&boxer_claims),
+        token: decision_log.token_of(&boxer_claims),
+        url: original_request.url(http_request.head()),
+        schema: schema.clone(),
+        request,
+        action: None,
+        resource: None,
+        determining_policies: Vec::new(),
+        enforcement: mode,
+        fallback: None,
+    });
+    let started = Instant::now();
+    // Shadow policies are evaluated concurrently and never change the response
+    let shadow_review =
+        shadow_evaluator.evaluate(&schema, boxer_claims.clone(), request_context.clone(), event.clone());
+    let (result, shadow_result) = unmatched
+        .run(async {
+            tokio::join!(
+                review.validate(cedar_validation_service.validate(boxer_claims, request_context, event)),
+                shadow_review
+            )
+        })
+        .await;
+    let fallback = unmatched.fallback();
+    let result = unmatched_routes.finish(&schema, &unmatched, result);
+    // `allow` and `deny` replace the enforced decision, so there is nothing to compare the shadow decision with
+    let shadow_result = shadow_result.filter(|_| !matches!(fallback, Some(RouteFallback::Allow | RouteFallback::Deny)));
+    let latency = started.elapsed();
+    let decision = Decision::of(&result);
+    metrics.record_review(&schema, decision, &review.finish(decision));
+    let shadow = shadow_result.map(|shadow_result| shadow_evaluator.compare(&schema, &result, &shadow_result));
+    if let Some(mut reviewed) = reviewed {
+        reviewed.action = review.resolved(ReviewStage::ActionLookup);
+        reviewed.resource = review.resolved(ReviewStage::ResourceLookup);
+        reviewed.determining_policies = determining_policies(event);
+        reviewed.fallback = fallback;
+        decision_log.record(reviewed, &result, shadow, latency);
+    }
+    if let Err(denial) = result
+        && enforcement.rejects(&schema, mode, &denial)
+    {
+        return Err(if fallback == Some(RouteFallback::Deny) {
+            ErrorForbidden(denial)
+        } else {
+            ErrorUnauthorized(denial)
+        });
+    }
     Ok(HttpResponse::Ok().finish())
 }
 
 pub fn routes(
     audit_service: Arc<dyn AuditWriter>,
-    decryptor: Arc<TokenDecryptionService>,
+    decryptor: Arc<SigningKeys>,
 ) -> impl actix_web::dev::HttpServiceFactory {
     web::scope("/token")
-        .service(token_review)
-        .continue_audit_scope::<TokenDecryptionService>(audit_service, decryptor)
+        // Middlewares run in reverse order: public routes are allowed before the token is looked up and decrypted,
+        // and the time spent decrypting the token is measured from the start of the inner scope
+        .wrap(from_fn(find_token))
+        .wrap(from_fn(mark_review_received))
+        .wrap(from_fn(allow_public_routes))
+        // Client certificates are checked before anything else, including for public routes
+        .wrap(from_fn(require_token_review_client))
+        .service(
+            web::scope("")
+                .service(token_review)
+                .continue_audit_scope::<SigningKeys>(audit_service, decryptor),
+        )
 }
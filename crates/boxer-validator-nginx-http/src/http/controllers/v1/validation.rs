@@ -0,0 +1,118 @@
+use crate::services::schema_catalog::{OrphanedRegistration, SchemaCatalog};
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
+use serde::Serialize;
+use std::fmt::{Display, Formatter};
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+/// A single problem found in a registration request.
+#[derive(ToSchema, Serialize, Debug)]
+pub struct Violation {
+    /// Path to the offending field, e.g. `routes[1].routeTemplate`.
+    pub field: String,
+    pub message: String,
+}
+
+/// Returned with `400 Bad Request` when a registration request fails validation.
+#[derive(ToSchema, Serialize, Debug, Default)]
+pub struct InvalidRegistration {
+    pub violations: Vec<Violation>,
+}
+
+impl InvalidRegistration {
+    pub fn push(&mut self, field: String, message: impl ToString) {
+        self.violations.push(Violation {
+            field,
+            message: message.to_string(),
+        });
+    }
+
+    pub fn check_hostname(&mut self, hostname: &str) {
+        if hostname.trim().is_empty() {
+            self.push("hostname".to_string(), "Hostname must not be empty");
+        } else if hostname.contains(['/', ' ']) {
+            self.push("hostname".to_string(), "Hostname must not contain '/' or spaces");
+        }
+    }
+
+    pub fn into_result(self) -> Result<(), InvalidRegistration> {
+        if self.violations.is_empty() { Ok(()) } else { Err(self) }
+    }
+}
+
+impl Display for InvalidRegistration {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Invalid registration: ")?;
+        let messages: Vec<String> = self
+            .violations
+            .iter()
+            .map(|v| format!("{}: {}", v.field, v.message))
+            .collect();
+        write!(f, "{}", messages.join("; "))
+    }
+}
+
+impl ResponseError for InvalidRegistration {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::BAD_REQUEST
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::BadRequest().json(self)
+    }
+}
+
+/// Returned with `409 Conflict` when a schema update or deletion would orphan existing registrations.
+#[derive(ToSchema, Serialize, Debug)]
+pub struct SchemaConflict {
+    pub orphans: Vec<OrphanedRegistration>,
+}
+
+impl Display for SchemaConflict {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Schema change would orphan {} registered route(s)",
+            self.orphans.len()
+        )
+    }
+}
+
+impl ResponseError for SchemaConflict {
+    fn status_code(&self) -> StatusCode {
+        StatusCode::CONFLICT
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::Conflict().json(self)
+    }
+}
+
+/// Validates a registration request before it is written to the backend.
+pub trait ValidateRegistration {
+    fn validate(&self) -> Result<(), InvalidRegistration>;
+
+    /// Checks that every UID in the request is declared in the schema.
+    fn validate_against(&self, catalog: &SchemaCatalog) -> Result<(), InvalidRegistration>;
+}
+
+/// Loads the schema a registration is bound to, reporting a missing schema as a violation.
+pub async fn load_schema_catalog(
+    schema: &str,
+    repository: &Arc<SchemaRepository>,
+) -> Result<SchemaCatalog, InvalidRegistration> {
+    let mut errors = InvalidRegistration::default();
+    let catalog = match repository.get(schema.to_string()).await {
+        Ok(fragment) => fragment
+            .to_json_value()
+            .map_err(anyhow::Error::from)
+            .and_then(|json| SchemaCatalog::from_json(&json)),
+        Err(e) => Err(anyhow::anyhow!("Schema {} could not be loaded: {}", schema, e)),
+    };
+    catalog.map_err(|e| {
+        errors.push("schema".to_string(), e);
+        errors
+    })
+}
@@ -8,25 +8,17 @@ use anyhow::bail;
 impl TryFrom<&HeaderValue> for BoxerToken {
     type Error = anyhow::Error;
 
-    // Compiler will complain that `return Err` statements in this code are unreachable,
-    // but this is not true. See test cases below.
-    #[allow(unreachable_code)]
+    /// Accepts `Bearer <token>` with a case-insensitive scheme and any amount of whitespace around the token.
     fn try_from(value: &HeaderValue) -> Result<Self, Self::Error> {
-        match value.to_str() {
-            Ok(string_value) => {
-                let tokens = string_value.split(" ").collect::<Vec<&str>>();
-
-                if tokens.len() != 2 {
-                    return Err(bail!("Invalid token format"));
-                }
-
-                if tokens[0] != "Bearer" {
-                    return Err(bail!("Invalid token format"));
-                }
-
-                Ok(BoxerToken::from(tokens[1].to_owned()))
+        let Ok(string_value) = value.to_str() else {
+            bail!("Invalid token format");
+        };
+        let mut parts = string_value.split_whitespace();
+        match (parts.next(), parts.next(), parts.next()) {
+            (Some(scheme), Some(token), None) if scheme.eq_ignore_ascii_case("Bearer") => {
+                Ok(BoxerToken::from(token.to_owned()))
             }
-            Err(_) => bail!("Invalid token format"),
+            _ => bail!("Invalid token format"),
         }
     }
 }
@@ -2,8 +2,11 @@ use super::*;
 use rstest::rstest;
 
 #[rstest]
-fn test_parsing_valid_token() {
-    let header = HeaderValue::from_static("Bearer token");
+#[case("Bearer token")]
+#[case("bearer token")]
+#[case("Bearer  token ")]
+fn test_parsing_valid_token(#[case] value: &'static str) {
+    let header = HeaderValue::from_static(value);
     let token = BoxerToken::try_from(&header).unwrap();
     let string_token: String = token.into();
     assert_eq!(string_token, "token".to_string());
@@ -15,6 +18,8 @@ fn test_parsing_valid_token() {
 #[case("My suer cool token")]
 #[case("")]
 #[case("Bearer")]
+#[case("Basic token")]
+#[case("Bearer token extra")]
 fn test_parsing_invalid_token(#[case] token: &str) {
     let header = HeaderValue::from_str(token).unwrap();
     let token = BoxerToken::try_from(&header);
@@ -1,8 +1,8 @@
+use crate::services::readiness::{Readiness, ReadinessReport};
 use actix_web::dev::HttpServiceFactory;
-use actix_web::get;
 use actix_web::web;
+use actix_web::{HttpResponse, get};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 
 #[cfg(test)]
 mod tests;
@@ -17,18 +17,20 @@ pub async fn get_health() -> actix_web::Result<String> {
     Ok("ok".into())
 }
 
+/// Reports the live status of every backend watcher.
 #[utoipa::path(
     context_path = "/health",
-    responses((status = OK, body = String)),
-    responses((status = StatusCode::SERVICE_UNAVAILABLE, description = "Service not ready")),
+    responses((status = OK, body = ReadinessReport)),
+    responses((status = StatusCode::SERVICE_UNAVAILABLE, body = ReadinessReport, description = "Service not ready")),
 )]
 #[get("/ready")]
-pub async fn get_health_probe(readiness_state: web::Data<Arc<AtomicBool>>) -> actix_web::Result<String> {
-    if readiness_state.load(Ordering::Acquire) {
-        return Ok("ok".into());
+pub async fn get_health_probe(readiness: web::Data<Arc<Readiness>>) -> HttpResponse {
+    let report = readiness.report();
+    if report.ready {
+        HttpResponse::Ok().json(report)
+    } else {
+        HttpResponse::ServiceUnavailable().json(report)
     }
-
-    Err(actix_web::error::ErrorServiceUnavailable("Service not ready"))
 }
 
 pub fn urls() -> impl HttpServiceFactory {
@@ -1,13 +1,23 @@
+use crate::services::readiness::Readiness;
 use actix_web::web;
 use actix_web::{App, http::StatusCode, test};
+use serde_json::Value;
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use std::time::Duration;
+
+fn readiness(synced: bool) -> web::Data<Arc<Readiness>> {
+    let readiness = Readiness::new(Duration::from_secs(60));
+    let status = readiness.register("action_lookup");
+    if synced {
+        status.mark_synced();
+    }
+    web::Data::new(Arc::new(readiness))
+}
 
 #[actix_web::test]
 async fn test_health_returns_ok() {
     // The purpose of this test is API documentation, not so much covering the business logic
-    let readiness_state = web::Data::new(Arc::new(AtomicBool::new(true)));
-    let app = test::init_service(App::new().app_data(readiness_state).service(super::urls())).await;
+    let app = test::init_service(App::new().app_data(readiness(true)).service(super::urls())).await;
     let req = test::TestRequest::get().uri("/health").to_request();
     let resp = test::call_service(&app, req).await;
 
@@ -16,21 +26,26 @@ async fn test_health_returns_ok() {
 
 #[actix_web::test]
 async fn test_health_probe_returns_service_unavailable_when_not_ready() {
-    let readiness_state = web::Data::new(Arc::new(AtomicBool::new(false)));
-    let app = test::init_service(App::new().app_data(readiness_state).service(super::urls())).await;
+    let app = test::init_service(App::new().app_data(readiness(false)).service(super::urls())).await;
 
     let req = test::TestRequest::get().uri("/health/ready").to_request();
     let resp = test::call_service(&app, req).await;
 
     assert_eq!(resp.status(), StatusCode::SERVICE_UNAVAILABLE);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["ready"], false);
+    assert_eq!(body["components"][0]["name"], "action_lookup");
+    assert_eq!(body["components"][0]["synced"], false);
 }
 
 #[actix_web::test]
 async fn test_health_probe_returns_ok_when_ready() {
-    let readiness_state = web::Data::new(Arc::new(AtomicBool::new(true)));
-    let app = test::init_service(App::new().app_data(readiness_state).service(super::urls())).await;
+    let app = test::init_service(App::new().app_data(readiness(true)).service(super::urls())).await;
     let req = test::TestRequest::get().uri("/health/ready").to_request();
     let resp = test::call_service(&app, req).await;
 
     assert_eq!(resp.status(), StatusCode::OK);
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(body["ready"], true);
+    assert_eq!(body["components"][0]["ready"], true);
 }
@@ -0,0 +1,22 @@
+use crate::services::prometheus::PrometheusMetrics;
+use actix_web::dev::HttpServiceFactory;
+use actix_web::error::ErrorInternalServerError;
+use actix_web::{HttpResponse, get, web};
+use std::sync::Arc;
+
+#[cfg(test)]
+mod tests;
+
+/// Exposes the validator metrics in the Prometheus text format. Only served when `prometheus.enabled` is set.
+#[utoipa::path(
+    responses((status = OK, body = String, content_type = "text/plain")),
+)]
+#[get("/metrics")]
+pub async fn get_metrics(metrics: web::Data<Arc<PrometheusMetrics>>) -> actix_web::Result<HttpResponse> {
+    let (content_type, body) = metrics.render().map_err(ErrorInternalServerError)?;
+    Ok(HttpResponse::Ok().content_type(content_type).body(body))
+}
+
+pub fn urls() -> impl HttpServiceFactory {
+    get_metrics
+}
@@ -0,0 +1,21 @@
+use crate::services::prometheus::PrometheusMetrics;
+use actix_web::http::header::CONTENT_TYPE;
+use actix_web::web;
+use actix_web::{App, http::StatusCode, test};
+use std::sync::Arc;
+
+#[actix_web::test]
+async fn test_metrics_returns_text_format() {
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(Arc::new(PrometheusMetrics::default())))
+            .service(super::urls()),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+    let resp = test::call_service(&app, req).await;
+
+    assert_eq!(resp.status(), StatusCode::OK);
+    let content_type = resp.headers().get(CONTENT_TYPE).unwrap().to_str().unwrap();
+    assert!(content_type.starts_with("text/plain"));
+}
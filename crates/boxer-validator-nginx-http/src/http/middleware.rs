@@ -0,0 +1,5 @@
+pub mod admin_authorization;
+pub mod client_certificate;
+pub mod public_routes;
+pub mod review_timing;
+pub mod token_sources;
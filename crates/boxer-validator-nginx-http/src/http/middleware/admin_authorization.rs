@@ -0,0 +1,98 @@
+#[cfg(test)]
+mod tests;
+
+use crate::http::signing_keys::SigningKeys;
+use crate::services::admin_authorization::AdminAuthorizer;
+use actix_web::body::MessageBody;
+use actix_web::dev::{HttpServiceFactory, ServiceRequest, ServiceResponse};
+use actix_web::error::{ErrorForbidden, ErrorInternalServerError, ErrorUnauthorized};
+use actix_web::middleware::{Next, from_fn};
+use actix_web::web::{Data, ServiceConfig};
+use actix_web::{HttpMessage, HttpRequest, web};
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::http::middleware::audit::audit_scope::AuditScope;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use log::{error, warn};
+use std::sync::Arc;
+
+/// The authorizer, token claims, audit event and request context of an admin API call.
+/// The audit event is taken out of the request extensions and must be put back once the call is authorized.
+fn admin_call(
+    req: &HttpRequest,
+) -> Result<(Arc<AdminAuthorizer>, BoxerClaims, AuditEvent, RequestContext), actix_web::Error> {
+    let authorizer = req.app_data::<Data<Arc<AdminAuthorizer>>>().cloned().ok_or_else(|| {
+        error!("AdminAuthorizer not found in application data");
+        ErrorInternalServerError("Admin authorization is not configured")
+    })?;
+    let boxer_claims = req
+        .extensions()
+        .get::<BoxerClaims>()
+        .cloned()
+        .ok_or_else(|| ErrorUnauthorized("No internal token claims found in request extensions"))?;
+    let event = req.extensions_mut().remove::<AuditEvent>().ok_or_else(|| {
+        error!("AuditEvent not found in request extensions");
+        ErrorUnauthorized("No audit event found in request extensions")
+    })?;
+
+    let url = {
+        let connection_info = req.connection_info();
+        format!("{}://{}{}", connection_info.scheme(), connection_info.host(), req.uri())
+    };
+    let request_context = RequestContext::new(url, req.method().to_string());
+    Ok((authorizer.get_ref().clone(), boxer_claims, event, request_context))
+}
+
+/// Authorizes admin API calls against the `boxer-admin` policy set.
+/// Must be wrapped inside the internal token scope, which provides the claims and the audit event.
+pub async fn authorize_admin(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    let (authorizer, boxer_claims, mut event, request_context) = admin_call(req.request())?;
+    let result = authorizer.authorize(boxer_claims, request_context, &mut event).await;
+    req.extensions_mut().insert(event);
+
+    if let Err(e) = result {
+        warn!("Admin API call {} {} was denied: {}", req.method(), req.path(), e);
+        return Err(ErrorForbidden(e));
+    }
+    next.call(req).await
+}
+
+/// Authorizes the `publish_action_set` action for an admin API call that registers public routes.
+pub async fn authorize_publication(req: &HttpRequest) -> Result<(), actix_web::Error> {
+    let (authorizer, boxer_claims, mut event, request_context) = admin_call(req)?;
+    let result = authorizer
+        .authorize_publication(boxer_claims, request_context, &mut event)
+        .await;
+    req.extensions_mut().insert(event);
+
+    result.map_err(|e| {
+        warn!(
+            "Publishing public routes with {} {} was denied: {}",
+            req.method(),
+            req.path(),
+            e
+        );
+        ErrorForbidden(e)
+    })
+}
+
+/// Mounts admin API services under `path`, behind the internal token scope and `authorize_admin`.
+pub fn admin_api_scope(
+    path: &str,
+    audit_writer: Arc<dyn AuditWriter>,
+    decryptor: Arc<SigningKeys>,
+    services: fn(&mut ServiceConfig),
+) -> impl HttpServiceFactory {
+    web::scope(path)
+        .service(authorized(services))
+        .continue_audit_scope::<SigningKeys>(audit_writer, decryptor)
+}
+
+/// Wraps services in `authorize_admin`. Authorization runs inside the token scope, once the claims are available.
+pub(crate) fn authorized(services: fn(&mut ServiceConfig)) -> impl HttpServiceFactory {
+    web::scope("").wrap(from_fn(authorize_admin)).configure(services)
+}
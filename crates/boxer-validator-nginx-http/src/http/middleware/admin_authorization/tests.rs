@@ -0,0 +1,54 @@
+use super::*;
+use crate::http::controllers::v1::action_set;
+use actix_web::test::{TestRequest, init_service, try_call_service};
+use actix_web::{App, http::StatusCode};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use boxer_core::services::validation_service::ValidationService;
+use serde_json::json;
+use test_case::test_case;
+
+struct DenyAll;
+
+#[async_trait]
+impl ValidationService<BoxerClaims> for DenyAll {
+    async fn validate(
+        &self,
+        _claims: BoxerClaims,
+        _context: RequestContext,
+        _event: &mut AuditEvent,
+    ) -> anyhow::Result<()> {
+        Err(anyhow!("No policy permits the request"))
+    }
+}
+
+/// Stands in for the internal token scope, which provides the claims and the audit event.
+async fn token_scope(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    req.extensions_mut().insert(BoxerClaims::default());
+    req.extensions_mut().insert(AuditEvent::default());
+    next.call(req).await
+}
+
+#[test_case(TestRequest::get().uri("/api/v1/action_set/photo-app/actions"); "read")]
+#[test_case(TestRequest::get().uri("/api/v1/action_set/photo-app"); "list")]
+#[test_case(TestRequest::post().uri("/api/v1/action_set/photo-app/actions").set_json(json!({ "hostname": "www.example.com", "routes": [] })); "write")]
+#[test_case(TestRequest::delete().uri("/api/v1/action_set/photo-app/actions"); "delete")]
+#[actix_web::test]
+async fn test_denied_principal_is_forbidden(request: TestRequest) {
+    let authorizer = AdminAuthorizer::with_validation_services(Arc::new(DenyAll), Arc::new(DenyAll));
+    let app = init_service(
+        App::new().app_data(Data::new(Arc::new(authorizer))).service(
+            web::scope("/api/v1/action_set")
+                .wrap(from_fn(token_scope))
+                .service(authorized(action_set::services)),
+        ),
+    )
+    .await;
+
+    let error = try_call_service(&app, request.to_request()).await.err().unwrap();
+
+    assert_eq!(error.as_response_error().status_code(), StatusCode::FORBIDDEN);
+}
@@ -0,0 +1,62 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::tls::{ClientScope, PeerCertificates, Tls};
+use actix_tls::accept::rustls_0_23::TlsStream;
+use actix_web::body::MessageBody;
+use actix_web::dev::{Extensions, ServiceRequest, ServiceResponse};
+use actix_web::error::{ErrorForbidden, ErrorUnauthorized};
+use actix_web::middleware::Next;
+use actix_web::rt::net::TcpStream;
+use actix_web::web::Data;
+use log::debug;
+use std::any::Any;
+use std::sync::Arc;
+
+/// Keeps the client certificates of a TLS connection, so scopes can check which CA issued them.
+/// Pass to `HttpServer::on_connect`.
+pub fn store_peer_certificates(connection: &dyn Any, data: &mut Extensions) {
+    if let Some(stream) = connection.downcast_ref::<TlsStream<TcpStream>>() {
+        let (_, session) = stream.get_ref();
+        if let Some(certificates) = session.peer_certificates() {
+            data.insert(PeerCertificates(
+                certificates
+                    .iter()
+                    .map(|certificate| certificate.clone().into_owned())
+                    .collect(),
+            ));
+        }
+    }
+}
+
+fn check_client(req: &ServiceRequest, scope: ClientScope) -> Result<(), actix_web::Error> {
+    let Some(client_ca) = req.app_data::<Data<Arc<Tls>>>().and_then(|tls| tls.client_ca(scope)) else {
+        return Ok(());
+    };
+    match req.conn_data::<PeerCertificates>() {
+        Some(certificates) if client_ca.verify(&certificates.0) => Ok(()),
+        Some(_) => {
+            debug!("Rejecting a client certificate not issued by the {:?} client CA", scope);
+            Err(ErrorForbidden("The client certificate is not accepted for this API"))
+        }
+        None => Err(ErrorUnauthorized("A client certificate is required for this API")),
+    }
+}
+
+/// Requires a client certificate issued by the token review client CA, if one is configured.
+pub async fn require_token_review_client(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    check_client(&req, ClientScope::TokenReview)?;
+    next.call(req).await
+}
+
+/// Requires a client certificate issued by the admin API client CA, if one is configured.
+pub async fn require_admin_client(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    check_client(&req, ClientScope::AdminApi)?;
+    next.call(req).await
+}
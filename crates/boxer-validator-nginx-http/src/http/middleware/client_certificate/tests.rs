@@ -0,0 +1,118 @@
+use super::*;
+use crate::services::configuration::models::{ClientCaSettings, TlsSettings};
+use actix_web::dev::Server;
+use actix_web::middleware::from_fn;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use reqwest::StatusCode;
+use rustls::ClientConfig;
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use std::net::{SocketAddr, TcpListener};
+use std::time::Duration;
+use test_case::test_case;
+
+fn testdata(name: &str) -> String {
+    format!("{}/src/services/tls/testdata/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn tls() -> Arc<Tls> {
+    let settings = TlsSettings {
+        certificate: testdata("server.pem"),
+        private_key: testdata("server.key"),
+        client_ca: ClientCaSettings {
+            token_review: Some(testdata("ingress-ca.pem")),
+            admin_api: Some(testdata("operators-ca.pem")),
+        },
+        reload_interval: Duration::from_secs(60).into(),
+    };
+    Arc::new(Tls::load(&settings).unwrap())
+}
+
+/// Serves `/token` and `/admin` over TLS, each scope requiring its own client CA.
+fn server(listener: TcpListener) -> Server {
+    let tls = tls();
+    let server_config = tls.server_config().unwrap();
+    HttpServer::new(move || {
+        App::new()
+            .app_data(Data::new(tls.clone()))
+            .service(
+                web::scope("/token")
+                    .wrap(from_fn(require_token_review_client))
+                    .route("", web::get().to(HttpResponse::Ok)),
+            )
+            .service(
+                web::scope("/admin")
+                    .wrap(from_fn(require_admin_client))
+                    .route("", web::get().to(HttpResponse::Ok)),
+            )
+    })
+    .on_connect(store_peer_certificates)
+    .disable_signals()
+    .workers(1)
+    .listen_rustls_0_23(listener, server_config)
+    .unwrap()
+    .run()
+}
+
+/// A client trusting the test server, presenting the named client certificate if any.
+fn client(certificate: Option<&str>) -> reqwest::Client {
+    let mut roots = rustls::RootCertStore::empty();
+    roots
+        .add(CertificateDer::from_pem_file(testdata("server-ca.pem")).unwrap())
+        .unwrap();
+    let builder = ClientConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots);
+    let config = match certificate {
+        Some(name) => builder
+            .with_client_auth_cert(
+                CertificateDer::pem_file_iter(testdata(&format!("{}.pem", name)))
+                    .unwrap()
+                    .collect::<Result<_, _>>()
+                    .unwrap(),
+                PrivateKeyDer::from_pem_file(testdata(&format!("{}.key", name))).unwrap(),
+            )
+            .unwrap(),
+        None => builder.with_no_client_auth(),
+    };
+    reqwest::Client::builder()
+        .tls_backend_preconfigured(config)
+        .build()
+        .unwrap()
+}
+
+async fn get(certificate: Option<&str>, path: &str) -> StatusCode {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address: SocketAddr = listener.local_addr().unwrap();
+    let server = server(listener);
+    let handle = server.handle();
+    let running = tokio::spawn(server);
+
+    let status = client(certificate)
+        .get(format!("https://localhost:{}{}", address.port(), path))
+        .send()
+        .await
+        .unwrap()
+        .status();
+
+    handle.stop(true).await;
+    running.await.unwrap().unwrap();
+    status
+}
+
+#[test_case(Some("ingress-client") => StatusCode::OK; "ingress client")]
+#[test_case(Some("operator-client") => StatusCode::FORBIDDEN; "operator client")]
+#[test_case(None => StatusCode::UNAUTHORIZED; "no client certificate")]
+#[actix_web::test]
+async fn test_token_review_requires_the_ingress_client_ca(certificate: Option<&str>) -> StatusCode {
+    get(certificate, "/token").await
+}
+
+#[test_case(Some("operator-client") => StatusCode::OK; "operator client")]
+#[test_case(Some("ingress-client") => StatusCode::FORBIDDEN; "ingress client")]
+#[test_case(None => StatusCode::UNAUTHORIZED; "no client certificate")]
+#[actix_web::test]
+async fn test_admin_api_requires_the_operators_client_ca(certificate: Option<&str>) -> StatusCode {
+    get(certificate, "/admin").await
+}
@@ -0,0 +1,28 @@
+use crate::http::original_request::OriginalRequest;
+use crate::services::repositories::action_repository::public_routes::PublicRouteRepository;
+use actix_web::HttpResponse;
+use actix_web::body::{EitherBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use log::debug;
+use std::sync::Arc;
+
+/// Allows token reviews of requests whose most specific matching action route is public.
+/// Must wrap the internal token scope, so public requests do not need a token.
+pub async fn allow_public_routes(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<EitherBody<impl MessageBody>>, actix_web::Error> {
+    let public_routes = req.app_data::<Data<Arc<PublicRouteRepository>>>().cloned();
+    let original_request = req.app_data::<Data<Arc<OriginalRequest>>>().cloned();
+    if let Some(public_routes) = public_routes
+        && let Some(original_request) = original_request
+        && let Ok(request_context) = original_request.extract(req.request())
+        && public_routes.is_public(request_context).await
+    {
+        debug!("Allowing request to a public route without a token");
+        return Ok(req.into_response(HttpResponse::Ok().finish()).map_into_right_body());
+    }
+    next.call(req).await.map(ServiceResponse::map_into_left_body)
+}
@@ -0,0 +1,19 @@
+use crate::services::review_stages::StageStart;
+use actix_web::HttpMessage;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::middleware::Next;
+
+/// Time at which a token review entered the token scope, before the token was decrypted.
+#[derive(Debug, Clone, Copy)]
+pub struct ReviewReceived(pub StageStart);
+
+/// Marks when the request entered the token scope, so the token review can report the time spent decrypting the token.
+/// Must wrap the internal token scope.
+pub async fn mark_review_received(
+    req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    req.extensions_mut().insert(ReviewReceived(StageStart::now()));
+    next.call(req).await
+}
@@ -0,0 +1,31 @@
+use crate::http::original_request::OriginalRequest;
+use crate::http::token_sources::TokenSources;
+use actix_web::body::MessageBody;
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::error::ErrorUnauthorized;
+use actix_web::http::header::{AUTHORIZATION, HeaderValue};
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use log::debug;
+use std::sync::Arc;
+
+/// Moves the token from the first configured source that has one into the `Authorization` header.
+/// Must wrap the internal token scope, so the token is decrypted from the header.
+pub async fn find_token(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody>,
+) -> Result<ServiceResponse<impl MessageBody>, actix_web::Error> {
+    if let Some(token_sources) = req.app_data::<Data<Arc<TokenSources>>>().cloned() {
+        let original_url = req
+            .app_data::<Data<Arc<OriginalRequest>>>()
+            .and_then(|original_request| original_request.url(req.head()));
+        let token = token_sources.find(req.head(), original_url.as_deref()).map_err(|e| {
+            debug!("{}", e);
+            ErrorUnauthorized(e)
+        })?;
+        let header = HeaderValue::from_str(&format!("Bearer {}", token))
+            .map_err(|_| ErrorUnauthorized("Invalid token format"))?;
+        req.headers_mut().insert(AUTHORIZATION, header);
+    }
+    next.call(req).await
+}
@@ -1,4 +1,10 @@
 pub mod controllers;
 pub mod conversions;
 pub mod health;
+pub mod metrics;
+pub mod middleware;
 pub mod openapi;
+pub mod original_request;
+pub mod plane;
+pub mod signing_keys;
+pub mod token_sources;
@@ -1,12 +1,14 @@
 use crate::http::controllers::v1::ApiV1;
 use crate::http::health;
+use crate::http::metrics;
 use utoipa::OpenApi;
 
 #[derive(OpenApi)]
 #[openapi(
     paths(
         health::get_health,
-        health::get_health_probe
+        health::get_health_probe,
+        metrics::get_metrics
     ),
     nest(
         (path = "/api/v1", api = ApiV1)
@@ -0,0 +1,77 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::RequestHeaderSettings;
+use actix_web::HttpRequest;
+use actix_web::dev::RequestHead;
+use actix_web::error::{ErrorBadRequest, ErrorForbidden};
+use actix_web::http::header::HeaderName;
+use anyhow::{Result, anyhow};
+use boxer_core::services::validation_service::request_context::RequestContext;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::str::FromStr;
+
+/// Reads the request under review from the headers set by the ingress controller.
+pub struct OriginalRequest {
+    url_header: HeaderName,
+    method_header: HeaderName,
+    trusted_proxies: Vec<IpNet>,
+}
+
+fn parse_proxy(proxy: &str) -> Result<IpNet> {
+    IpNet::from_str(proxy)
+        .or_else(|_| IpAddr::from_str(proxy).map(IpNet::from))
+        .map_err(|_| anyhow!("Invalid trusted proxy address: {}", proxy))
+}
+
+impl OriginalRequest {
+    pub fn new(settings: &RequestHeaderSettings) -> Result<Self> {
+        Ok(OriginalRequest {
+            url_header: HeaderName::from_str(&settings.original_url)?,
+            method_header: HeaderName::from_str(&settings.original_method)?,
+            trusted_proxies: settings
+                .trusted_proxies
+                .iter()
+                .map(|proxy| parse_proxy(proxy))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    fn is_trusted(&self, head: &RequestHead) -> bool {
+        if self.trusted_proxies.is_empty() {
+            return true;
+        }
+        head.peer_addr
+            .is_some_and(|peer| self.trusted_proxies.iter().any(|proxy| proxy.contains(&peer.ip())))
+    }
+
+    fn header(&self, head: &RequestHead, name: &HeaderName) -> Result<String, actix_web::Error> {
+        head.headers()
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .map(str::to_string)
+            .ok_or_else(|| ErrorBadRequest(format!("Missing or invalid header: {}", name)))
+    }
+
+    /// The URL of the request under review, if it was sent by a trusted proxy.
+    pub fn url(&self, head: &RequestHead) -> Option<String> {
+        if !self.is_trusted(head) {
+            return None;
+        }
+        self.header(head, &self.url_header).ok()
+    }
+
+    pub fn extract(&self, req: &HttpRequest) -> Result<RequestContext, actix_web::Error> {
+        let head = req.head();
+        if !self.is_trusted(head) {
+            return Err(ErrorForbidden(
+                "The original request headers are not accepted from this peer",
+            ));
+        }
+        Ok(RequestContext::new(
+            self.header(head, &self.url_header)?,
+            self.header(head, &self.method_header)?,
+        ))
+    }
+}
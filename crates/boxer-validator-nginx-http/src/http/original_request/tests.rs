@@ -0,0 +1,63 @@
+use super::*;
+use actix_web::test::TestRequest;
+use std::net::SocketAddr;
+use test_case::test_case;
+
+fn settings(trusted_proxies: &[&str]) -> RequestHeaderSettings {
+    RequestHeaderSettings {
+        original_url: "X-Forwarded-Original-Url".to_string(),
+        original_method: "X-Forwarded-Original-Method".to_string(),
+        trusted_proxies: trusted_proxies.iter().map(|proxy| proxy.to_string()).collect(),
+    }
+}
+
+fn request(peer: &str) -> HttpRequest {
+    TestRequest::default()
+        .peer_addr(SocketAddr::from_str(peer).unwrap())
+        .insert_header(("X-Forwarded-Original-Url", "https://www.example.com/photos/1"))
+        .insert_header(("X-Forwarded-Original-Method", "GET"))
+        .to_http_request()
+}
+
+#[test]
+fn test_extract_reads_configured_headers() {
+    let original_request = OriginalRequest::new(&settings(&[])).unwrap();
+
+    let request_context = original_request.extract(&request("192.168.1.10:40000")).unwrap();
+    let segments: Vec<boxer_core::services::validation_service::request_segment::RequestSegment> =
+        request_context.try_into().unwrap();
+
+    assert!(!segments.is_empty());
+    assert_eq!(
+        original_request.url(request("192.168.1.10:40000").head()).as_deref(),
+        Some("https://www.example.com/photos/1")
+    );
+}
+
+#[test]
+fn test_extract_rejects_missing_header() {
+    let original_request = OriginalRequest::new(&RequestHeaderSettings::default()).unwrap();
+
+    let result = original_request.extract(&request("192.168.1.10:40000"));
+
+    assert!(result.is_err());
+}
+
+#[test_case(&["10.0.0.0/8"], "10.1.2.3:40000" => true; "peer in trusted range")]
+#[test_case(&["10.0.0.1"], "10.0.0.1:40000" => true; "trusted address")]
+#[test_case(&["10.0.0.0/8", "fd00::/8"], "[fd00::1]:40000" => true; "trusted ipv6 range")]
+#[test_case(&["10.0.0.0/8"], "192.168.1.10:40000" => false; "untrusted peer")]
+fn test_extract_honors_trusted_proxies(trusted_proxies: &[&str], peer: &str) -> bool {
+    let original_request = OriginalRequest::new(&settings(trusted_proxies)).unwrap();
+
+    let trusted = original_request.extract(&request(peer)).is_ok();
+
+    assert_eq!(original_request.url(request(peer).head()).is_some(), trusted);
+    trusted
+}
+
+#[test_case("not an address"; "invalid proxy")]
+#[test_case("10.0.0.0/33"; "invalid prefix")]
+fn test_new_rejects_invalid_proxy(proxy: &str) {
+    assert!(OriginalRequest::new(&settings(&[proxy])).is_err());
+}
@@ -0,0 +1,38 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::{AdminListener, MetricsPlane};
+
+/// Endpoints served by a listener, next to health which every listener serves.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Plane {
+    /// Token review.
+    Data,
+    /// Admin API and Swagger UI.
+    Admin,
+    /// Both, on a single listener.
+    All,
+}
+
+impl Plane {
+    pub fn serves_data(self) -> bool {
+        self != Plane::Admin
+    }
+
+    pub fn serves_admin(self) -> bool {
+        self != Plane::Data
+    }
+
+    /// The plane of the listener that serves `/metrics`.
+    pub fn of_metrics(metrics: MetricsPlane, admin_listener: &AdminListener) -> Plane {
+        match (metrics, admin_listener) {
+            (MetricsPlane::Admin, AdminListener::Separate { .. }) => Plane::Admin,
+            _ => Plane::Data,
+        }
+    }
+
+    /// Returns true if this listener serves the endpoints of the plane.
+    pub fn serves(self, plane: Plane) -> bool {
+        self == Plane::All || self == plane
+    }
+}
@@ -0,0 +1,26 @@
+use super::*;
+use test_case::test_case;
+
+fn separate() -> AdminListener {
+    AdminListener::Separate {
+        listen_address: "127.0.0.1:8081".parse().unwrap(),
+        workers: None,
+    }
+}
+
+#[test_case(MetricsPlane::Admin, separate() => Plane::Admin; "admin listener")]
+#[test_case(MetricsPlane::Admin, AdminListener::Shared => Plane::Data; "shared admin listener")]
+#[test_case(MetricsPlane::Admin, AdminListener::Disabled => Plane::Data; "disabled admin listener")]
+#[test_case(MetricsPlane::Data, separate() => Plane::Data; "data listener")]
+fn test_metrics_plane(metrics: MetricsPlane, admin_listener: AdminListener) -> Plane {
+    Plane::of_metrics(metrics, &admin_listener)
+}
+
+#[test_case(Plane::All, Plane::Admin => true; "shared listener serves admin")]
+#[test_case(Plane::All, Plane::Data => true; "shared listener serves data")]
+#[test_case(Plane::Admin, Plane::Admin => true; "admin listener serves admin")]
+#[test_case(Plane::Admin, Plane::Data => false; "admin listener does not serve data")]
+#[test_case(Plane::Data, Plane::Admin => false; "data listener does not serve admin")]
+fn test_serves(listener: Plane, plane: Plane) -> bool {
+    listener.serves(plane)
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::signing_keys::{AcceptedKeys, key_id};
+use anyhow::{Result, anyhow};
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::services::token_decryption_service::{TokenDecryptionService, TokenDecryptor};
+use log::{debug, info, warn};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::{Arc, RwLock};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+type BuildDecryptor<D> = Box<dyn Fn(&Value) -> Result<D> + Send + Sync>;
+
+/// A decryptor for every accepted signing key.
+struct Decryptors<D> {
+    primary: String,
+    keys: BTreeMap<String, Arc<D>>,
+}
+
+/// Decrypts internal tokens with the accepted signing keys. The keys are swapped in place when they change,
+/// so the token middleware is built once and requests in flight keep the keys they started with.
+pub struct SigningKeys<D = TokenDecryptionService> {
+    build: BuildDecryptor<D>,
+    current: RwLock<Arc<Decryptors<D>>>,
+}
+
+impl<D: TokenDecryptor + 'static> SigningKeys<D> {
+    /// `build` creates the decryptor of a key in the format of `token_settings.keys`.
+    pub fn new(accepted: &AcceptedKeys, build: impl Fn(&Value) -> Result<D> + Send + Sync + 'static) -> Result<Self> {
+        let build: BuildDecryptor<D> = Box::new(build);
+        let current = Self::decryptors(accepted, &build)?;
+        Ok(SigningKeys {
+            build,
+            current: RwLock::new(Arc::new(current)),
+        })
+    }
+
+    fn decryptors(accepted: &AcceptedKeys, build: &BuildDecryptor<D>) -> Result<Decryptors<D>> {
+        if !accepted.keys.contains_key(&accepted.primary) {
+            return Err(anyhow!("Primary signing key {} is not accepted", accepted.primary));
+        }
+        Ok(Decryptors {
+            primary: accepted.primary.clone(),
+            keys: accepted
+                .keys
+                .iter()
+                .map(|(key_id, keys)| Ok((key_id.clone(), Arc::new(build(keys)?))))
+                .collect::<Result<_>>()?,
+        })
+    }
+
+    /// Replaces the decryptors with the ones for the accepted keys. Invalid keys leave the current ones in place.
+    pub fn update(&self, accepted: &AcceptedKeys) -> Result<()> {
+        let next = Self::decryptors(accepted, &self.build)?;
+        *self.current.write().unwrap() = Arc::new(next);
+        Ok(())
+    }
+
+    /// Updates the decryptors whenever the accepted keys change, until the sender is dropped.
+    pub fn follow(self: Arc<Self>, mut keys: watch::Receiver<AcceptedKeys>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            while keys.changed().await.is_ok() {
+                let accepted = keys.borrow_and_update().clone();
+                match self.update(&accepted) {
+                    Ok(()) => info!(
+                        "Decrypting internal tokens with the signing keys {:?}",
+                        accepted.keys.keys()
+                    ),
+                    Err(e) => warn!("Failed to load the signing keys, keeping the current ones: {}", e),
+                }
+            }
+        })
+    }
+
+    /// The keys a token is tried with, in order: the key its `kid` names if that key is accepted,
+    /// otherwise every accepted key, the primary one first.
+    fn candidates(&self, token: &str) -> Vec<(String, Arc<D>)> {
+        let current = self.current.read().unwrap().clone();
+        if let Some(key_id) = key_id(token)
+            && let Some(decryptor) = current.keys.get(&key_id)
+        {
+            return vec![(key_id, decryptor.clone())];
+        }
+        let primary = current.keys.get_key_value(&current.primary);
+        primary
+            .into_iter()
+            .chain(current.keys.iter().filter(|(key_id, _)| **key_id != current.primary))
+            .map(|(key_id, decryptor)| (key_id.clone(), decryptor.clone()))
+            .collect()
+    }
+}
+
+impl<D: TokenDecryptor + 'static> TokenDecryptor for SigningKeys<D> {
+    fn decrypt(&self, token: &str) -> Result<BoxerClaims> {
+        let mut last_error = None;
+        for (key_id, decryptor) in self.candidates(token) {
+            match decryptor.decrypt(token) {
+                Ok(claims) => return Ok(claims),
+                Err(e) => {
+                    debug!("Signing key {} does not decrypt the token: {}", key_id, e);
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| anyhow!("No signing key is accepted")))
+    }
+}
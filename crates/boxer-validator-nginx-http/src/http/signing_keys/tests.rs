@@ -0,0 +1,100 @@
+use super::*;
+use anyhow::bail;
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use serde_json::json;
+use std::sync::Mutex;
+use test_case::test_case;
+
+/// Decrypts the tokens whose payload is its key, and records the keys it was tried with.
+struct FakeDecryptor {
+    key: String,
+    tried: Arc<Mutex<Vec<String>>>,
+}
+
+impl TokenDecryptor for FakeDecryptor {
+    fn decrypt(&self, token: &str) -> Result<BoxerClaims> {
+        self.tried.lock().unwrap().push(self.key.clone());
+        match token.split('.').nth(1) == Some(self.key.as_str()) {
+            true => Ok(BoxerClaims::default()),
+            false => bail!("wrong key"),
+        }
+    }
+}
+
+fn accepted(primary: &str, key_ids: &[&str]) -> AcceptedKeys {
+    AcceptedKeys {
+        primary: primary.to_string(),
+        keys: key_ids
+            .iter()
+            .map(|key_id| (key_id.to_string(), json!({ "public": key_id })))
+            .collect(),
+    }
+}
+
+fn signing_keys(tried: &Arc<Mutex<Vec<String>>>) -> SigningKeys<FakeDecryptor> {
+    let tried = tried.clone();
+    SigningKeys::new(&accepted("2026-10", &["2026-07", "2026-10", "2026-12"]), move |keys| {
+        Ok(FakeDecryptor {
+            key: keys["public"].as_str().unwrap().to_string(),
+            tried: tried.clone(),
+        })
+    })
+    .unwrap()
+}
+
+fn token(header: serde_json::Value, signed_with: &str) -> String {
+    format!(
+        "{}.{}.signature",
+        URL_SAFE_NO_PAD.encode(header.to_string()),
+        signed_with
+    )
+}
+
+#[test_case(json!({ "kid": "2026-07" }), "2026-07" => (true, "2026-07".to_string()); "named key")]
+#[test_case(json!({ "kid": "2026-07" }), "2026-10" => (false, "2026-07".to_string()); "named key does not decrypt")]
+#[test_case(json!({}), "2026-10" => (true, "2026-10".to_string()); "no key id, primary key")]
+#[test_case(json!({}), "2026-12" => (true, "2026-10,2026-07,2026-12".to_string()); "no key id, other key")]
+#[test_case(json!({ "kid": "2025-01" }), "2026-07" => (true, "2026-10,2026-07".to_string()); "unknown key id")]
+#[test_case(json!({}), "2025-01" => (false, "2026-10,2026-07,2026-12".to_string()); "no accepted key")]
+fn test_token_is_tried_with_its_signing_keys(header: serde_json::Value, signed_with: &str) -> (bool, String) {
+    let tried = Arc::new(Mutex::new(Vec::new()));
+
+    let decrypted = signing_keys(&tried).decrypt(&token(header, signed_with)).is_ok();
+
+    (decrypted, tried.lock().unwrap().join(","))
+}
+
+#[test]
+fn test_keys_are_swapped_on_update() {
+    let tried = Arc::new(Mutex::new(Vec::new()));
+    let signing_keys = signing_keys(&tried);
+
+    signing_keys.update(&accepted("2027-01", &["2027-01"])).unwrap();
+
+    assert!(signing_keys.decrypt(&token(json!({}), "2027-01")).is_ok());
+    assert!(signing_keys.decrypt(&token(json!({}), "2026-10")).is_err());
+}
+
+#[test]
+fn test_primary_key_must_be_accepted() {
+    let tried = Arc::new(Mutex::new(Vec::new()));
+    let signing_keys = signing_keys(&tried);
+
+    assert!(signing_keys.update(&accepted("2027-01", &["2026-10"])).is_err());
+    assert!(signing_keys.decrypt(&token(json!({}), "2026-10")).is_ok());
+}
+
+#[tokio::test]
+async fn test_follows_the_accepted_keys() {
+    let tried = Arc::new(Mutex::new(Vec::new()));
+    let signing_keys = Arc::new(signing_keys(&tried));
+    let (sender, keys) = watch::channel(accepted("2026-10", &["2026-10"]));
+    let following = signing_keys.clone().follow(keys);
+
+    sender.send_replace(accepted("2027-01", &["2027-01"]));
+    drop(sender);
+    following.await.unwrap();
+
+    assert!(signing_keys.decrypt(&token(json!({}), "2027-01")).is_ok());
+}
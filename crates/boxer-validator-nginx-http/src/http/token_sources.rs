@@ -0,0 +1,119 @@
+#[cfg(test)]
+mod tests;
+
+use crate::models::token::BoxerToken;
+use crate::services::configuration::models::TokenSource;
+use actix_web::cookie::Cookie;
+use actix_web::dev::RequestHead;
+use actix_web::http::header::{AUTHORIZATION, COOKIE, HeaderName, UPGRADE};
+use anyhow::Result;
+use percent_encoding::percent_decode_str;
+use std::str::FromStr;
+
+enum Source {
+    Authorization,
+    Cookie(String),
+    Header(HeaderName),
+    QueryParameter(String),
+}
+
+impl Source {
+    fn describe(&self) -> String {
+        match self {
+            Source::Authorization => "Authorization header".to_string(),
+            Source::Cookie(name) => format!("cookie {}", name),
+            Source::Header(name) => format!("header {}", name),
+            Source::QueryParameter(name) => format!("query parameter {} (websocket upgrades only)", name),
+        }
+    }
+
+    fn find(&self, head: &RequestHead, original_url: Option<&str>) -> Option<String> {
+        match self {
+            Source::Authorization => BoxerToken::try_from(head.headers().get(AUTHORIZATION)?)
+                .ok()
+                .map(Into::into),
+            Source::Cookie(name) => head
+                .headers()
+                .get_all(COOKIE)
+                .filter_map(|value| value.to_str().ok())
+                .flat_map(|value| value.split(';'))
+                .filter_map(|pair| Cookie::parse(pair.trim()).ok())
+                .find(|cookie| cookie.name() == name)
+                .map(|cookie| cookie.value().to_string()),
+            Source::Header(name) => Some(head.headers().get(name)?.to_str().ok()?.trim().to_string()),
+            Source::QueryParameter(name) => {
+                let original_url = original_url.filter(|url| is_websocket_upgrade(head, url))?;
+                let (_, query) = original_url.split_once('?')?;
+                let query = query.split('#').next().unwrap_or_default();
+                query
+                    .split('&')
+                    .filter_map(|pair| pair.split_once('='))
+                    .find(|(key, _)| percent_decode_str(key).decode_utf8_lossy() == name.as_str())
+                    .map(|(_, value)| {
+                        percent_decode_str(&value.replace('+', " "))
+                            .decode_utf8_lossy()
+                            .into_owned()
+                    })
+            }
+        }
+        .filter(|token| !token.is_empty())
+    }
+}
+
+/// Query parameters leak into access logs and browser history, so they are only read for websocket upgrades,
+/// where clients cannot set headers. The upgrade is recognized by the `Upgrade` header the ingress controller
+/// passes along, or by a `ws` or `wss` original URL.
+fn is_websocket_upgrade(head: &RequestHead, original_url: &str) -> bool {
+    let upgrade = head
+        .headers()
+        .get(UPGRADE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.trim().eq_ignore_ascii_case("websocket"));
+    let scheme = original_url.split_once("://").map(|(scheme, _)| scheme);
+    upgrade || scheme.is_some_and(|scheme| scheme.eq_ignore_ascii_case("ws") || scheme.eq_ignore_ascii_case("wss"))
+}
+
+/// Finds the token under review in the configured sources, in priority order.
+pub struct TokenSources {
+    sources: Vec<Source>,
+}
+
+impl TokenSources {
+    pub fn new(settings: &[TokenSource]) -> Result<Self> {
+        let sources = settings
+            .iter()
+            .map(|source| {
+                Ok(match source {
+                    TokenSource::Authorization => Source::Authorization,
+                    TokenSource::Cookie { name } => Source::Cookie(name.clone()),
+                    TokenSource::Header { name } => Source::Header(HeaderName::from_str(name)?),
+                    TokenSource::QueryParameter { name } => Source::QueryParameter(name.clone()),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(TokenSources { sources })
+    }
+
+    /// Returns the token from the first source that has one, or an error naming every source that was tried.
+    /// `original_url` is the URL of the request under review, used for query parameter sources.
+    pub fn find(&self, head: &RequestHead, original_url: Option<&str>) -> Result<String, String> {
+        self.sources
+            .iter()
+            .find_map(|source| source.find(head, original_url))
+            .ok_or_else(|| {
+                let tried: Vec<String> = self.sources.iter().map(Source::describe).collect();
+                format!("No token found, tried: {}", tried.join(", "))
+            })
+    }
+
+    /// The query parameters that may carry the token. Decision logs redact them from recorded URLs.
+    pub fn query_parameters(&self) -> Vec<String> {
+        self.sources
+            .iter()
+            .filter_map(|source| match source {
+                Source::QueryParameter(name) => Some(name.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+}
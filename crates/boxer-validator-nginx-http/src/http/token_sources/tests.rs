@@ -0,0 +1,105 @@
+use super::*;
+use actix_web::cookie::Cookie;
+use actix_web::test::TestRequest;
+use test_case::test_case;
+
+fn token_sources() -> TokenSources {
+    TokenSources::new(&[
+        TokenSource::Authorization,
+        TokenSource::Cookie {
+            name: "boxer_token".to_string(),
+        },
+        TokenSource::Header {
+            name: "X-Boxer-Token".to_string(),
+        },
+        TokenSource::QueryParameter {
+            name: "access_token".to_string(),
+        },
+    ])
+    .unwrap()
+}
+
+#[test]
+fn test_authorization_header_takes_priority() {
+    let req = TestRequest::default()
+        .insert_header(("Authorization", "bearer  from-authorization "))
+        .cookie(Cookie::new("boxer_token", "from-cookie"))
+        .to_http_request();
+
+    assert_eq!(
+        token_sources().find(req.head(), None).as_deref(),
+        Ok("from-authorization")
+    );
+}
+
+#[test]
+fn test_cookie_is_used_before_header() {
+    let req = TestRequest::default()
+        .cookie(Cookie::new("boxer_token", "from-cookie"))
+        .insert_header(("X-Boxer-Token", "from-header"))
+        .to_http_request();
+
+    assert_eq!(token_sources().find(req.head(), None).as_deref(), Ok("from-cookie"));
+}
+
+#[test]
+fn test_other_authorization_scheme_falls_through() {
+    let req = TestRequest::default()
+        .insert_header(("Authorization", "Basic dXNlcjpwYXNz"))
+        .insert_header(("X-Boxer-Token", "from-header"))
+        .to_http_request();
+
+    assert_eq!(token_sources().find(req.head(), None).as_deref(), Ok("from-header"));
+}
+
+#[test_case("wss://www.example.com/stream?access_token=from-query" => Ok("from-query".to_string()); "query parameter")]
+#[test_case("wss://www.example.com/stream?room=1&access_token=a%2Bb#top" => Ok("a+b".to_string()); "encoded query parameter")]
+#[test_case("wss://www.example.com/stream?room=1" => matches Err(_); "missing query parameter")]
+fn test_query_parameter(original_url: &str) -> Result<String, String> {
+    let req = TestRequest::default().to_http_request();
+
+    token_sources().find(req.head(), Some(original_url))
+}
+
+#[test_case(None => matches Err(_); "plain request")]
+#[test_case(Some("websocket") => Ok("from-query".to_string()); "websocket upgrade")]
+#[test_case(Some("WebSocket") => Ok("from-query".to_string()); "websocket upgrade in other case")]
+#[test_case(Some("h2c") => matches Err(_); "other upgrade")]
+fn test_query_parameter_requires_websocket_upgrade(upgrade: Option<&str>) -> Result<String, String> {
+    let mut req = TestRequest::default();
+    if let Some(upgrade) = upgrade {
+        req = req.insert_header(("Upgrade", upgrade));
+    }
+
+    token_sources().find(
+        req.to_http_request().head(),
+        Some("https://www.example.com/stream?access_token=from-query"),
+    )
+}
+
+#[test]
+fn test_query_parameters_are_listed() {
+    assert_eq!(token_sources().query_parameters(), vec!["access_token".to_string()]);
+}
+
+#[test]
+fn test_missing_token_lists_sources() {
+    let req = TestRequest::default().to_http_request();
+
+    assert_eq!(
+        token_sources().find(req.head(), None),
+        Err(
+            "No token found, tried: Authorization header, cookie boxer_token, header x-boxer-token, query parameter access_token (websocket upgrades only)"
+                .to_string()
+        )
+    );
+}
+
+#[test]
+fn test_invalid_header_name_is_rejected() {
+    let result = TokenSources::new(&[TokenSource::Header {
+        name: "not a header".to_string(),
+    }]);
+
+    assert!(result.is_err());
+}
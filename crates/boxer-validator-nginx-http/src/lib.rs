@@ -4,15 +4,45 @@ pub mod services;
 
 use crate::http::controllers::v1;
 use crate::http::health;
-use crate::services::configuration::models::AppSettings;
-use crate::services::repositories::action_repository::read_write::ActionDataRepository;
-use crate::services::repositories::policy_repository::read_write::PolicyDataRepository;
-use crate::services::repositories::resource_repository::read_write::ResourceDiscoveryDocumentRepository;
+use crate::http::metrics;
+use crate::http::middleware::client_certificate::store_peer_certificates;
+use crate::http::original_request::OriginalRequest;
+use crate::http::plane::Plane;
+use crate::http::signing_keys::SigningKeys;
+use crate::http::token_sources::TokenSources;
+use crate::services::admin_authorization::AdminAuthorizer;
+use crate::services::api_server::ApiServer;
+use crate::services::audit_trail::{AuditTrail, AuditWriterChain};
+use crate::services::configuration::models::{AdminListener, AppSettings};
+use crate::services::decision_log::DecisionLog;
+use crate::services::enforcement_mode::{Enforcement, SchemaEnforcementModes};
+use crate::services::json_lines::{JsonLinesWriter, append_to};
+use crate::services::metrics::{TimedLookup, TokenReviewMetrics, observe_backend};
+use crate::services::policy_simulation::PolicySimulator;
+use crate::services::repositories::action_repository::public_routes::PublicRouteRepository;
+use crate::services::repositories::action_repository::read_write::{ActionDataRepository, ActionSetListRepository};
+use crate::services::repositories::list_repository::SchemaListRepository;
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use crate::services::repositories::lookup_trie::route_table::RouteTables;
+use crate::services::repositories::policy_repository::read_only::ShadowPolicyRepository;
+use crate::services::repositories::policy_repository::read_write::{PolicyDataRepository, PolicySetListRepository};
+use crate::services::repositories::resource_repository::read_write::{
+    ResourceDiscoveryDocumentRepository, ResourceSetListRepository,
+};
+use crate::services::review_stages::{ReviewStage, StagedRepository};
+use crate::services::schema_catalog::SchemaUsageIndex;
 use crate::services::schema_provider::KubernetesSchemaProvider;
+use crate::services::shadow_policies::ShadowEvaluator;
+use crate::services::shutdown::ShutdownHooks;
+use crate::services::signing_keys::SigningKeyWatch;
+use crate::services::telemetry::Telemetry;
+use crate::services::tls::Tls;
+use crate::services::unmatched_routes::{FallbackLookup, UnmatchedRoutes};
 use actix_web::dev::Server;
 use actix_web::middleware::{Logger, from_fn};
 use actix_web::{App, HttpServer, web};
 use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
 use boxer_core::http::middleware::logging::custom_error_logging;
 use boxer_core::services::audit::log_audit_service::LogAuditService;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
@@ -21,68 +51,275 @@ use boxer_core::services::service_provider::ServiceProvider;
 use boxer_core::services::token_decryption_service::TokenDecryptionService;
 use boxer_core::services::validation_service::ValidationService;
 use boxer_core::services::validation_service::cedar_validation_service::CedarValidationService;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
 use boxer_core::services::validation_service::schema_provider::SchemaProvider;
+use cedar_policy::{EntityUid, PolicySet};
 use http::openapi::ApiDoc;
 use log::info;
 use opentelemetry_instrumentation_actix_web::RequestTracing;
 use services::backends::kubernetes::{KubernetesBackend, ValidatorBackend};
+use std::io;
+use std::net::{SocketAddr, TcpListener};
 use std::sync::Arc;
+use std::time::Duration;
 use utoipa::OpenApi;
 use utoipa_swagger_ui::SwaggerUi;
 
-pub fn start_api_server(
+/// Metrics namespace of validation services that do not review tokens, e.g. `boxer-validator-simulation`.
+/// Called once per service at startup, so the leaked names stay bounded.
+fn scoped_namespace(root: &'static str, scope: &str) -> &'static str {
+    Box::leak(format!("{}-{}", root, scope).into_boxed_str())
+}
+
+pub async fn start_api_server(
     current_backend: Arc<KubernetesBackend>,
     app_settings: AppSettings,
     root_metrics_namespace: &'static str,
-) -> Result<Server, anyhow::Error> {
-    let schema_provider: Arc<dyn SchemaProvider<BoxerClaims>> =
-        Arc::new(KubernetesSchemaProvider::new(current_backend.get()));
-    let action_repository = current_backend.get();
-    let readiness_state = current_backend.readiness_state();
-    let resource_repository = current_backend.get();
-    let policy_repository = current_backend.get();
+    telemetry: &Telemetry,
+    shutdown_hooks: &ShutdownHooks,
+) -> Result<ApiServer, anyhow::Error> {
+    let prometheus = telemetry.prometheus();
+    let schema_provider: Arc<dyn SchemaProvider<BoxerClaims>> = Arc::new(KubernetesSchemaProvider::new(
+        current_backend.get(),
+        &opentelemetry::global::meter(root_metrics_namespace),
+    ));
+    let action_repository: Arc<AssociatedRepository<(String, Vec<RequestSegment>), EntityUid>> =
+        Arc::new(StagedRepository::new(
+            Arc::new(FallbackLookup::actions(Arc::new(TimedLookup::new(
+                current_backend.get(),
+                "action",
+                &opentelemetry::global::meter(root_metrics_namespace),
+            )))),
+            ReviewStage::ActionLookup,
+        ));
+    let readiness = current_backend.readiness();
+    let resource_repository: Arc<AssociatedRepository<(String, Vec<PathSegment>), EntityUid>> =
+        Arc::new(StagedRepository::new(
+            Arc::new(FallbackLookup::resources(Arc::new(TimedLookup::new(
+                current_backend.get(),
+                "resource",
+                &opentelemetry::global::meter(root_metrics_namespace),
+            )))),
+            ReviewStage::ResourceLookup,
+        ));
+    let policy_repository: Arc<AssociatedRepository<String, PolicySet>> =
+        Arc::new(StagedRepository::new(current_backend.get(), ReviewStage::PolicyFetch));
     let audit_service = Arc::new(LogAuditService::new());
     let cedar_validation_service: Arc<dyn ValidationService<BoxerClaims>> = Arc::new(CedarValidationService::new(
-        schema_provider,
-        action_repository,
-        resource_repository,
-        policy_repository,
+        schema_provider.clone(),
+        action_repository.clone(),
+        resource_repository.clone(),
+        policy_repository.clone(),
         MetricsProvider::new(root_metrics_namespace, app_settings.instance_name.clone()),
     ));
+    let shadow_policy_repository: Arc<ShadowPolicyRepository> = current_backend.get();
+    let shadow_evaluator = Arc::new(ShadowEvaluator::new(
+        shadow_policy_repository.clone(),
+        Arc::new(CedarValidationService::new(
+            schema_provider.clone(),
+            action_repository.clone(),
+            resource_repository.clone(),
+            shadow_policy_repository,
+            MetricsProvider::new(
+                scoped_namespace(root_metrics_namespace, "shadow"),
+                app_settings.instance_name.clone(),
+            ),
+        )),
+        &opentelemetry::global::meter(root_metrics_namespace),
+    ));
+    let unmatched_routes = Arc::new(UnmatchedRoutes::new(
+        &app_settings.unmatched_routes,
+        &opentelemetry::global::meter(root_metrics_namespace),
+    )?);
+    let policy_simulator = Arc::new(PolicySimulator::new(
+        action_repository.clone(),
+        resource_repository.clone(),
+        unmatched_routes.clone(),
+        scoped_namespace(root_metrics_namespace, "simulation"),
+        app_settings.instance_name.clone(),
+    ));
+    let schema_enforcement_modes: Arc<SchemaEnforcementModes> = current_backend.get();
+    let enforcement = Arc::new(Enforcement::new(
+        schema_enforcement_modes,
+        app_settings.enforcement_mode,
+        &opentelemetry::global::meter(root_metrics_namespace),
+    ));
+    let admin_authorizer = Arc::new(AdminAuthorizer::new(
+        &app_settings.admin_api,
+        current_backend.get(),
+        root_metrics_namespace,
+        app_settings.instance_name.clone(),
+    )?);
+
+    let mut audit_trail_writers: Vec<Arc<dyn AuditWriter>> = vec![audit_service.clone()];
+    if let Some(trail_file) = &app_settings.audit.trail_file {
+        info!("writing audit trail to {}", trail_file);
+        audit_trail_writers.push(Arc::new(JsonLinesWriter::spawn(append_to(trail_file).await?)));
+    }
+    let audit_trail = Arc::new(AuditTrail::new(Arc::new(AuditWriterChain::new(audit_trail_writers))));
 
     let action_repository: Arc<ActionDataRepository> = current_backend.get();
     let resource_repository: Arc<ResourceDiscoveryDocumentRepository> = current_backend.get();
     let policy_repository: Arc<PolicyDataRepository> = current_backend.get();
 
     let schema_repository: Arc<SchemaRepository> = current_backend.get();
-
-    info!(
-        "listening on {}:{}",
-        &app_settings.listen_address.ip(),
-        &app_settings.listen_address.port()
+    let schema_usage_index: Arc<SchemaUsageIndex> = current_backend.get();
+    let route_tables: Arc<RouteTables> = current_backend.get();
+    let public_routes: Arc<PublicRouteRepository> = current_backend.get();
+    let original_request = Arc::new(OriginalRequest::new(&app_settings.request_headers)?);
+    let token_sources = Arc::new(TokenSources::new(&app_settings.token_sources)?);
+    observe_backend(
+        &opentelemetry::global::meter(root_metrics_namespace),
+        readiness.clone(),
+        route_tables.clone(),
     );
-    let decryptor = Arc::new(TokenDecryptionService::new(
-        app_settings.get_signatures()?,
-        app_settings.token_settings,
-    ));
-    let http_server_builder = HttpServer::new(move || {
+    let token_review_metrics = Arc::new(TokenReviewMetrics::new(&opentelemetry::global::meter(
+        root_metrics_namespace,
+    )));
+    let decision_log = Arc::new(
+        DecisionLog::from_settings(
+            &app_settings.decision_log,
+            app_settings.instance_name.clone(),
+            &token_sources,
+        )
+        .await?,
+    );
+    shutdown_hooks.register("decision_log", {
+        let decision_log = decision_log.clone();
+        async move {
+            decision_log.shutdown().await;
+            Ok(())
+        }
+    });
+    let schema_list_repository: Arc<SchemaListRepository> = current_backend.get();
+    let action_list_repository: Arc<ActionSetListRepository> = current_backend.get();
+    let resource_list_repository: Arc<ResourceSetListRepository> = current_backend.get();
+    let policy_list_repository: Arc<PolicySetListRepository> = current_backend.get();
+
+    let signing_key_watch = SigningKeyWatch::start(
+        &app_settings.signing_keys,
+        &app_settings.token_settings.keys,
+        current_backend.get(),
+        &app_settings.backend.kubernetes.namespace,
+        readiness.register("signing_keys"),
+    )
+    .await?;
+    let token_settings = app_settings.token_settings.clone();
+    let signing_keys = Arc::new(SigningKeys::new(
+        &signing_key_watch.subscribe().borrow(),
+        move |keys| {
+            Ok(TokenDecryptionService::new(
+                serde_json::from_value(keys.clone())?,
+                token_settings.clone(),
+            ))
+        },
+    )?);
+    // Stops once the watch is stopped on shutdown
+    signing_keys.clone().follow(signing_key_watch.subscribe());
+    shutdown_hooks.register("signing_keys", async move {
+        signing_key_watch.stop();
+        Ok(())
+    });
+    let tls = app_settings.tls.as_ref().map(Tls::load).transpose()?.map(Arc::new);
+    let tls_config = tls.as_ref().map(|tls| tls.server_config()).transpose()?;
+    if let Some(tls) = &tls {
+        info!("serving HTTPS");
+        let tls_watch = tls.watch();
+        shutdown_hooks.register("tls", async move {
+            tls_watch.abort();
+            Ok(())
+        });
+    }
+    let metrics_plane = Plane::of_metrics(app_settings.prometheus.plane, &app_settings.admin_api.listener);
+    let app = move |plane: Plane| {
         App::new()
             .wrap(RequestTracing::new())
             .wrap(Logger::default())
             .wrap(from_fn(custom_error_logging))
             .app_data(web::Data::new(cedar_validation_service.clone()))
+            .app_data(web::Data::new(shadow_evaluator.clone()))
+            .app_data(web::Data::new(enforcement.clone()))
+            .app_data(web::Data::new(unmatched_routes.clone()))
+            .app_data(web::Data::new(admin_authorizer.clone()))
+            .app_data(web::Data::new(audit_trail.clone()))
+            .app_data(web::Data::new(decision_log.clone()))
             .app_data(web::Data::new(schema_repository.clone()))
+            .app_data(web::Data::new(schema_usage_index.clone()))
+            .app_data(web::Data::new(route_tables.clone()))
+            .app_data(web::Data::new(policy_simulator.clone()))
+            .app_data(web::Data::new(public_routes.clone()))
+            .app_data(web::Data::new(original_request.clone()))
+            .app_data(web::Data::new(token_sources.clone()))
             .app_data(web::Data::new(action_repository.clone()))
             .app_data(web::Data::new(resource_repository.clone()))
             .app_data(web::Data::new(policy_repository.clone()))
-            .app_data(web::Data::new(readiness_state.clone()))
+            .app_data(web::Data::new(schema_list_repository.clone()))
+            .app_data(web::Data::new(action_list_repository.clone()))
+            .app_data(web::Data::new(resource_list_repository.clone()))
+            .app_data(web::Data::new(policy_list_repository.clone()))
+            .app_data(web::Data::new(readiness.clone()))
+            .app_data(web::Data::new(token_review_metrics.clone()))
             // The last middleware in the chain should always be InternalTokenMiddleware
             // to ensure that the token is valid in the beginning of the request processing
-            .service(v1::urls(audit_service.clone(), decryptor.clone()))
+            .service(v1::urls(audit_service.clone(), signing_keys.clone(), plane))
+            .configure(|cfg| {
+                if let Some(tls) = &tls {
+                    cfg.app_data(web::Data::new(tls.clone()));
+                }
+            })
             .service(health::urls())
-            .service(SwaggerUi::new("/swagger/{_:.*}").url("/api-docs/openapi.json", ApiDoc::openapi()))
-    })
-    .bind(app_settings.listen_address)?;
+            .configure(|cfg| {
+                if plane.serves(metrics_plane)
+                    && let Some(prometheus) = &prometheus
+                {
+                    cfg.app_data(web::Data::new(prometheus.clone()))
+                        .service(metrics::urls());
+                }
+            })
+            .configure(|cfg| {
+                if plane.serves_admin() {
+                    cfg.service(SwaggerUi::new("/swagger/{_:.*}").url("/api-docs/openapi.json", ApiDoc::openapi()));
+                }
+            })
+    };
+    let shutdown_timeout = Duration::from(app_settings.shutdown.timeout).as_secs();
+    let listen = |address: SocketAddr, workers: Option<usize>, plane: Plane| -> io::Result<Server> {
+        let listener = TcpListener::bind(address)?;
+        info!(
+            "listening on {}:{} for the {:?} plane",
+            address.ip(),
+            address.port(),
+            plane
+        );
+        let app = app.clone();
+        let server = HttpServer::new(move || app(plane))
+            .on_connect(store_peer_certificates)
+            .disable_signals()
+            .shutdown_timeout(shutdown_timeout);
+        let server = match workers {
+            Some(workers) => server.workers(workers),
+            None => server,
+        };
+        let server = match &tls_config {
+            Some(tls_config) => server.listen_rustls_0_23(listener, tls_config.clone())?,
+            None => server.listen(listener)?,
+        };
+        Ok(server.run())
+    };
+
+    let data_plane = match app_settings.admin_api.listener {
+        AdminListener::Shared => Plane::All,
+        AdminListener::Separate { .. } | AdminListener::Disabled => Plane::Data,
+    };
+    let mut servers = vec![listen(app_settings.listen_address, app_settings.workers, data_plane)?];
+    if let AdminListener::Separate {
+        listen_address,
+        workers,
+    } = app_settings.admin_api.listener
+    {
+        servers.push(listen(listen_address, workers, Plane::Admin)?);
+    }
 
-    Ok(http_server_builder.run())
+    Ok(ApiServer::all(servers))
 }
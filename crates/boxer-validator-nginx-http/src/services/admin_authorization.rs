@@ -0,0 +1,267 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::AdminApiSettings;
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use anyhow::{Result, anyhow, bail};
+use async_trait::async_trait;
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use boxer_core::services::observability::open_telemetry::metrics::provider::MetricsProvider;
+use boxer_core::services::validation_service::ValidationService;
+use boxer_core::services::validation_service::cedar_validation_service::CedarValidationService;
+use boxer_core::services::validation_service::http_method::HTTPMethod;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+use boxer_core::services::validation_service::required_claims::RequiredClaims;
+use boxer_core::services::validation_service::schema_provider::SchemaProvider;
+use cedar_policy::{EntityId, EntityTypeName, EntityUid, PolicySet, Schema, SchemaFragment};
+use serde_json::{Map, Value, json};
+use std::str::FromStr;
+use std::sync::Arc;
+
+/// The built-in schema guarding the admin API. Its policies are stored as a regular policy set.
+pub const ADMIN_SCHEMA_ID: &str = "boxer-admin";
+
+const ADMIN_NAMESPACE: &str = "BoxerAdmin";
+const API_PREFIX: [&str; 2] = ["api", "v1"];
+
+/// Admin API scopes; each of them is guarded by `read_*`, `write_*` and `delete_*` actions.
+const ADMIN_SCOPES: [&str; 6] = [
+    "schema",
+    "action_set",
+    "resource_set",
+    "policy_set",
+    "route_table",
+    "simulation",
+];
+const ADMIN_OPERATIONS: [&str; 3] = ["read", "write", "delete"];
+/// Required in addition to `write_action_set` to register action sets with public routes,
+/// which let requests through without a token on the hostname of the action set.
+const PUBLISH_ACTION: &str = "publish_action_set";
+
+fn admin_operation(method: &HTTPMethod) -> Result<&'static str> {
+    match method {
+        HTTPMethod::Get => Ok("read"),
+        HTTPMethod::Post => Ok("write"),
+        HTTPMethod::Delete => Ok("delete"),
+        other => bail!("Method {} is not supported by the admin API", other),
+    }
+}
+
+/// Splits an admin API path into its scope and the schema it targets, if any.
+fn admin_scope(path: &[String]) -> Result<(&str, Option<&str>)> {
+    let rest = path
+        .strip_prefix(&API_PREFIX.map(String::from))
+        .ok_or_else(|| anyhow!("Path /{} is not part of the admin API", path.join("/")))?;
+    match rest {
+        [scope, remainder @ ..] if ADMIN_SCOPES.contains(&scope.as_str()) => {
+            Ok((scope, remainder.first().map(String::as_str)))
+        }
+        _ => bail!("Path /{} is not part of the admin API", path.join("/")),
+    }
+}
+
+fn admin_uid(entity_type: &str, id: &str) -> Result<EntityUid> {
+    let type_name = EntityTypeName::from_str(&format!("{}::{}", ADMIN_NAMESPACE, entity_type))?;
+    Ok(EntityUid::from_type_name_and_id(type_name, EntityId::new(id)))
+}
+
+/// Maps an admin API call to the action it performs, e.g. `BoxerAdmin::Action::"write_policy_set"`.
+pub fn admin_action(method: &HTTPMethod, path: &[String]) -> Result<EntityUid> {
+    let (scope, _) = admin_scope(path)?;
+    admin_uid("Action", &format!("{}_{}", admin_operation(method)?, scope))
+}
+
+/// Maps an admin API call to the resource it touches: the schema it is bound to,
+/// or the scope itself for calls that span all schemas.
+pub fn admin_resource(path: &[String]) -> Result<EntityUid> {
+    match admin_scope(path)? {
+        (_, Some(schema)) => admin_uid("Schema", schema),
+        (scope, None) => admin_uid("Catalog", scope),
+    }
+}
+
+/// Builds the `BoxerAdmin` schema fragment for the configured principal types.
+pub fn admin_schema(principal_types: &[String]) -> Result<SchemaFragment> {
+    let applies_to = json!({
+        "principalTypes": principal_types,
+        "resourceTypes": ["Schema", "Catalog"],
+    });
+    let actions: Map<String, Value> = ADMIN_SCOPES
+        .iter()
+        .flat_map(|scope| {
+            ADMIN_OPERATIONS
+                .iter()
+                .map(move |operation| format!("{}_{}", operation, scope))
+        })
+        .chain(std::iter::once(PUBLISH_ACTION.to_string()))
+        .map(|action| (action, json!({ "appliesTo": applies_to })))
+        .collect();
+    let schema = json!({
+        ADMIN_NAMESPACE: {
+            "entityTypes": {
+                "Schema": {},
+                "Catalog": {}
+            },
+            "actions": actions
+        }
+    });
+    SchemaFragment::from_json_value(schema).map_err(anyhow::Error::from)
+}
+
+fn static_segments(path: impl Iterator<Item = PathSegment>) -> Vec<String> {
+    path.filter_map(|segment| match segment {
+        PathSegment::Static(value) => Some(value),
+        PathSegment::Parameter => None,
+    })
+    .collect()
+}
+
+/// Combines the built-in admin schema with the principal schema carried by the token.
+pub struct AdminSchemaProvider {
+    admin_schema: SchemaFragment,
+}
+
+#[async_trait]
+impl SchemaProvider<BoxerClaims> for AdminSchemaProvider {
+    async fn get_schema(&self, boxer_claims: &BoxerClaims) -> Result<Schema> {
+        let principal_schema = boxer_claims.get_schema().clone();
+        Schema::from_schema_fragments(vec![self.admin_schema.clone(), principal_schema]).map_err(anyhow::Error::from)
+    }
+}
+
+/// Resolves admin API actions from the request method and path; the schema ID from the token is ignored.
+pub struct AdminActionRepository;
+
+#[async_trait]
+impl ReadOnlyRepository<(String, Vec<RequestSegment>), EntityUid> for AdminActionRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, (_, segments): (String, Vec<RequestSegment>)) -> Result<EntityUid, Self::ReadError> {
+        let method = segments
+            .iter()
+            .find_map(|segment| match segment {
+                RequestSegment::Verb(method) => Some(method.clone()),
+                _ => None,
+            })
+            .ok_or_else(|| anyhow!("Request has no method"))?;
+        let path = static_segments(segments.into_iter().filter_map(|segment| match segment {
+            RequestSegment::Path(segment) => Some(segment),
+            _ => None,
+        }));
+        admin_action(&method, &path)
+    }
+}
+
+/// Resolves every admin API call to the `publish_action_set` action.
+pub struct PublishActionRepository;
+
+#[async_trait]
+impl ReadOnlyRepository<(String, Vec<RequestSegment>), EntityUid> for PublishActionRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, _: (String, Vec<RequestSegment>)) -> Result<EntityUid, Self::ReadError> {
+        admin_uid("Action", PUBLISH_ACTION)
+    }
+}
+
+/// Resolves admin API resources from the request path; the schema ID from the token is ignored.
+pub struct AdminResourceRepository;
+
+#[async_trait]
+impl ReadOnlyRepository<(String, Vec<PathSegment>), EntityUid> for AdminResourceRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, (_, segments): (String, Vec<PathSegment>)) -> Result<EntityUid, Self::ReadError> {
+        admin_resource(&static_segments(segments.into_iter()))
+    }
+}
+
+/// Always reads the policy set registered for the `boxer-admin` schema.
+pub struct AdminPolicyRepository {
+    policies: Arc<AssociatedRepository<String, PolicySet>>,
+}
+
+#[async_trait]
+impl ReadOnlyRepository<String, PolicySet> for AdminPolicyRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, _: String) -> Result<PolicySet, Self::ReadError> {
+        self.policies.get(ADMIN_SCHEMA_ID.to_string()).await
+    }
+}
+
+/// Authorizes admin API calls with Cedar against the `boxer-admin` schema and policy set.
+pub struct AdminAuthorizer {
+    validation_service: Arc<dyn ValidationService<BoxerClaims>>,
+    publication_service: Arc<dyn ValidationService<BoxerClaims>>,
+}
+
+impl AdminAuthorizer {
+    pub fn new(
+        settings: &AdminApiSettings,
+        policies: Arc<AssociatedRepository<String, PolicySet>>,
+        metrics_namespace: &'static str,
+        instance: String,
+    ) -> Result<Self> {
+        let schema_provider = Arc::new(AdminSchemaProvider {
+            admin_schema: admin_schema(&settings.principal_types)?,
+        });
+        let policies = Arc::new(AdminPolicyRepository { policies });
+        let validation_service = Arc::new(CedarValidationService::new(
+            schema_provider.clone(),
+            Arc::new(AdminActionRepository),
+            Arc::new(AdminResourceRepository),
+            policies.clone(),
+            MetricsProvider::new(metrics_namespace, instance.clone()),
+        ));
+        let publication_service = Arc::new(CedarValidationService::new(
+            schema_provider,
+            Arc::new(PublishActionRepository),
+            Arc::new(AdminResourceRepository),
+            policies,
+            MetricsProvider::new(metrics_namespace, instance),
+        ));
+        Ok(AdminAuthorizer {
+            validation_service,
+            publication_service,
+        })
+    }
+
+    #[cfg(test)]
+    pub(crate) fn with_validation_services(
+        validation_service: Arc<dyn ValidationService<BoxerClaims>>,
+        publication_service: Arc<dyn ValidationService<BoxerClaims>>,
+    ) -> Self {
+        AdminAuthorizer {
+            validation_service,
+            publication_service,
+        }
+    }
+
+    pub async fn authorize(
+        &self,
+        boxer_claims: BoxerClaims,
+        request_context: RequestContext,
+        event: &mut AuditEvent,
+    ) -> Result<()> {
+        self.validation_service
+            .validate(boxer_claims, request_context, event)
+            .await
+    }
+
+    /// Authorizes the `publish_action_set` action on the schema the admin API call targets.
+    pub async fn authorize_publication(
+        &self,
+        boxer_claims: BoxerClaims,
+        request_context: RequestContext,
+        event: &mut AuditEvent,
+    ) -> Result<()> {
+        self.publication_service
+            .validate(boxer_claims, request_context, event)
+            .await
+    }
+}
@@ -0,0 +1,44 @@
+use super::*;
+use test_case::test_case;
+
+fn path(value: &str) -> Vec<String> {
+    value.split('/').filter(|s| !s.is_empty()).map(String::from).collect()
+}
+
+#[test_case(HTTPMethod::Get, "api/v1/schema/photo-app" => "BoxerAdmin::Action::\"read_schema\""; "read schema")]
+#[test_case(HTTPMethod::Post, "api/v1/policy_set/photo-app/admins" => "BoxerAdmin::Action::\"write_policy_set\""; "write policy set")]
+#[test_case(HTTPMethod::Delete, "api/v1/action_set/photo-app/actions" => "BoxerAdmin::Action::\"delete_action_set\""; "delete action set")]
+#[test_case(HTTPMethod::Get, "api/v1/route_table" => "BoxerAdmin::Action::\"read_route_table\""; "read route tables")]
+#[test_case(HTTPMethod::Post, "api/v1/simulation/photo-app" => "BoxerAdmin::Action::\"write_simulation\""; "run a simulation")]
+fn test_admin_action(method: HTTPMethod, value: &str) -> String {
+    admin_action(&method, &path(value)).unwrap().to_string()
+}
+
+#[test_case("api/v1/schema/photo-app" => "BoxerAdmin::Schema::\"photo-app\""; "schema document")]
+#[test_case("api/v1/resource_set/photo-app/resources" => "BoxerAdmin::Schema::\"photo-app\""; "schema-bound document")]
+#[test_case("api/v1/route_table/photo-app/resolve" => "BoxerAdmin::Schema::\"photo-app\""; "schema-bound lookup")]
+#[test_case("api/v1/schema" => "BoxerAdmin::Catalog::\"schema\""; "list all schemas")]
+fn test_admin_resource(value: &str) -> String {
+    admin_resource(&path(value)).unwrap().to_string()
+}
+
+#[test_case(HTTPMethod::Put, "api/v1/schema/photo-app"; "unsupported method")]
+#[test_case(HTTPMethod::Get, "api/v1/token/review"; "token scope")]
+#[test_case(HTTPMethod::Get, "api/v2/schema/photo-app"; "other API version")]
+#[test_case(HTTPMethod::Get, ""; "root path")]
+fn test_admin_action_rejects(method: HTTPMethod, value: &str) {
+    assert!(admin_action(&method, &path(value)).is_err());
+}
+
+#[tokio::test]
+async fn test_publication_resolves_publish_action() {
+    let action = PublishActionRepository
+        .get((
+            ADMIN_SCHEMA_ID.to_string(),
+            vec![RequestSegment::Verb(HTTPMethod::Post)],
+        ))
+        .await
+        .unwrap();
+
+    assert_eq!(action.to_string(), "BoxerAdmin::Action::\"publish_action_set\"");
+}
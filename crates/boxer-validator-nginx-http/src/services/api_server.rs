@@ -0,0 +1,55 @@
+#[cfg(test)]
+mod tests;
+
+use actix_web::dev::{Server, ServerHandle};
+use futures::FutureExt;
+use futures::future::{BoxFuture, join_all, try_join_all};
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+/// Stops every server of an [`ApiServer`].
+#[derive(Clone)]
+pub struct ApiServerHandle(Vec<ServerHandle>);
+
+impl ApiServerHandle {
+    pub async fn stop(&self, graceful: bool) {
+        join_all(self.0.iter().map(|handle| handle.stop(graceful))).await;
+    }
+}
+
+/// The HTTP servers of the validator, one per listener.
+pub struct ApiServer {
+    handle: ApiServerHandle,
+    run: BoxFuture<'static, io::Result<()>>,
+}
+
+impl ApiServer {
+    /// Runs the servers side by side, until all of them stop or one of them fails.
+    pub fn all(servers: impl IntoIterator<Item = Server>) -> Self {
+        let servers: Vec<Server> = servers.into_iter().collect();
+        ApiServer {
+            handle: ApiServerHandle(servers.iter().map(Server::handle).collect()),
+            run: try_join_all(servers).map(|result| result.map(|_| ())).boxed(),
+        }
+    }
+
+    pub fn handle(&self) -> ApiServerHandle {
+        self.handle.clone()
+    }
+}
+
+impl From<Server> for ApiServer {
+    fn from(server: Server) -> Self {
+        ApiServer::all([server])
+    }
+}
+
+impl Future for ApiServer {
+    type Output = io::Result<()>;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        self.run.as_mut().poll(cx)
+    }
+}
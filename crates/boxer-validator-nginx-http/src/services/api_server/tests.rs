@@ -0,0 +1,42 @@
+use super::*;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use std::net::{SocketAddr, TcpListener};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+fn server(listener: TcpListener, body: &'static str) -> Server {
+    HttpServer::new(move || App::new().route("/", web::get().to(move || async move { HttpResponse::Ok().body(body) })))
+        .disable_signals()
+        .workers(1)
+        .listen(listener)
+        .unwrap()
+        .run()
+}
+
+async fn get(address: SocketAddr) -> String {
+    let mut stream = TcpStream::connect(address).await.unwrap();
+    stream
+        .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).await.unwrap();
+    response.split("\r\n\r\n").nth(1).unwrap_or_default().to_string()
+}
+
+#[actix_web::test]
+async fn test_servers_run_side_by_side_and_stop_together() {
+    let data_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let admin_listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let data_address = data_listener.local_addr().unwrap();
+    let admin_address = admin_listener.local_addr().unwrap();
+    let server = ApiServer::all([server(data_listener, "data"), server(admin_listener, "admin")]);
+    let handle = server.handle();
+    let running = tokio::spawn(server);
+
+    assert_eq!(get(data_address).await, "data");
+    assert_eq!(get(admin_address).await, "admin");
+
+    handle.stop(true).await;
+    running.await.unwrap().unwrap();
+}
@@ -0,0 +1,107 @@
+#[cfg(test)]
+mod tests;
+
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::audit_facade::to_audit_record::ToAuditRecord;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use boxer_core::services::validation_service::required_claims::RequiredClaims;
+use k8s_openapi::chrono::{SecondsFormat, Utc};
+use serde::Serialize;
+use std::sync::Arc;
+
+/// Changes recorded in the audit trail.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOperation {
+    Upsert,
+    Delete,
+}
+
+/// A single admin API write, with the document content before and after the change.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct AuditTrailEvent {
+    /// RFC 3339 timestamp of the change.
+    pub timestamp: String,
+    /// Principal of the internal token that made the change.
+    pub principal: String,
+    pub operation: AuditOperation,
+    /// Document kind, e.g. `action_set`.
+    pub kind: String,
+    pub schema: String,
+    pub id: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+impl AuditTrailEvent {
+    pub fn new(principal: &str, operation: AuditOperation, kind: &str, schema: &str, id: &str) -> Self {
+        AuditTrailEvent {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            principal: principal.to_string(),
+            operation,
+            kind: kind.to_string(),
+            schema: schema.to_string(),
+            id: id.to_string(),
+            before: None,
+            after: None,
+        }
+    }
+
+    /// Starts an event for a change made with validated internal token claims.
+    pub fn by(claims: &BoxerClaims, operation: AuditOperation, kind: &str, schema: &str, id: &str) -> Self {
+        let principal = claims.get_principal().uid().to_string();
+        AuditTrailEvent::new(&principal, operation, kind, schema, id)
+    }
+
+    pub fn with_before(mut self, before: Option<&impl ToAuditRecord>) -> Self {
+        self.before = before.map(|value| value.to_audit_record());
+        self
+    }
+
+    pub fn with_after(mut self, after: Option<&impl ToAuditRecord>) -> Self {
+        self.after = after.map(|value| value.to_audit_record());
+        self
+    }
+}
+
+impl From<AuditTrailEvent> for AuditEvent {
+    fn from(event: AuditTrailEvent) -> Self {
+        AuditEvent::default().with_details(serde_json::to_value(event).unwrap_or_default())
+    }
+}
+
+/// Sends every audit event to each of the chained writers.
+pub struct AuditWriterChain {
+    writers: Vec<Arc<dyn AuditWriter>>,
+}
+
+impl AuditWriterChain {
+    pub fn new(writers: Vec<Arc<dyn AuditWriter>>) -> Self {
+        AuditWriterChain { writers }
+    }
+}
+
+impl AuditWriter for AuditWriterChain {
+    fn write(&self, event: AuditEvent) {
+        for writer in &self.writers {
+            writer.write(event.clone());
+        }
+    }
+}
+
+/// Records admin API changes with the configured audit writer.
+pub struct AuditTrail {
+    writer: Arc<dyn AuditWriter>,
+}
+
+impl AuditTrail {
+    pub fn new(writer: Arc<dyn AuditWriter>) -> Self {
+        AuditTrail { writer }
+    }
+
+    /// Writing is best-effort: the change is already applied, so failures are reported by the writer.
+    pub fn record(&self, event: AuditTrailEvent) {
+        self.writer.write(event.into());
+    }
+}
@@ -0,0 +1,76 @@
+use super::*;
+use crate::services::json_lines::{JsonLinesWriter, append_to, temp_path};
+use std::sync::Mutex;
+
+struct Record(&'static str);
+
+impl ToAuditRecord for Record {
+    fn to_audit_record(&self) -> String {
+        self.0.to_string()
+    }
+}
+
+#[derive(Default)]
+struct CountingWriter(Mutex<usize>);
+
+impl AuditWriter for CountingWriter {
+    fn write(&self, _event: AuditEvent) {
+        *self.0.lock().unwrap() += 1;
+    }
+}
+
+#[test]
+fn test_event_records_before_and_after() {
+    let event = AuditTrailEvent::new(
+        "PhotoApp::User::\"root\"",
+        AuditOperation::Upsert,
+        "policy_set",
+        "photo-app",
+        "admins",
+    )
+    .with_before(None::<&Record>)
+    .with_after(Some(&Record("permit(principal, action, resource);")));
+
+    assert_eq!(event.before, None);
+    assert_eq!(event.after.as_deref(), Some("permit(principal, action, resource);"));
+}
+
+#[test]
+fn test_chain_writes_to_every_writer() {
+    let first = Arc::new(CountingWriter::default());
+    let second = Arc::new(CountingWriter::default());
+    let trail = AuditTrail::new(Arc::new(AuditWriterChain::new(vec![first.clone(), second.clone()])));
+
+    trail.record(AuditTrailEvent::new(
+        "root",
+        AuditOperation::Delete,
+        "schema",
+        "photo-app",
+        "photo-app",
+    ));
+
+    assert_eq!(*first.0.lock().unwrap(), 1);
+    assert_eq!(*second.0.lock().unwrap(), 1);
+}
+
+#[tokio::test]
+async fn test_json_lines_writer_appends_events() {
+    let path = temp_path("boxer-audit-trail");
+    let writer = JsonLinesWriter::spawn(append_to(&path).await.unwrap());
+    writer.write(AuditTrailEvent::new("root", AuditOperation::Upsert, "schema", "photo-app", "photo-app").into());
+    writer.flush().await;
+    drop(writer);
+    // Reopening must not truncate the trail
+    let writer = JsonLinesWriter::spawn(append_to(&path).await.unwrap());
+    writer.write(AuditTrailEvent::new("root", AuditOperation::Delete, "schema", "photo-app", "photo-app").into());
+    writer.flush().await;
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(content.lines().count(), 2);
+    assert!(
+        content
+            .lines()
+            .all(|line| serde_json::from_str::<serde_json::Value>(line).is_ok())
+    );
+}
@@ -1,30 +1,49 @@
 mod configuration;
 
+use crate::services::enforcement_mode::SchemaEnforcementModes;
+use crate::services::readiness::Readiness;
 use crate::services::repositories::action_repository::ActionReadOnlyRepository;
 use crate::services::repositories::action_repository::action_discovery_document::ActionDiscoveryDocument;
-use crate::services::repositories::action_repository::read_write::ActionDataRepository;
+use crate::services::repositories::action_repository::public_routes::PublicRouteRepository;
+use crate::services::repositories::action_repository::read_write::{ActionDataRepository, ActionSetListRepository};
+use crate::services::repositories::list_repository::SchemaListRepository;
 use crate::services::repositories::lookup_trie::backend::{AssociatedRepository, ReadOnlyRepositoryBackend};
+use crate::services::repositories::lookup_trie::route_table::RouteTables;
 use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
-use crate::services::repositories::policy_repository::read_only::PolicyRepositoryData;
-use crate::services::repositories::policy_repository::read_write::PolicyDataRepository;
+use crate::services::repositories::policy_repository::read_only::{PolicyRepositoryData, ShadowPolicyRepository};
+use crate::services::repositories::policy_repository::read_write::{PolicyDataRepository, PolicySetListRepository};
 use crate::services::repositories::resource_repository::ResourceReadOnlyRepository;
-use crate::services::repositories::resource_repository::read_write::ResourceDiscoveryDocumentRepository;
+use crate::services::repositories::resource_repository::read_write::{
+    ResourceDiscoveryDocumentRepository, ResourceSetListRepository,
+};
 use crate::services::repositories::resource_repository::resource_discovery_document::ResourceDiscoveryDocument;
+use crate::services::schema_catalog::SchemaUsageIndex;
 use boxer_core::services::backends::Backend;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaRepository;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::KubernetesResourceWatcherRunner;
 use boxer_core::services::service_provider::ServiceProvider;
 use boxer_core::services::validation_service::path_segment::PathSegment;
 use boxer_core::services::validation_service::request_segment::RequestSegment;
 use cedar_policy::{EntityUid, PolicySet};
+use kube::Client;
 use std::sync::Arc;
-use std::sync::atomic::AtomicBool;
+use tokio::task::JoinHandle;
 
 pub struct KubernetesBackend {
     schema_repository: Arc<SchemaRepository>,
+    schema_usage_index: Arc<SchemaUsageIndex>,
+    schema_enforcement_modes: Arc<SchemaEnforcementModes>,
+    route_tables: Arc<RouteTables>,
+    schema_list_repository: Arc<SchemaListRepository>,
+    action_list_repository: Arc<ActionSetListRepository>,
+    resource_list_repository: Arc<ResourceSetListRepository>,
+    policy_list_repository: Arc<PolicySetListRepository>,
     action_repository: Arc<ActionDataRepository>,
     resource_repository: Arc<ResourceDiscoveryDocumentRepository>,
     policy_repository: Arc<PolicyDataRepository>,
-    readiness_state: Arc<AtomicBool>,
+    readiness: Arc<Readiness>,
+    client: Client,
+    schema_enforcement_modes_watch: JoinHandle<()>,
 
     action_lookup_table_listener: Arc<
         ReadOnlyRepositoryBackend<
@@ -42,6 +61,8 @@ pub struct KubernetesBackend {
             EntityUid,
         >,
     >,
+    public_route_listener:
+        Arc<ReadOnlyRepositoryBackend<PublicRouteRepository, ActionDiscoveryDocument, Vec<RequestSegment>, EntityUid>>,
     policy_lookup_watcher: Arc<ReadOnlyRepositoryBackend<PolicyRepositoryData, PolicyDocument, String, PolicySet>>,
 }
 
@@ -51,12 +72,66 @@ impl ServiceProvider<Arc<SchemaRepository>> for KubernetesBackend {
     }
 }
 
+impl ServiceProvider<Arc<SchemaUsageIndex>> for KubernetesBackend {
+    fn get(&self) -> Arc<SchemaUsageIndex> {
+        self.schema_usage_index.clone()
+    }
+}
+
+impl ServiceProvider<Client> for KubernetesBackend {
+    fn get(&self) -> Client {
+        self.client.clone()
+    }
+}
+
+impl ServiceProvider<Arc<SchemaEnforcementModes>> for KubernetesBackend {
+    fn get(&self) -> Arc<SchemaEnforcementModes> {
+        self.schema_enforcement_modes.clone()
+    }
+}
+
+impl ServiceProvider<Arc<RouteTables>> for KubernetesBackend {
+    fn get(&self) -> Arc<RouteTables> {
+        self.route_tables.clone()
+    }
+}
+
+impl ServiceProvider<Arc<SchemaListRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<SchemaListRepository> {
+        self.schema_list_repository.clone()
+    }
+}
+
+impl ServiceProvider<Arc<ActionSetListRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<ActionSetListRepository> {
+        self.action_list_repository.clone()
+    }
+}
+
+impl ServiceProvider<Arc<ResourceSetListRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<ResourceSetListRepository> {
+        self.resource_list_repository.clone()
+    }
+}
+
+impl ServiceProvider<Arc<PolicySetListRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<PolicySetListRepository> {
+        self.policy_list_repository.clone()
+    }
+}
+
 impl ServiceProvider<Arc<AssociatedRepository<(String, Vec<RequestSegment>), EntityUid>>> for KubernetesBackend {
     fn get(&self) -> Arc<AssociatedRepository<(String, Vec<RequestSegment>), EntityUid>> {
         self.action_lookup_table_listener.get().clone()
     }
 }
 
+impl ServiceProvider<Arc<PublicRouteRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<PublicRouteRepository> {
+        self.public_route_listener.update_handler()
+    }
+}
+
 impl ServiceProvider<Arc<ActionDataRepository>> for KubernetesBackend {
     fn get(&self) -> Arc<ActionDataRepository> {
         self.action_repository.clone()
@@ -81,6 +156,12 @@ impl ServiceProvider<Arc<AssociatedRepository<String, PolicySet>>> for Kubernete
     }
 }
 
+impl ServiceProvider<Arc<ShadowPolicyRepository>> for KubernetesBackend {
+    fn get(&self) -> Arc<ShadowPolicyRepository> {
+        self.policy_lookup_watcher.update_handler().shadow()
+    }
+}
+
 impl ServiceProvider<Arc<PolicyDataRepository>> for KubernetesBackend {
     fn get(&self) -> Arc<PolicyDataRepository> {
         self.policy_repository.clone()
@@ -92,11 +173,23 @@ impl Backend for KubernetesBackend {
 }
 
 pub trait ValidatorBackend: Send + Sync + Backend {
-    fn readiness_state(&self) -> Arc<AtomicBool>;
+    fn readiness(&self) -> Arc<Readiness>;
+
+    /// Stops the background watchers owned by the backend.
+    fn stop(&self) -> anyhow::Result<()>;
 }
 
 impl ValidatorBackend for KubernetesBackend {
-    fn readiness_state(&self) -> Arc<AtomicBool> {
-        self.readiness_state.clone()
+    fn readiness(&self) -> Arc<Readiness> {
+        self.readiness.clone()
+    }
+
+    fn stop(&self) -> anyhow::Result<()> {
+        self.action_lookup_table_listener.stop()?;
+        self.resource_lookup_table_listener.stop()?;
+        self.public_route_listener.stop()?;
+        self.policy_lookup_watcher.stop()?;
+        self.schema_enforcement_modes_watch.abort();
+        Ok(())
     }
 }
@@ -1,15 +1,26 @@
 use crate::services::backends::BackendBuilder;
 use crate::services::backends::kubernetes::KubernetesBackend;
 use crate::services::configuration::models::KubernetesBackendSettings;
+use crate::services::enforcement_mode::SchemaEnforcementModes;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
-use crate::services::repositories::action_repository::read_write::ActionDataRepository;
+use crate::services::readiness::{Readiness, ReportingUpdateHandler, WatcherStatus};
+use crate::services::repositories::action_repository::action_discovery_document::ActionDiscoveryDocument;
+use crate::services::repositories::action_repository::public_routes::PublicRouteRepository;
+use crate::services::repositories::action_repository::read_write::{ActionDataRepository, ActionSetListRepository};
+use crate::services::repositories::list_repository::SchemaListRepository;
 use crate::services::repositories::lookup_trie::backend::ReadOnlyRepositoryBackend;
+use crate::services::repositories::lookup_trie::route_table::RouteTables;
 use crate::services::repositories::lookup_trie::schema_bound_trie_repository::SchemaBoundedTrieRepositoryData;
 use crate::services::repositories::lookup_trie::{EntityCollectionResource, SchemaBoundResource};
 use crate::services::repositories::policy_repository;
 use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
 use crate::services::repositories::policy_repository::read_only::PolicyRepositoryData;
-use crate::services::repositories::resource_repository::read_write::ResourceDiscoveryDocumentRepository;
+use crate::services::repositories::policy_repository::read_write::PolicySetListRepository;
+use crate::services::repositories::resource_repository::read_write::{
+    ResourceDiscoveryDocumentRepository, ResourceSetListRepository,
+};
+use crate::services::route_template::NormalizeSegment;
+use crate::services::schema_catalog::SchemaUsageIndex;
 use anyhow::bail;
 use async_trait::async_trait;
 use boxer_core::services::audit::audit_facade::WithAuditFacade;
@@ -26,15 +37,16 @@ use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::{
     KubernetesResourceWatcher, KubernetesResourceWatcherRunner,
 };
 use boxer_core::services::backends::kubernetes::logging_update_handler::LoggingUpdateHandler;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
 use boxer_core::services::observability::open_telemetry::tracing::tracing_facade::WithTracingFacade;
 use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
 use cedar_policy::{EntityUid, PolicySet};
 use k8s_openapi::NamespaceResourceScope;
-use kube::Config;
+use kube::{Client, Config};
 use std::fmt::Debug;
 use std::hash::Hash;
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::Duration;
 use tokio;
 
@@ -50,14 +62,20 @@ impl BackendConfiguration for BackendBuilder {
     ) -> anyhow::Result<Arc<Self::InitializedBackend>> {
         let kubeconfig = Self::get_kubeconfig(settings).await?;
         let owner_mark = ObjectOwnerMark::new(&settings.resource_owner_label, &instance_name);
+        let readiness = Arc::new(
+            Readiness::new(settings.max_watch_error_duration.into())
+                .with_max_event_age(settings.max_watch_event_age.map(Into::into)),
+        );
 
-        let (schema_repository, schema_repository_readiness) = Self::create_repository(
+        let schema_repository = Self::create_repository(
             &settings.namespace,
             kubeconfig.clone(),
             owner_mark.clone(),
             settings.operation_timeout.into(),
+            readiness.register("schema_repository"),
         )
         .await?;
+        let schema_list_repository: Arc<SchemaListRepository> = schema_repository.clone();
         let schema_repository = schema_repository.with_audit(Arc::new(LogAuditService::new()));
 
         let action_lookup_table_listener = Self::create_lookup_trie(
@@ -66,17 +84,28 @@ impl BackendConfiguration for BackendBuilder {
             owner_mark.clone(),
             settings.operation_timeout.into(),
             "action_lookup".to_string(),
+            readiness.register("action_lookup"),
+        )
+        .await?;
+
+        let public_route_listener = Self::create_public_routes(
+            &settings.namespace,
+            kubeconfig.clone(),
+            owner_mark.clone(),
+            settings.operation_timeout.into(),
+            readiness.register("public_routes"),
         )
         .await?;
 
-        let (action_repository, action_repository_readiness): (_, tokio::sync::oneshot::Receiver<()>) =
-            Self::create_repository(
-                &settings.namespace,
-                kubeconfig.clone(),
-                owner_mark.clone(),
-                settings.operation_timeout.into(),
-            )
-            .await?;
+        let action_repository = Self::create_repository(
+            &settings.namespace,
+            kubeconfig.clone(),
+            owner_mark.clone(),
+            settings.operation_timeout.into(),
+            readiness.register("action_repository"),
+        )
+        .await?;
+        let action_list_repository: Arc<ActionSetListRepository> = action_repository.clone();
         let action_repository: Arc<ActionDataRepository> =
             action_repository.with_audit(Arc::new(LogAuditService::new()));
 
@@ -86,17 +115,19 @@ impl BackendConfiguration for BackendBuilder {
             owner_mark.clone(),
             settings.operation_timeout.into(),
             "resource_lookup".to_string(),
+            readiness.register("resource_lookup"),
         )
         .await?;
 
-        let (resource_repository, resource_repository_readiness): (_, tokio::sync::oneshot::Receiver<()>) =
-            Self::create_repository(
-                &settings.namespace,
-                kubeconfig.clone(),
-                owner_mark.clone(),
-                settings.operation_timeout.into(),
-            )
-            .await?;
+        let resource_repository = Self::create_repository(
+            &settings.namespace,
+            kubeconfig.clone(),
+            owner_mark.clone(),
+            settings.operation_timeout.into(),
+            readiness.register("resource_repository"),
+        )
+        .await?;
+        let resource_list_repository: Arc<ResourceSetListRepository> = resource_repository.clone();
         let resource_repository: Arc<ResourceDiscoveryDocumentRepository> =
             resource_repository.with_audit(Arc::new(LogAuditService::new()));
 
@@ -105,36 +136,57 @@ impl BackendConfiguration for BackendBuilder {
             kubeconfig.clone(),
             owner_mark.clone(),
             settings.operation_timeout.into(),
+            readiness.register("policy_lookup"),
         )
         .await?;
 
-        let (policy_repository, policy_repository_readiness) = Self::create_repository(
+        let policy_repository = Self::create_repository(
             &settings.namespace,
             kubeconfig.clone(),
             owner_mark.clone(),
             settings.operation_timeout.into(),
+            readiness.register("policy_repository"),
         )
         .await?;
+        let policy_list_repository: Arc<PolicySetListRepository> = policy_repository.clone();
         let policy_repository = policy_repository.with_audit(Arc::new(LogAuditService::new()));
 
-        let readiness_state = Arc::new(AtomicBool::new(false));
-        let readiness_state_update = readiness_state.clone();
-        tokio::spawn(async move {
-            let is_ready = schema_repository_readiness.await.is_ok()
-                && action_repository_readiness.await.is_ok()
-                && resource_repository_readiness.await.is_ok()
-                && policy_repository_readiness.await.is_ok();
-            readiness_state_update.store(is_ready, Ordering::Release);
+        let client = Client::try_from(kubeconfig.clone())?;
+        let schema_enforcement_modes = Arc::new(SchemaEnforcementModes::default());
+        let schema_enforcement_modes_watch = schema_enforcement_modes.watch(
+            client.clone(),
+            &settings.namespace,
+            &owner_mark,
+            readiness.register("schema_enforcement_modes"),
+        );
+        let route_tables = Arc::new(RouteTables {
+            actions: action_lookup_table_listener.update_handler(),
+            resources: resource_lookup_table_listener.update_handler(),
         });
 
+        let schema_usage_index = Arc::new(SchemaUsageIndex::new(
+            action_lookup_table_listener.update_handler(),
+            resource_lookup_table_listener.update_handler(),
+        ));
+
         Ok(Arc::new(KubernetesBackend {
             schema_repository,
+            schema_usage_index,
+            schema_enforcement_modes,
+            route_tables,
+            schema_list_repository,
+            action_list_repository,
+            resource_list_repository,
+            policy_list_repository,
             action_repository,
             resource_repository,
             policy_repository,
-            readiness_state,
+            readiness,
+            client,
+            schema_enforcement_modes_watch,
             action_lookup_table_listener,
             resource_lookup_table_listener,
+            public_route_listener,
             policy_lookup_watcher,
         }))
     }
@@ -163,15 +215,14 @@ impl BackendBuilder {
         }
     }
 
+    /// Starts a repository backed by a boxer-core resource manager whose watch events are reported to `status`.
     pub async fn create_repository<R>(
         namespace: &str,
         kubeconfig: Config,
         owner_mark: ObjectOwnerMark,
         operation_timeout: Duration,
-    ) -> anyhow::Result<(
-        Arc<KubernetesRepository<R, GenericKubernetesResourceManager<R>>>,
-        tokio::sync::oneshot::Receiver<()>,
-    )>
+        status: Arc<WatcherStatus>,
+    ) -> anyhow::Result<Arc<KubernetesRepository<R, GenericKubernetesResourceManager<R>>>>
     where
         R: kube::Resource<Scope = NamespaceResourceScope>
             + SoftDeleteResource
@@ -188,26 +239,34 @@ impl BackendBuilder {
             owner_mark,
             operation_timeout,
         };
+        let update_handler = ReportingUpdateHandler::new(LoggingUpdateHandler, status.clone());
         let (resource_manager, readiness_rx) =
-            GenericKubernetesResourceManager::start(config, Arc::new(LoggingUpdateHandler)).await?;
-        let repository =
-            KubernetesRepository::<R, GenericKubernetesResourceManager<R>>::start(resource_manager, operation_timeout)
-                .await
-                .map(Arc::new)?;
-        Ok((repository, readiness_rx))
+            GenericKubernetesResourceManager::start(config, Arc::new(update_handler)).await?;
+        // The resource manager reports the end of its initial sync separately from the watch events
+        tokio::spawn(async move {
+            if readiness_rx.await.is_ok() {
+                status.mark_synced();
+            }
+        });
+        KubernetesRepository::<R, GenericKubernetesResourceManager<R>>::start(resource_manager, operation_timeout)
+            .await
+            .map(Arc::new)
     }
 
-    pub async fn create_lookup_trie<R, K>(
+    /// Starts a lookup trie keyed by `K` that is queried with keys made of `Q` segments.
+    pub async fn create_lookup_trie<R, K, Q>(
         namespace: &str,
         kubeconfig: Config,
         owner_mark: ObjectOwnerMark,
         operation_timeout: Duration,
         operation_name: String,
+        status: Arc<WatcherStatus>,
     ) -> anyhow::Result<
-        Arc<ReadOnlyRepositoryBackend<SchemaBoundedTrieRepositoryData<K>, R, (String, Vec<K>), EntityUid>>,
+        Arc<ReadOnlyRepositoryBackend<SchemaBoundedTrieRepositoryData<K>, R, (String, Vec<Q>), EntityUid>>,
     >
     where
-        K: Debug + Ord + Clone + Send + Sync + Hash + 'static + ParametrizedMatcher,
+        K: Debug + Ord + Clone + Send + Sync + Hash + 'static + ParametrizedMatcher + NormalizeSegment,
+        SchemaBoundedTrieRepositoryData<K>: ReadOnlyRepository<(String, Vec<Q>), EntityUid, ReadError = anyhow::Error>,
         R: kube::Resource<Scope = NamespaceResourceScope>
             + SoftDeleteResource
             + EntityCollectionResource<K>
@@ -226,7 +285,30 @@ impl BackendBuilder {
             operation_timeout,
         };
         let lookup_trie = Arc::new(SchemaBoundedTrieRepositoryData::<K>::new());
-        let mut r = ReadOnlyRepositoryBackend::new(lookup_trie.clone(), lookup_trie.with_tracing(operation_name));
+        let repository =
+            WithTracingFacade::<(String, Vec<Q>), EntityUid>::with_tracing(lookup_trie.clone(), operation_name);
+        let mut r = ReadOnlyRepositoryBackend::new(lookup_trie, repository).with_status(status);
+        r.start(config).await?;
+        Ok(Arc::new(r))
+    }
+
+    pub async fn create_public_routes(
+        namespace: &str,
+        kubeconfig: Config,
+        owner_mark: ObjectOwnerMark,
+        operation_timeout: Duration,
+        status: Arc<WatcherStatus>,
+    ) -> anyhow::Result<
+        Arc<ReadOnlyRepositoryBackend<PublicRouteRepository, ActionDiscoveryDocument, Vec<RequestSegment>, EntityUid>>,
+    > {
+        let config = KubernetesResourceManagerConfig {
+            namespace: namespace.to_string(),
+            kubeconfig: kubeconfig.clone(),
+            owner_mark,
+            operation_timeout,
+        };
+        let public_routes = Arc::new(PublicRouteRepository::new());
+        let mut r = ReadOnlyRepositoryBackend::new(public_routes.clone(), public_routes).with_status(status);
         r.start(config).await?;
         Ok(Arc::new(r))
     }
@@ -236,6 +318,7 @@ impl BackendBuilder {
         kubeconfig: Config,
         owner_mark: ObjectOwnerMark,
         operation_timeout: Duration,
+        status: Arc<WatcherStatus>,
     ) -> anyhow::Result<Arc<ReadOnlyRepositoryBackend<PolicyRepositoryData, PolicyDocument, String, PolicySet>>>
     where
         K: Ord,
@@ -250,7 +333,8 @@ impl BackendBuilder {
         let mut r = ReadOnlyRepositoryBackend::new(
             lookup_trie.clone(),
             lookup_trie.with_tracing("policy_lookup".to_string()),
-        );
+        )
+        .with_status(status);
         r.start(config).await?;
         Ok(Arc::new(r))
     }
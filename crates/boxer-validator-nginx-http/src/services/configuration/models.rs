@@ -1,10 +1,11 @@
-use anyhow::Result;
+use crate::services::enforcement_mode::EnforcementMode;
 use boxer_core::services::observability::open_telemetry::settings::OpenTelemetrySettings;
-use boxer_core::services::token_decryption_service::encryption_keys::EncryptionKeys;
 use boxer_core::services::token_decryption_service::token_settings::TokenValidationSettings;
 use duration_string::DurationString;
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::net::SocketAddr;
+use std::time::Duration;
 
 #[derive(Debug, Deserialize)]
 pub struct KubernetesBackendSettings {
@@ -14,6 +15,17 @@ pub struct KubernetesBackendSettings {
     pub namespace: String,
     pub resource_owner_label: String,
     pub operation_timeout: DurationString,
+    /// Readiness fails once a watcher keeps failing for longer than this.
+    #[serde(default = "default_max_watch_error_duration")]
+    pub max_watch_error_duration: DurationString,
+    /// Readiness fails once a watcher has not received an event for longer than this. Unset by default,
+    /// since a watcher of a namespace without changes legitimately stays quiet.
+    #[serde(default)]
+    pub max_watch_event_age: Option<DurationString>,
+}
+
+fn default_max_watch_error_duration() -> DurationString {
+    Duration::from_secs(60).into()
 }
 
 #[derive(Debug, Deserialize)]
@@ -21,18 +33,400 @@ pub struct BackendSettings {
     pub kubernetes: KubernetesBackendSettings,
 }
 
+#[derive(Debug, Deserialize, Default)]
+pub struct AdminApiSettings {
+    /// Fully qualified entity types of token principals that `boxer-admin` policies may refer to,
+    /// e.g. `PhotoApp::User`.
+    #[serde(default)]
+    pub principal_types: Vec<String>,
+    /// Where the admin API and Swagger UI are served.
+    #[serde(default)]
+    pub listener: AdminListener,
+}
+
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminListener {
+    /// On `listen_address`, next to the token review endpoint.
+    #[default]
+    Shared,
+    /// On a listener of its own, so only the token review endpoint is exposed on `listen_address`.
+    Separate {
+        listen_address: SocketAddr,
+        /// Worker threads of the listener; defaults to the number of CPUs.
+        #[serde(default)]
+        workers: Option<usize>,
+    },
+    /// Not served at all.
+    Disabled,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct AuditSettings {
+    /// Append the admin API audit trail to this JSON-lines file in addition to the application log.
+    pub trail_file: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionLogFileSettings {
+    pub path: String,
+    /// Rotate the file once it grows past this size.
+    #[serde(default = "default_decision_log_max_size_bytes")]
+    pub max_size_bytes: u64,
+    /// Number of rotated files to keep next to the active one.
+    #[serde(default = "default_decision_log_max_files")]
+    pub max_files: usize,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionLogWebhookSettings {
+    pub url: String,
+    /// Maximum number of decisions sent in a single request.
+    #[serde(default = "default_decision_log_batch_size")]
+    pub batch_size: usize,
+    /// Send a partial batch once it has been waiting for this long.
+    #[serde(default = "default_decision_log_flush_interval")]
+    pub flush_interval: DurationString,
+    #[serde(default = "default_decision_log_max_retries")]
+    pub max_retries: u32,
+    /// Delay before the first retry; doubled on every further attempt.
+    #[serde(default = "default_decision_log_retry_backoff")]
+    pub retry_backoff: DurationString,
+    /// Time a single request may take, including the connection; it is retried like any other failure.
+    #[serde(default = "default_decision_log_request_timeout")]
+    pub request_timeout: DurationString,
+    /// Time to establish the connection to the webhook.
+    #[serde(default = "default_decision_log_connect_timeout")]
+    pub connect_timeout: DurationString,
+    /// Time queued decisions are given to be delivered on shutdown before they are dropped.
+    #[serde(default = "default_decision_log_shutdown_timeout")]
+    pub shutdown_timeout: DurationString,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecisionSamplingSettings {
+    /// Percentage of allowed decisions to record. Denied decisions are always recorded.
+    #[serde(default = "default_decision_allow_percent")]
+    pub allow_percent: f64,
+    /// Overrides `allow_percent` for the listed validator schemas.
+    #[serde(default)]
+    pub schema_allow_percent: HashMap<String, f64>,
+}
+
+impl Default for DecisionSamplingSettings {
+    fn default() -> Self {
+        DecisionSamplingSettings {
+            allow_percent: default_decision_allow_percent(),
+            schema_allow_percent: HashMap::new(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DecisionRedactionSettings {
+    /// Query parameters whose values are masked in recorded URLs, e.g. `access_token`.
+    /// Names are matched case-insensitively after percent-decoding.
+    #[serde(default)]
+    pub query_parameters: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct DecisionLogSettings {
+    /// Print decisions to stdout as JSON lines.
+    #[serde(default)]
+    pub stdout: bool,
+    pub file: Option<DecisionLogFileSettings>,
+    pub webhook: Option<DecisionLogWebhookSettings>,
+    /// Claims of the validated internal token copied into decision records, e.g. `sub`.
+    /// Claims that are not listed are never recorded.
+    #[serde(default)]
+    pub claims: Vec<String>,
+    /// Record the whole validated internal token, so that the policy simulation can replay decisions
+    /// with the real principal entity. The allowlist above does not apply to the recorded token.
+    #[serde(default)]
+    pub replayable: bool,
+    #[serde(default)]
+    pub sampling: DecisionSamplingSettings,
+    #[serde(default)]
+    pub redaction: DecisionRedactionSettings,
+}
+
+/// What a token review does when the request matches no registered action or resource.
+#[derive(Debug, Deserialize, Default, Clone, PartialEq)]
+#[serde(tag = "behavior", rename_all = "snake_case")]
+pub enum UnmatchedRouteBehavior {
+    /// Reject the request with 401 like any other request that fails validation.
+    #[default]
+    Unauthorized,
+    /// Reject the request with 403.
+    Deny,
+    /// Let the request through without evaluating policies.
+    Allow,
+    /// Let Cedar policies decide, with the missing action or resource replaced by these UIDs.
+    Map { action: String, resource: String },
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct UnmatchedRouteSettings {
+    #[serde(default)]
+    pub default: UnmatchedRouteBehavior,
+    /// Overrides by validator schema.
+    #[serde(default)]
+    pub schemas: HashMap<String, UnmatchedRouteBehavior>,
+    /// Overrides by request hostname; these take precedence over schema overrides.
+    #[serde(default)]
+    pub hostnames: HashMap<String, UnmatchedRouteBehavior>,
+}
+
+/// Where a token review looks for the token.
+#[derive(Debug, Deserialize, Clone, PartialEq)]
+#[serde(tag = "source", rename_all = "snake_case")]
+pub enum TokenSource {
+    /// `Authorization: Bearer <token>`.
+    Authorization,
+    Cookie {
+        name: String,
+    },
+    Header {
+        name: String,
+    },
+    /// A query parameter of the request under review, read only for websocket upgrades since websocket clients
+    /// cannot set headers. Its value is redacted from decision logs.
+    QueryParameter {
+        name: String,
+    },
+}
+
+fn default_token_sources() -> Vec<TokenSource> {
+    vec![TokenSource::Authorization]
+}
+
+/// Headers the ingress controller uses to pass the request under review.
+#[derive(Debug, Deserialize)]
+pub struct RequestHeaderSettings {
+    #[serde(default = "default_original_url_header")]
+    pub original_url: String,
+    #[serde(default = "default_original_method_header")]
+    pub original_method: String,
+    /// Addresses or CIDR ranges of the proxies allowed to set these headers. Every peer is trusted when empty.
+    #[serde(default)]
+    pub trusted_proxies: Vec<String>,
+}
+
+impl Default for RequestHeaderSettings {
+    fn default() -> Self {
+        RequestHeaderSettings {
+            original_url: default_original_url_header(),
+            original_method: default_original_method_header(),
+            trusted_proxies: Vec::new(),
+        }
+    }
+}
+
+fn default_original_url_header() -> String {
+    "X-Original-Url".to_string()
+}
+
+fn default_original_method_header() -> String {
+    "X-Original-Method".to_string()
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct PrometheusSettings {
+    /// Serve `/metrics` for Prometheus to scrape, alongside pushing metrics over OTLP if that is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+    /// Listener serving `/metrics`.
+    #[serde(default)]
+    pub plane: MetricsPlane,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricsPlane {
+    /// The admin listener, or `listen_address` if the admin API has no listener of its own.
+    #[default]
+    Admin,
+    /// `listen_address`, next to the token review endpoint.
+    Data,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct ShutdownSettings {
+    /// Time between reporting not ready and stopping the HTTP server, so load balancers stop routing to the pod.
+    #[serde(default = "default_shutdown_drain_period")]
+    pub drain_period: DurationString,
+    /// Time in-flight requests are given to complete once the server stops accepting connections.
+    #[serde(default = "default_shutdown_timeout")]
+    pub timeout: DurationString,
+}
+
+impl Default for ShutdownSettings {
+    fn default() -> Self {
+        ShutdownSettings {
+            drain_period: default_shutdown_drain_period(),
+            timeout: default_shutdown_timeout(),
+        }
+    }
+}
+
+fn default_shutdown_drain_period() -> DurationString {
+    Duration::from_secs(5).into()
+}
+
+fn default_shutdown_timeout() -> DurationString {
+    Duration::from_secs(30).into()
+}
+
+/// Where the signing keys of internal tokens are loaded from.
+/// File and Secret sources hold a key set: `{"primary": "<key id>", "keys": {"<key id>": <keys>, ...}}`,
+/// where each entry has the format of `token_settings.keys`.
+#[derive(Debug, Deserialize, Clone, PartialEq, Default)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SigningKeySource {
+    /// `token_settings.keys`, loaded once at startup.
+    #[default]
+    Inline,
+    /// A key set file, reloaded when its content changes.
+    File {
+        path: String,
+        #[serde(default = "default_signing_key_poll_interval")]
+        poll_interval: DurationString,
+    },
+    /// An entry of a Secret in the backend namespace, reloaded when the Secret changes.
+    Secret { name: String, key: String },
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct SigningKeySettings {
+    #[serde(default)]
+    pub source: SigningKeySource,
+    /// Time a key removed from the key set is still accepted, so tokens signed with it can expire.
+    #[serde(default = "default_signing_key_grace_period")]
+    pub grace_period: DurationString,
+    /// Secret in the backend namespace the key ring is saved to, so that retired keys stay accepted across restarts
+    /// and keys removed while the validator was down are retired on startup. Without it, a restart forgets them.
+    pub key_ring_secret: Option<String>,
+}
+
+impl Default for SigningKeySettings {
+    fn default() -> Self {
+        SigningKeySettings {
+            source: SigningKeySource::default(),
+            grace_period: default_signing_key_grace_period(),
+            key_ring_secret: None,
+        }
+    }
+}
+
+fn default_signing_key_poll_interval() -> DurationString {
+    Duration::from_secs(30).into()
+}
+
+fn default_signing_key_grace_period() -> DurationString {
+    Duration::from_secs(24 * 60 * 60).into()
+}
+
+/// CA bundles that must have issued the client certificate, per scope. A scope without one accepts any client.
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct ClientCaSettings {
+    /// Required for `/api/v1/token`, e.g. the CA of the ingress controller.
+    pub token_review: Option<String>,
+    /// Required for the rest of `/api/v1`, e.g. the CA of operators.
+    pub admin_api: Option<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+pub struct TlsSettings {
+    /// PEM certificate chain served on the listener.
+    pub certificate: String,
+    /// PEM private key of the certificate.
+    pub private_key: String,
+    #[serde(default)]
+    pub client_ca: ClientCaSettings,
+    /// How often the certificate and CA files are checked for changes.
+    #[serde(default = "default_tls_reload_interval")]
+    pub reload_interval: DurationString,
+}
+
+fn default_tls_reload_interval() -> DurationString {
+    Duration::from_secs(60).into()
+}
+
+fn default_decision_allow_percent() -> f64 {
+    100.0
+}
+
+fn default_decision_log_max_size_bytes() -> u64 {
+    100 * 1024 * 1024
+}
+
+fn default_decision_log_max_files() -> usize {
+    5
+}
+
+fn default_decision_log_batch_size() -> usize {
+    100
+}
+
+fn default_decision_log_flush_interval() -> DurationString {
+    Duration::from_secs(5).into()
+}
+
+fn default_decision_log_max_retries() -> u32 {
+    3
+}
+
+fn default_decision_log_retry_backoff() -> DurationString {
+    Duration::from_secs(1).into()
+}
+
+fn default_decision_log_request_timeout() -> DurationString {
+    Duration::from_secs(10).into()
+}
+
+fn default_decision_log_connect_timeout() -> DurationString {
+    Duration::from_secs(5).into()
+}
+
+fn default_decision_log_shutdown_timeout() -> DurationString {
+    Duration::from_secs(10).into()
+}
+
 #[derive(Debug, Deserialize)]
 pub struct AppSettings {
     pub deploy_environment: String,
     pub listen_address: SocketAddr,
+    /// Worker threads of the `listen_address` listener; defaults to the number of CPUs.
+    #[serde(default)]
+    pub workers: Option<usize>,
     pub instance_name: String,
     pub backend: BackendSettings,
     pub opentelemetry: OpenTelemetrySettings,
     pub token_settings: TokenValidationSettings,
-}
-
-impl AppSettings {
-    pub fn get_signatures(&self) -> Result<EncryptionKeys> {
-        serde_json::from_str::<EncryptionKeys>(self.token_settings.keys.as_ref()).map_err(|e| anyhow::anyhow!(e))
-    }
+    #[serde(default)]
+    pub admin_api: AdminApiSettings,
+    #[serde(default)]
+    pub audit: AuditSettings,
+    #[serde(default)]
+    pub decision_log: DecisionLogSettings,
+    /// Overrides the enforcement mode annotated on every SchemaDocument.
+    #[serde(default)]
+    pub enforcement_mode: Option<EnforcementMode>,
+    #[serde(default)]
+    pub unmatched_routes: UnmatchedRouteSettings,
+    #[serde(default)]
+    pub shutdown: ShutdownSettings,
+    #[serde(default)]
+    pub prometheus: PrometheusSettings,
+    #[serde(default)]
+    pub request_headers: RequestHeaderSettings,
+    /// Sources of the token under review, in priority order.
+    #[serde(default = "default_token_sources")]
+    pub token_sources: Vec<TokenSource>,
+    #[serde(default)]
+    pub signing_keys: SigningKeySettings,
+    /// Serves HTTPS instead of HTTP on every listener.
+    #[serde(default)]
+    pub tls: Option<TlsSettings>,
 }
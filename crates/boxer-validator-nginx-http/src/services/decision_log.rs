@@ -0,0 +1,270 @@
+#[cfg(test)]
+mod tests;
+
+pub mod redaction;
+pub mod rotating_file_sink;
+pub mod sampling;
+pub mod webhook_sink;
+
+use crate::http::token_sources::TokenSources;
+use crate::services::audit_trail::AuditWriterChain;
+use crate::services::configuration::models::DecisionLogSettings;
+use crate::services::decision_log::redaction::RedactingWriter;
+use crate::services::decision_log::rotating_file_sink::RotatingFile;
+use crate::services::decision_log::sampling::SamplingWriter;
+use crate::services::decision_log::webhook_sink::WebhookAuditWriter;
+use crate::services::enforcement_mode::EnforcementMode;
+use crate::services::json_lines::JsonLinesWriter;
+use crate::services::repositories::lookup_trie::route_table::RouteDescription;
+use crate::services::shadow_policies::ShadowDecision;
+use crate::services::unmatched_routes::RouteFallback;
+use anyhow::Result;
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use k8s_openapi::chrono::{SecondsFormat, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::time::Duration;
+use utoipa::ToSchema;
+
+/// Outcome of a token review.
+#[derive(ToSchema, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Decision {
+    Allow,
+    Deny,
+}
+
+impl Decision {
+    pub fn of<T, E>(result: &Result<T, E>) -> Self {
+        if result.is_ok() {
+            Decision::Allow
+        } else {
+            Decision::Deny
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Decision::Allow => "allow",
+            Decision::Deny => "deny",
+        }
+    }
+}
+
+/// A structured record of a single authorization decision.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DecisionRecord {
+    /// RFC 3339 timestamp of the decision.
+    pub timestamp: String,
+    /// Name of the validator instance that made the decision.
+    pub instance: String,
+    pub principal: String,
+    /// Claims of the validated internal token listed in the `claims` setting.
+    #[serde(default, skip_serializing_if = "Map::is_empty")]
+    pub claims: Map<String, Value>,
+    /// The validated internal token, recorded only if the `replayable` setting is on.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token: Option<Value>,
+    /// Validator schema the principal's token is bound to.
+    pub schema: String,
+    /// The original URL of the reviewed request.
+    pub url: Option<String>,
+    /// The reviewed request as a normalized lookup key.
+    pub request: RouteDescription,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub decision: Decision,
+    /// Ids of the policies that determined the decision.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub determining_policies: Vec<String>,
+    /// `permissive` if a denied request was let through.
+    #[serde(default)]
+    pub enforcement: EnforcementMode,
+    /// Fallback applied if the request matched no registered action or resource.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub fallback: Option<RouteFallback>,
+    /// Explanation reported by the validator for a denied request, including the determining policies.
+    pub reason: Option<String>,
+    /// Would-be decision of the shadow policies, if any are registered for the schema.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub shadow: Option<ShadowDecision>,
+    pub latency_ms: f64,
+}
+
+impl DecisionRecord {
+    /// Parses a line of the decision log, where the record is the details of the written audit event.
+    pub fn from_json_line(line: &str) -> serde_json::Result<Self> {
+        let event: Value = serde_json::from_str(line)?;
+        let details = match &event {
+            Value::Object(fields) => fields
+                .values()
+                .find_map(|field| serde_json::from_value(field.clone()).ok()),
+            _ => None,
+        };
+        match details {
+            Some(record) => Ok(record),
+            None => serde_json::from_value(event),
+        }
+    }
+}
+
+impl From<DecisionRecord> for AuditEvent {
+    fn from(record: DecisionRecord) -> Self {
+        AuditEvent::default().with_details(serde_json::to_value(record).unwrap_or_default())
+    }
+}
+
+/// Ids of the policies that determined the Cedar decision recorded in the audit event.
+pub fn determining_policies(event: &AuditEvent) -> Vec<String> {
+    event
+        .response()
+        .map(|response| response.diagnostics().reason().map(|id| id.to_string()).collect())
+        .unwrap_or_default()
+}
+
+/// The token review a decision was made for.
+pub struct ReviewedRequest {
+    pub principal: String,
+    pub claims: Map<String, Value>,
+    pub token: Option<Value>,
+    pub url: Option<String>,
+    pub schema: String,
+    /// The reviewed request as a normalized lookup key.
+    pub request: RouteDescription,
+    /// Action and resource resolved while the request was validated.
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub determining_policies: Vec<String>,
+    pub enforcement: EnforcementMode,
+    pub fallback: Option<RouteFallback>,
+}
+
+/// Records token review decisions with the configured audit writers.
+/// Decisions are sampled and redacted before they reach any writer. Query parameters that may carry the token
+/// are always redacted, in addition to the ones listed in the settings.
+pub struct DecisionLog {
+    instance: String,
+    claims: Vec<String>,
+    replayable: bool,
+    writer: Option<SamplingWriter>,
+    webhook: Option<Arc<WebhookAuditWriter>>,
+}
+
+impl DecisionLog {
+    pub fn new(
+        settings: &DecisionLogSettings,
+        instance: String,
+        token_sources: &TokenSources,
+        writers: Vec<Arc<dyn AuditWriter>>,
+    ) -> Self {
+        let writer = (!writers.is_empty()).then(|| {
+            let writers = Arc::new(AuditWriterChain::new(writers));
+            let mut query_parameters = settings.redaction.query_parameters.clone();
+            query_parameters.extend(token_sources.query_parameters());
+            SamplingWriter::new(
+                &settings.sampling,
+                Arc::new(RedactingWriter::new(query_parameters, writers)),
+            )
+        });
+        DecisionLog {
+            instance,
+            claims: settings.claims.clone(),
+            replayable: settings.replayable,
+            writer,
+            webhook: None,
+        }
+    }
+
+    /// Creates the writers enabled in the settings. Must be called from within a Tokio runtime.
+    pub async fn from_settings(
+        settings: &DecisionLogSettings,
+        instance: String,
+        token_sources: &TokenSources,
+    ) -> Result<Self> {
+        let mut writers: Vec<Arc<dyn AuditWriter>> = Vec::new();
+        if settings.stdout {
+            writers.push(Arc::new(JsonLinesWriter::spawn(tokio::io::stdout())));
+        }
+        if let Some(file) = &settings.file {
+            let file = RotatingFile::open(&file.path, file.max_size_bytes, file.max_files).await?;
+            writers.push(Arc::new(JsonLinesWriter::spawn(file)));
+        }
+        let webhook = settings
+            .webhook
+            .as_ref()
+            .map(WebhookAuditWriter::start)
+            .transpose()?
+            .map(Arc::new);
+        if let Some(webhook) = &webhook {
+            writers.push(webhook.clone());
+        }
+        Ok(DecisionLog {
+            webhook,
+            ..DecisionLog::new(settings, instance, token_sources, writers)
+        })
+    }
+
+    /// Delivers the decisions still queued for the webhook. Decisions recorded afterwards are dropped.
+    pub async fn shutdown(&self) {
+        if let Some(webhook) = &self.webhook {
+            webhook.shutdown().await;
+        }
+    }
+
+    /// The claims of the validated internal token that are allowed into decision records.
+    pub fn claims_of(&self, claims: &BoxerClaims) -> Map<String, Value> {
+        let Ok(Value::Object(mut all_claims)) = serde_json::to_value(claims) else {
+            return Map::new();
+        };
+        self.claims
+            .iter()
+            .filter_map(|claim| all_claims.remove_entry(claim))
+            .collect()
+    }
+
+    /// The validated internal token, if decisions are recorded for replay.
+    pub fn token_of(&self, claims: &BoxerClaims) -> Option<Value> {
+        self.replayable.then(|| serde_json::to_value(claims).ok()).flatten()
+    }
+
+    /// Decisions are only recorded when at least one writer is configured.
+    pub fn is_enabled(&self) -> bool {
+        self.writer.is_some()
+    }
+
+    /// Writes the decision to every writer. Writing is best-effort and never changes the decision.
+    pub fn record(
+        &self,
+        request: ReviewedRequest,
+        result: &Result<()>,
+        shadow: Option<ShadowDecision>,
+        latency: Duration,
+    ) {
+        let Some(writer) = &self.writer else {
+            return;
+        };
+        let record = DecisionRecord {
+            timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+            instance: self.instance.clone(),
+            principal: request.principal,
+            claims: request.claims,
+            token: request.token,
+            schema: request.schema,
+            url: request.url,
+            request: request.request,
+            action: request.action,
+            resource: request.resource,
+            decision: Decision::of(result),
+            determining_policies: request.determining_policies,
+            enforcement: request.enforcement,
+            fallback: request.fallback,
+            reason: result.as_ref().err().map(|e| e.to_string()),
+            shadow,
+            latency_ms: latency.as_secs_f64() * 1000.0,
+        };
+        writer.write(record.into());
+    }
+}
@@ -0,0 +1,73 @@
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use percent_encoding::percent_decode_str;
+use serde_json::Value;
+use std::sync::Arc;
+
+pub const REDACTED: &str = "[REDACTED]";
+
+/// Masks sensitive query parameters in decision records before they reach the wrapped writer.
+pub struct RedactingWriter {
+    query_parameters: Vec<String>,
+    inner: Arc<dyn AuditWriter>,
+}
+
+fn is_redacted(name: &str, names: &[String]) -> bool {
+    let name = percent_decode_str(name).decode_utf8_lossy();
+    names.iter().any(|redacted| redacted.eq_ignore_ascii_case(&name))
+}
+
+/// Replaces the values of the given query parameters in every URL found in the text.
+/// Parameter names are matched case-insensitively after percent-decoding.
+pub fn redact_query_parameters(text: &str, names: &[String]) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(position) = rest.find(['?', '&']) {
+        let (head, tail) = rest.split_at(position + 1);
+        result.push_str(head);
+        rest = tail;
+        let name_length = rest.find(['=', '&', '#', ' ', '"', '\'']).unwrap_or(rest.len());
+        let (name, value) = rest.split_at(name_length);
+        if let Some(value) = value.strip_prefix('=')
+            && is_redacted(name, names)
+        {
+            let value_length = value.find(['&', '#', ' ', '"', '\'']).unwrap_or(value.len());
+            result.push_str(name);
+            result.push('=');
+            result.push_str(REDACTED);
+            rest = &value[value_length..];
+        }
+    }
+    result.push_str(rest);
+    result
+}
+
+fn redact_field(field: Option<&mut Value>, names: &[String]) {
+    if let Some(Value::String(text)) = field {
+        *text = redact_query_parameters(text, names);
+    }
+}
+
+impl RedactingWriter {
+    pub fn new(query_parameters: Vec<String>, inner: Arc<dyn AuditWriter>) -> Self {
+        RedactingWriter {
+            query_parameters,
+            inner,
+        }
+    }
+}
+
+impl AuditWriter for RedactingWriter {
+    fn write(&self, mut event: AuditEvent) {
+        if !self.query_parameters.is_empty() {
+            let record = event.details_mut();
+            redact_field(record.get_mut("url"), &self.query_parameters);
+            redact_field(record.get_mut("reason"), &self.query_parameters);
+            redact_field(
+                record.get_mut("shadow").and_then(|shadow| shadow.get_mut("reason")),
+                &self.query_parameters,
+            );
+        }
+        self.inner.write(event);
+    }
+}
@@ -0,0 +1,67 @@
+use crate::services::json_lines::{LineSink, append_to};
+use anyhow::Result;
+use async_trait::async_trait;
+use std::path::{Path, PathBuf};
+use tokio::fs::File;
+use tokio::io::AsyncWriteExt;
+
+/// A JSON-lines file that is rotated once it grows past a size limit.
+/// Rotated files are named `<path>.1` (newest) to `<path>.<max_files>` (oldest).
+pub struct RotatingFile {
+    path: PathBuf,
+    max_size_bytes: u64,
+    max_files: usize,
+    file: File,
+    size: u64,
+}
+
+fn rotated_path(path: &Path, index: usize) -> PathBuf {
+    let mut rotated = path.as_os_str().to_owned();
+    rotated.push(format!(".{}", index));
+    PathBuf::from(rotated)
+}
+
+impl RotatingFile {
+    pub async fn open(path: impl AsRef<Path>, max_size_bytes: u64, max_files: usize) -> Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let file = append_to(&path).await?;
+        let size = file.metadata().await?.len();
+        Ok(RotatingFile {
+            path,
+            max_size_bytes,
+            max_files,
+            file,
+            size,
+        })
+    }
+
+    async fn rotate(&mut self) -> Result<()> {
+        self.file.flush().await?;
+        for index in (1..self.max_files).rev() {
+            let from = rotated_path(&self.path, index);
+            if tokio::fs::try_exists(&from).await? {
+                tokio::fs::rename(&from, rotated_path(&self.path, index + 1)).await?;
+            }
+        }
+        if self.max_files > 0 {
+            tokio::fs::rename(&self.path, rotated_path(&self.path, 1)).await?;
+        } else {
+            tokio::fs::remove_file(&self.path).await?;
+        }
+        self.file = append_to(&self.path).await?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LineSink for RotatingFile {
+    async fn append(&mut self, line: &[u8]) -> Result<()> {
+        if self.size > 0 && self.size + line.len() as u64 > self.max_size_bytes {
+            self.rotate().await?;
+        }
+        self.file.append(line).await?;
+        self.size += line.len() as u64;
+        Ok(())
+    }
+}
@@ -0,0 +1,75 @@
+use crate::services::configuration::models::DecisionSamplingSettings;
+use crate::services::decision_log::Decision;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Decides which decisions are recorded: denies always are, allows are sampled per schema.
+pub struct DecisionSampler {
+    allow_percent: f64,
+    schema_allow_percent: HashMap<String, f64>,
+    state: RandomState,
+    counter: AtomicU64,
+}
+
+impl DecisionSampler {
+    pub fn new(settings: &DecisionSamplingSettings) -> Self {
+        DecisionSampler {
+            allow_percent: settings.allow_percent,
+            schema_allow_percent: settings.schema_allow_percent.clone(),
+            state: RandomState::new(),
+            counter: AtomicU64::new(0),
+        }
+    }
+
+    pub fn should_record(&self, schema: &str, allowed: bool) -> bool {
+        if !allowed {
+            return true;
+        }
+        let percent = self
+            .schema_allow_percent
+            .get(schema)
+            .copied()
+            .unwrap_or(self.allow_percent);
+        if percent >= 100.0 {
+            return true;
+        }
+        if percent <= 0.0 {
+            return false;
+        }
+        // Hashing a counter with randomly keyed SipHash gives uniformly distributed samples
+        let sample = self.state.hash_one(self.counter.fetch_add(1, Ordering::Relaxed));
+        (sample as f64 / u64::MAX as f64) * 100.0 < percent
+    }
+}
+
+/// Passes sampled decision records on to the wrapped writer.
+/// Decisions the shadow policies disagree with are never sampled out.
+pub struct SamplingWriter {
+    sampler: DecisionSampler,
+    inner: Arc<dyn AuditWriter>,
+}
+
+impl SamplingWriter {
+    pub fn new(settings: &DecisionSamplingSettings, inner: Arc<dyn AuditWriter>) -> Self {
+        SamplingWriter {
+            sampler: DecisionSampler::new(settings),
+            inner,
+        }
+    }
+}
+
+impl AuditWriter for SamplingWriter {
+    fn write(&self, event: AuditEvent) {
+        let record = event.details();
+        let diverged = record["shadow"]["diverged"].as_bool().unwrap_or(false);
+        let schema = record["schema"].as_str().unwrap_or_default();
+        let allowed = record["decision"].as_str() == Some(Decision::Allow.as_str());
+        if diverged || self.sampler.should_record(schema, allowed) {
+            self.inner.write(event);
+        }
+    }
+}
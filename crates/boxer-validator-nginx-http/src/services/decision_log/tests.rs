@@ -0,0 +1,395 @@
+use super::*;
+use crate::services::configuration::models::{
+    DecisionLogWebhookSettings, DecisionRedactionSettings, DecisionSamplingSettings, TokenSource,
+};
+use crate::services::decision_log::redaction::redact_query_parameters;
+use crate::services::decision_log::sampling::DecisionSampler;
+use crate::services::decision_log::webhook_sink::{WebhookAuditWriter, next_batch, retry_delay};
+use crate::services::json_lines::temp_path;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use test_case::test_case;
+use tokio::sync::mpsc;
+
+#[derive(Default)]
+struct CapturingWriter(Mutex<Vec<AuditEvent>>);
+
+impl AuditWriter for CapturingWriter {
+    fn write(&self, event: AuditEvent) {
+        self.0.lock().unwrap().push(event);
+    }
+}
+
+impl CapturingWriter {
+    fn records(&self) -> Vec<DecisionRecord> {
+        self.0
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|event| serde_json::from_value(event.details().clone()).unwrap())
+            .collect()
+    }
+}
+
+fn decision_log(settings: DecisionLogSettings, writer: Arc<CapturingWriter>) -> DecisionLog {
+    let token_sources = TokenSources::new(&[TokenSource::Authorization]).unwrap();
+    DecisionLog::new(&settings, "test".to_string(), &token_sources, vec![writer])
+}
+
+fn reviewed(schema: &str) -> ReviewedRequest {
+    ReviewedRequest {
+        principal: "PhotoApp::User::\"root\"".to_string(),
+        claims: Map::from_iter([
+            ("sub".to_string(), Value::from("root")),
+            ("email".to_string(), Value::from("root@example.com")),
+        ]),
+        token: None,
+        url: Some("https://www.example.com/photos/1?access_token=secret&size=large".to_string()),
+        schema: schema.to_string(),
+        request: RouteDescription {
+            hostname: Some("www.example.com".to_string()),
+            verb: Some("GET".to_string()),
+            path: "/photos/1".to_string(),
+        },
+        action: Some("PhotoApp::Action::\"viewPhoto\"".to_string()),
+        resource: Some("PhotoApp::Photo::\"1\"".to_string()),
+        determining_policies: vec!["photo-app-viewers".to_string()],
+        enforcement: EnforcementMode::Enforce,
+        fallback: None,
+    }
+}
+
+fn record(principal: &str) -> DecisionRecord {
+    DecisionRecord {
+        timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+        instance: "test".to_string(),
+        principal: principal.to_string(),
+        claims: Map::new(),
+        token: None,
+        schema: "photo-app".to_string(),
+        url: None,
+        request: RouteDescription::default(),
+        action: None,
+        resource: None,
+        decision: Decision::Allow,
+        determining_policies: Vec::new(),
+        enforcement: EnforcementMode::Enforce,
+        fallback: None,
+        reason: None,
+        shadow: None,
+        latency_ms: 1.0,
+    }
+}
+
+#[test]
+fn test_record_keeps_validation_result() {
+    let writer = Arc::new(CapturingWriter::default());
+    let log = decision_log(DecisionLogSettings::default(), writer.clone());
+
+    log.record(
+        reviewed("photo-app"),
+        &Err(anyhow::anyhow!("No policy permits the request")),
+        None,
+        Duration::from_millis(3),
+    );
+
+    let records = writer.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].decision, Decision::Deny);
+    assert_eq!(records[0].action.as_deref(), Some("PhotoApp::Action::\"viewPhoto\""));
+    assert_eq!(records[0].resource.as_deref(), Some("PhotoApp::Photo::\"1\""));
+    assert_eq!(records[0].determining_policies, vec!["photo-app-viewers"]);
+    assert_eq!(records[0].request.path, "/photos/1");
+    assert_eq!(records[0].reason.as_deref(), Some("No policy permits the request"));
+    assert_eq!(records[0].latency_ms, 3.0);
+}
+
+#[test]
+fn test_record_is_read_back_from_the_written_event() {
+    let line = serde_json::to_string(&AuditEvent::from(record("a"))).unwrap();
+
+    assert_eq!(DecisionRecord::from_json_line(&line).unwrap(), record("a"));
+}
+
+#[tokio::test]
+async fn test_rotating_file_rotates_and_keeps_max_files() {
+    let path = temp_path("boxer-decisions");
+    let line_length = serde_json::to_vec(&AuditEvent::from(record("a"))).unwrap().len() as u64 + 1;
+    // Every file holds two records
+    let writer = JsonLinesWriter::spawn(RotatingFile::open(&path, line_length * 2, 2).await.unwrap());
+
+    for principal in ["a", "b", "c", "d", "e", "f", "g"] {
+        writer.write(record(principal).into());
+    }
+    writer.flush().await;
+
+    let principals = |path: &PathBuf| -> Vec<String> {
+        std::fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(|line| DecisionRecord::from_json_line(line).unwrap().principal)
+            .collect()
+    };
+    let first = rotated(&path, 1);
+    let second = rotated(&path, 2);
+    assert_eq!(principals(&path), vec!["g"]);
+    assert_eq!(principals(&first), vec!["e", "f"]);
+    assert_eq!(principals(&second), vec!["c", "d"]);
+    assert!(!rotated(&path, 3).exists());
+
+    for file in [path, first, second] {
+        std::fs::remove_file(file).unwrap();
+    }
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    PathBuf::from(format!("{}.{}", path.display(), index))
+}
+
+#[test_case(0 => Duration::from_millis(100); "first retry")]
+#[test_case(1 => Duration::from_millis(200); "second retry")]
+#[test_case(3 => Duration::from_millis(800); "fourth retry")]
+fn test_retry_delay(attempt: u32) -> Duration {
+    retry_delay(Duration::from_millis(100), attempt)
+}
+
+#[tokio::test]
+async fn test_next_batch_is_limited_by_size() {
+    let (sender, mut receiver) = mpsc::channel(10);
+    for principal in ["a", "b", "c"] {
+        sender.send(record(principal).into()).await.unwrap();
+    }
+
+    let batch = next_batch(&mut receiver, 2, Duration::from_secs(60)).await;
+
+    assert_eq!(batch.len(), 2);
+}
+
+#[tokio::test]
+async fn test_next_batch_flushes_partial_batch() {
+    let (sender, mut receiver) = mpsc::channel(10);
+    sender.send(record("a").into()).await.unwrap();
+
+    let batch = next_batch(&mut receiver, 10, Duration::from_millis(10)).await;
+
+    assert_eq!(batch.len(), 1);
+    drop(sender);
+    assert!(
+        next_batch(&mut receiver, 10, Duration::from_millis(10))
+            .await
+            .is_empty()
+    );
+}
+
+#[actix_web::test]
+async fn test_webhook_shutdown_delivers_queued_records() {
+    let received = web::Data::new(AtomicUsize::new(0));
+    let server = HttpServer::new({
+        let received = received.clone();
+        move || {
+            App::new().app_data(received.clone()).route(
+                "/",
+                web::post().to(
+                    |received: web::Data<AtomicUsize>, batch: web::Json<Vec<Value>>| async move {
+                        received.fetch_add(batch.len(), Ordering::SeqCst);
+                        HttpResponse::Ok().finish()
+                    },
+                ),
+            )
+        }
+    })
+    .disable_signals()
+    .workers(1)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let address = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+    let writer = WebhookAuditWriter::start(&DecisionLogWebhookSettings {
+        url: format!("http://{}/", address),
+        batch_size: 10,
+        flush_interval: Duration::from_secs(60).into(),
+        max_retries: 0,
+        retry_backoff: Duration::from_millis(10).into(),
+        request_timeout: Duration::from_secs(10).into(),
+        connect_timeout: Duration::from_secs(5).into(),
+        shutdown_timeout: Duration::from_secs(10).into(),
+    })
+    .unwrap();
+    writer.write(record("a").into());
+    writer.write(record("b").into());
+
+    writer.shutdown().await;
+    writer.write(record("c").into());
+
+    assert_eq!(received.load(Ordering::SeqCst), 2);
+    handle.stop(true).await;
+}
+
+/// Starts a webhook that never responds, and returns its address.
+fn hanging_webhook() -> (std::net::SocketAddr, actix_web::dev::ServerHandle) {
+    let server = HttpServer::new(|| {
+        App::new().route(
+            "/",
+            web::post().to(|| async {
+                tokio::time::sleep(Duration::from_secs(3600)).await;
+                HttpResponse::Ok().finish()
+            }),
+        )
+    })
+    .disable_signals()
+    .workers(1)
+    .shutdown_timeout(0)
+    .bind(("127.0.0.1", 0))
+    .unwrap();
+    let address = server.addrs()[0];
+    let server = server.run();
+    let handle = server.handle();
+    actix_web::rt::spawn(server);
+    (address, handle)
+}
+
+#[test_case(Duration::from_millis(100), Duration::from_secs(3600); "request timeout")]
+#[test_case(Duration::from_secs(3600), Duration::from_millis(100); "shutdown timeout")]
+#[actix_web::test]
+async fn test_webhook_shutdown_does_not_wait_for_a_hanging_webhook(
+    request_timeout: Duration,
+    shutdown_timeout: Duration,
+) {
+    let (address, handle) = hanging_webhook();
+    let writer = WebhookAuditWriter::start(&DecisionLogWebhookSettings {
+        url: format!("http://{}/", address),
+        batch_size: 1,
+        flush_interval: Duration::from_millis(10).into(),
+        max_retries: 0,
+        retry_backoff: Duration::from_millis(10).into(),
+        request_timeout: request_timeout.into(),
+        connect_timeout: Duration::from_secs(5).into(),
+        shutdown_timeout: shutdown_timeout.into(),
+    })
+    .unwrap();
+    writer.write(record("a").into());
+
+    let started = std::time::Instant::now();
+    writer.shutdown().await;
+
+    assert!(started.elapsed() < Duration::from_secs(5));
+    handle.stop(false).await;
+}
+
+fn sampling(allow_percent: f64, schema_allow_percent: &[(&str, f64)]) -> DecisionLogSettings {
+    DecisionLogSettings {
+        sampling: DecisionSamplingSettings {
+            allow_percent,
+            schema_allow_percent: schema_allow_percent
+                .iter()
+                .map(|(schema, percent)| (schema.to_string(), *percent))
+                .collect(),
+        },
+        ..Default::default()
+    }
+}
+
+#[test_case(sampling(0.0, &[]), "photo-app", true => 0; "allows are not sampled")]
+#[test_case(sampling(0.0, &[]), "photo-app", false => 10; "denies are always recorded")]
+#[test_case(sampling(100.0, &[]), "photo-app", true => 10; "all allows are recorded")]
+#[test_case(sampling(100.0, &[("photo-app", 0.0)]), "photo-app", true => 0; "schema override")]
+#[test_case(sampling(100.0, &[("other", 0.0)]), "photo-app", true => 10; "override of another schema")]
+fn test_sampling(settings: DecisionLogSettings, schema: &str, allowed: bool) -> usize {
+    let writer = Arc::new(CapturingWriter::default());
+    let log = decision_log(settings, writer.clone());
+    for _ in 0..10 {
+        let result = if allowed {
+            Ok(())
+        } else {
+            Err(anyhow::anyhow!("denied"))
+        };
+        log.record(reviewed(schema), &result, None, Duration::ZERO);
+    }
+    writer.records().len()
+}
+
+#[test]
+fn test_diverging_shadow_decisions_are_not_sampled_out() {
+    let writer = Arc::new(CapturingWriter::default());
+    let log = decision_log(sampling(0.0, &[]), writer.clone());
+    let shadow = ShadowDecision::new(&Ok(()), &Err(anyhow::anyhow!("denied by shadow policy")));
+
+    log.record(reviewed("photo-app"), &Ok(()), None, Duration::ZERO);
+    log.record(reviewed("photo-app"), &Ok(()), Some(shadow.clone()), Duration::ZERO);
+
+    let records = writer.records();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].shadow, Some(shadow));
+}
+
+#[test]
+fn test_partial_sampling_rate() {
+    let sampler = DecisionSampler::new(&DecisionSamplingSettings {
+        allow_percent: 25.0,
+        ..Default::default()
+    });
+
+    let recorded = (0..10_000).filter(|_| sampler.should_record("photo-app", true)).count();
+
+    assert!((2_000..3_000).contains(&recorded), "recorded {} of 10000", recorded);
+}
+
+#[test_case("https://host/path?access_token=secret&size=large" => "https://host/path?access_token=[REDACTED]&size=large"; "first parameter")]
+#[test_case("https://host/path?size=large&access_token=secret#top" => "https://host/path?size=large&access_token=[REDACTED]#top"; "last parameter")]
+#[test_case("denied for https://host/?access_token=secret and https://other/?access_token=other" => "denied for https://host/?access_token=[REDACTED] and https://other/?access_token=[REDACTED]"; "several urls in text")]
+#[test_case("https://host/path?access_token_hint=value" => "https://host/path?access_token_hint=value"; "similar name")]
+#[test_case("https://host/path" => "https://host/path"; "no query")]
+#[test_case("https://host/path?Access_Token=secret" => "https://host/path?Access_Token=[REDACTED]"; "different case")]
+#[test_case("https://host/path?access%5Ftoken=secret&size=large" => "https://host/path?access%5Ftoken=[REDACTED]&size=large"; "percent-encoded name")]
+fn test_redact_query_parameters(text: &str) -> String {
+    redact_query_parameters(text, &["access_token".to_string()])
+}
+
+#[test]
+fn test_token_query_parameter_is_redacted_without_settings() {
+    let writer = Arc::new(CapturingWriter::default());
+    let token_sources = TokenSources::new(&[
+        TokenSource::Authorization,
+        TokenSource::QueryParameter {
+            name: "access_token".to_string(),
+        },
+    ])
+    .unwrap();
+    let log = DecisionLog::new(
+        &DecisionLogSettings::default(),
+        "test".to_string(),
+        &token_sources,
+        vec![writer.clone()],
+    );
+
+    log.record(reviewed("photo-app"), &Ok(()), None, Duration::ZERO);
+
+    assert_eq!(
+        writer.records()[0].url.as_deref(),
+        Some("https://www.example.com/photos/1?access_token=[REDACTED]&size=large")
+    );
+}
+
+#[test]
+fn test_record_is_redacted() {
+    let writer = Arc::new(CapturingWriter::default());
+    let settings = DecisionLogSettings {
+        redaction: DecisionRedactionSettings {
+            query_parameters: vec!["access_token".to_string()],
+        },
+        ..Default::default()
+    };
+    let log = decision_log(settings, writer.clone());
+
+    log.record(reviewed("photo-app"), &Ok(()), None, Duration::ZERO);
+
+    let records = writer.records();
+    assert_eq!(
+        records[0].url.as_deref(),
+        Some("https://www.example.com/photos/1?access_token=[REDACTED]&size=large")
+    );
+}
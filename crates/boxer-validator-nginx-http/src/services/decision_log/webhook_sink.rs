@@ -0,0 +1,169 @@
+use crate::services::configuration::models::DecisionLogWebhookSettings;
+use anyhow::Result;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use log::{error, warn};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::task::JoinHandle;
+use tokio::time::{Instant, sleep_until};
+
+/// Number of batches that may wait for delivery before new records are dropped.
+const QUEUED_BATCHES: usize = 10;
+
+/// Posts decision records as JSON arrays to a webhook.
+/// Records are queued and sent in batches by a background task, so a slow endpoint never delays token reviews.
+pub struct WebhookAuditWriter {
+    /// `None` once the writer is shut down.
+    sender: RwLock<Option<mpsc::Sender<AuditEvent>>>,
+    delivery: Mutex<Option<JoinHandle<()>>>,
+    shutdown_timeout: Duration,
+}
+
+struct WebhookDelivery {
+    client: reqwest::Client,
+    url: String,
+    batch_size: usize,
+    flush_interval: Duration,
+    max_retries: u32,
+    retry_backoff: Duration,
+}
+
+/// Delay before the given retry attempt (starting at zero), doubling every time.
+pub fn retry_delay(backoff: Duration, attempt: u32) -> Duration {
+    backoff.saturating_mul(2u32.saturating_pow(attempt))
+}
+
+/// Receives the next batch: waits for the first record, then collects more until the batch is full
+/// or the flush interval has passed. Returns an empty batch once the channel is closed.
+pub async fn next_batch(
+    receiver: &mut mpsc::Receiver<AuditEvent>,
+    batch_size: usize,
+    flush_interval: Duration,
+) -> Vec<AuditEvent> {
+    let mut batch = Vec::with_capacity(batch_size);
+    let Some(first) = receiver.recv().await else {
+        return batch;
+    };
+    batch.push(first);
+    let deadline = Instant::now() + flush_interval;
+    while batch.len() < batch_size {
+        tokio::select! {
+            record = receiver.recv() => match record {
+                Some(record) => batch.push(record),
+                None => break,
+            },
+            _ = sleep_until(deadline) => break,
+        }
+    }
+    batch
+}
+
+impl WebhookDelivery {
+    async fn send(&self, batch: &[AuditEvent]) -> Result<()> {
+        self.client
+            .post(&self.url)
+            .json(batch)
+            .send()
+            .await?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    async fn deliver(&self, batch: Vec<AuditEvent>) {
+        for attempt in 0..=self.max_retries {
+            match self.send(&batch).await {
+                Ok(()) => return,
+                Err(e) if attempt < self.max_retries => {
+                    let delay = retry_delay(self.retry_backoff, attempt);
+                    warn!(
+                        "Failed to send decision records to {}, retrying in {:?}: {}",
+                        self.url, delay, e
+                    );
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => error!(
+                    "Dropping {} decision records after {} attempts to send them to {}: {}",
+                    batch.len(),
+                    attempt + 1,
+                    self.url,
+                    e
+                ),
+            }
+        }
+    }
+
+    async fn run(self, mut receiver: mpsc::Receiver<AuditEvent>) {
+        loop {
+            let batch = next_batch(&mut receiver, self.batch_size, self.flush_interval).await;
+            if batch.is_empty() {
+                return;
+            }
+            self.deliver(batch).await;
+        }
+    }
+}
+
+impl WebhookAuditWriter {
+    /// Spawns the delivery task. Must be called from within a Tokio runtime.
+    pub fn start(settings: &DecisionLogWebhookSettings) -> Result<Self> {
+        let batch_size = settings.batch_size.max(1);
+        let (sender, receiver) = mpsc::channel(batch_size * QUEUED_BATCHES);
+        // reqwest is built without a crypto provider, it uses the process default
+        let _ = rustls::crypto::ring::default_provider().install_default();
+        let delivery = WebhookDelivery {
+            client: reqwest::Client::builder()
+                .timeout(settings.request_timeout.into())
+                .connect_timeout(settings.connect_timeout.into())
+                .build()?,
+            url: settings.url.clone(),
+            batch_size,
+            flush_interval: settings.flush_interval.into(),
+            max_retries: settings.max_retries,
+            retry_backoff: settings.retry_backoff.into(),
+        };
+        let delivery = tokio::spawn(delivery.run(receiver));
+        Ok(WebhookAuditWriter {
+            sender: RwLock::new(Some(sender)),
+            delivery: Mutex::new(Some(delivery)),
+            shutdown_timeout: settings.shutdown_timeout.into(),
+        })
+    }
+
+    /// Stops accepting records and waits until the queued ones have been delivered or dropped after their retries.
+    /// Delivery is aborted if it takes longer than the shutdown timeout.
+    pub async fn shutdown(&self) {
+        self.sender.write().unwrap().take();
+        let Some(mut delivery) = self.delivery.lock().unwrap().take() else {
+            return;
+        };
+        match tokio::time::timeout(self.shutdown_timeout, &mut delivery).await {
+            Ok(Ok(())) => {}
+            Ok(Err(e)) => error!("Webhook delivery task failed: {}", e),
+            Err(_) => {
+                error!(
+                    "Webhook delivery did not finish within {:?}, dropping the queued decision records",
+                    self.shutdown_timeout
+                );
+                delivery.abort();
+            }
+        }
+    }
+}
+
+impl AuditWriter for WebhookAuditWriter {
+    fn write(&self, event: AuditEvent) {
+        let sender = self.sender.read().unwrap();
+        let Some(sender) = sender.as_ref() else {
+            error!("Webhook delivery has stopped, dropping decision record");
+            return;
+        };
+        match sender.try_send(event) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => error!("Webhook queue is full, dropping decision record"),
+            Err(TrySendError::Closed(_)) => error!("Webhook delivery has stopped, dropping decision record"),
+        }
+    }
+}
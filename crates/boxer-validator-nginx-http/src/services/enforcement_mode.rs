@@ -0,0 +1,187 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::readiness::WatcherStatus;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::object_owner_mark::ObjectOwnerMark;
+use futures::StreamExt;
+use kube::api::{ApiResource, DynamicObject, GroupVersionKind};
+use kube::runtime::watcher::Event;
+use kube::runtime::{WatchStreamExt, watcher};
+use kube::{Api, Client, ResourceExt};
+use log::{info, warn};
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Meter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use utoipa::ToSchema;
+
+/// SchemaDocument annotation that selects the enforcement mode of the schema, e.g. `permissive`.
+pub const ENFORCEMENT_MODE_ANNOTATION: &str = "auth.sneaksanddata.com/enforcement-mode";
+
+/// SchemaDocuments are managed by boxer_core, so their annotations are watched through dynamic objects.
+fn schema_document_api(client: Client, namespace: &str) -> Api<DynamicObject> {
+    let gvk = GroupVersionKind::gvk("auth.sneaksanddata.com", "v1beta1", "SchemaDocument");
+    let resource = ApiResource::from_gvk_with_plural(&gvk, "schema-documents");
+    Api::namespaced_with(client, namespace, &resource)
+}
+
+/// How token review decisions of a schema are applied.
+#[derive(ToSchema, Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum EnforcementMode {
+    /// Denied requests are rejected.
+    #[default]
+    Enforce,
+    /// Denied requests are logged and counted, but let through.
+    Permissive,
+}
+
+impl EnforcementMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EnforcementMode::Enforce => "enforce",
+            EnforcementMode::Permissive => "permissive",
+        }
+    }
+}
+
+/// Reads the enforcement mode annotation; documents without a valid annotation are enforced.
+pub fn annotated_mode(object: &DynamicObject) -> EnforcementMode {
+    match object.annotations().get(ENFORCEMENT_MODE_ANNOTATION) {
+        None => EnforcementMode::Enforce,
+        Some(value) => serde_json::from_value(value.as_str().into()).unwrap_or_else(|_| {
+            warn!(
+                "Schema {} has an unknown enforcement mode {}, enforcing",
+                object.name_any(),
+                value
+            );
+            EnforcementMode::Enforce
+        }),
+    }
+}
+
+/// Enforcement modes annotated on SchemaDocuments, kept up to date by a watcher.
+#[derive(Default)]
+pub struct SchemaEnforcementModes {
+    permissive: RwLock<HashSet<String>>,
+    /// Schemas collected while the watcher relists, swapped in once the relist is done.
+    relisted: Mutex<Option<HashSet<String>>>,
+}
+
+impl SchemaEnforcementModes {
+    pub async fn get(&self, schema: &str) -> EnforcementMode {
+        if self.permissive.read().await.contains(schema) {
+            EnforcementMode::Permissive
+        } else {
+            EnforcementMode::Enforce
+        }
+    }
+
+    async fn set(&self, schema: String, mode: EnforcementMode) {
+        let mut permissive = self.permissive.write().await;
+        let changed = match mode {
+            EnforcementMode::Permissive => permissive.insert(schema.clone()),
+            EnforcementMode::Enforce => permissive.remove(&schema),
+        };
+        if changed {
+            info!("Schema {} is now in {} mode", schema, mode.as_str());
+        }
+    }
+
+    pub async fn handle_event(&self, event: Event<DynamicObject>) {
+        match event {
+            Event::Apply(object) => self.set(object.name_any(), annotated_mode(&object)).await,
+            Event::Delete(object) => self.set(object.name_any(), EnforcementMode::Enforce).await,
+            Event::Init => *self.relisted.lock().await = Some(HashSet::new()),
+            Event::InitApply(object) => {
+                if annotated_mode(&object) == EnforcementMode::Permissive
+                    && let Some(relisted) = self.relisted.lock().await.as_mut()
+                {
+                    relisted.insert(object.name_any());
+                }
+            }
+            Event::InitDone => {
+                if let Some(relisted) = self.relisted.lock().await.take() {
+                    *self.permissive.write().await = relisted;
+                }
+            }
+        }
+    }
+
+    /// Watches the SchemaDocuments owned by this instance in the background.
+    pub fn watch(
+        self: &Arc<Self>,
+        client: Client,
+        namespace: &str,
+        owner_mark: &ObjectOwnerMark,
+        status: Arc<WatcherStatus>,
+    ) -> JoinHandle<()> {
+        let watcher_config: watcher::Config = owner_mark.into();
+        let stream = watcher(schema_document_api(client, namespace), watcher_config).default_backoff();
+        let modes = self.clone();
+        tokio::spawn(async move {
+            let _running = status.running();
+            let mut stream = std::pin::pin!(stream);
+            while let Some(event) = stream.next().await {
+                match event {
+                    Ok(event) => {
+                        let init_done = matches!(event, Event::InitDone);
+                        modes.handle_event(event).await;
+                        status.applied(init_done);
+                    }
+                    Err(err) => {
+                        status.failed(&err);
+                        warn!("Error while watching schema enforcement modes: {:?}", err)
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// Decides whether a token review decision is enforced.
+pub struct Enforcement {
+    schemas: Arc<SchemaEnforcementModes>,
+    override_mode: Option<EnforcementMode>,
+    permissive_denials: Counter<u64>,
+}
+
+impl Enforcement {
+    /// A mode set in the application settings overrides the mode of every schema.
+    pub fn new(schemas: Arc<SchemaEnforcementModes>, override_mode: Option<EnforcementMode>, meter: &Meter) -> Self {
+        Enforcement {
+            schemas,
+            override_mode,
+            permissive_denials: meter
+                .u64_counter("permissive_denials")
+                .with_description("Denied token reviews let through by permissive mode, by schema")
+                .build(),
+        }
+    }
+
+    pub async fn mode(&self, schema: &str) -> EnforcementMode {
+        match self.override_mode {
+            Some(mode) => mode,
+            None => self.schemas.get(schema).await,
+        }
+    }
+
+    /// Returns true if a denied request must be rejected. In permissive mode the denial is logged and counted instead.
+    pub fn rejects(&self, schema: &str, mode: EnforcementMode, denial: &anyhow::Error) -> bool {
+        match mode {
+            EnforcementMode::Enforce => true,
+            EnforcementMode::Permissive => {
+                warn!(
+                    "Permissive mode let a denied request through for schema {}: {}",
+                    schema, denial
+                );
+                self.permissive_denials
+                    .add(1, &[KeyValue::new("schema", schema.to_string())]);
+                false
+            }
+        }
+    }
+}
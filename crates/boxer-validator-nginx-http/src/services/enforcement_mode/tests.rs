@@ -0,0 +1,85 @@
+use super::*;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use std::collections::BTreeMap;
+use test_case::test_case;
+
+fn schema_document(name: &str, mode: Option<&str>) -> DynamicObject {
+    DynamicObject {
+        types: None,
+        metadata: ObjectMeta {
+            name: Some(name.to_string()),
+            annotations: mode.map(|mode| BTreeMap::from([(ENFORCEMENT_MODE_ANNOTATION.to_string(), mode.to_string())])),
+            ..Default::default()
+        },
+        data: serde_json::Value::Null,
+    }
+}
+
+#[test_case(None => EnforcementMode::Enforce; "no annotation")]
+#[test_case(Some("permissive") => EnforcementMode::Permissive; "permissive")]
+#[test_case(Some("enforce") => EnforcementMode::Enforce; "enforce")]
+#[test_case(Some("log-only") => EnforcementMode::Enforce; "unknown mode")]
+fn test_annotated_mode(mode: Option<&str>) -> EnforcementMode {
+    annotated_mode(&schema_document("photo-app", mode))
+}
+
+#[tokio::test]
+async fn test_apply_and_delete_events() {
+    let modes = SchemaEnforcementModes::default();
+
+    modes
+        .handle_event(Event::Apply(schema_document("photo-app", Some("permissive"))))
+        .await;
+    assert_eq!(modes.get("photo-app").await, EnforcementMode::Permissive);
+
+    modes
+        .handle_event(Event::Delete(schema_document("photo-app", Some("permissive"))))
+        .await;
+    assert_eq!(modes.get("photo-app").await, EnforcementMode::Enforce);
+}
+
+#[tokio::test]
+async fn test_relist_replaces_permissive_schemas() {
+    let modes = SchemaEnforcementModes::default();
+    modes
+        .handle_event(Event::Apply(schema_document(
+            "removed-while-offline",
+            Some("permissive"),
+        )))
+        .await;
+
+    modes.handle_event(Event::Init).await;
+    modes
+        .handle_event(Event::InitApply(schema_document("photo-app", Some("permissive"))))
+        .await;
+    // The previous state is kept until the relist is complete
+    assert_eq!(modes.get("removed-while-offline").await, EnforcementMode::Permissive);
+    modes.handle_event(Event::InitDone).await;
+
+    assert_eq!(modes.get("photo-app").await, EnforcementMode::Permissive);
+    assert_eq!(modes.get("removed-while-offline").await, EnforcementMode::Enforce);
+}
+
+#[test_case(None => EnforcementMode::Permissive; "schema mode")]
+#[test_case(Some(EnforcementMode::Enforce) => EnforcementMode::Enforce; "global override")]
+#[tokio::test]
+async fn test_override_mode(override_mode: Option<EnforcementMode>) -> EnforcementMode {
+    let modes = Arc::new(SchemaEnforcementModes::default());
+    modes
+        .handle_event(Event::Apply(schema_document("photo-app", Some("permissive"))))
+        .await;
+    let enforcement = Enforcement::new(modes, override_mode, &opentelemetry::global::meter("test"));
+
+    enforcement.mode("photo-app").await
+}
+
+#[test_case(EnforcementMode::Enforce => true; "enforce")]
+#[test_case(EnforcementMode::Permissive => false; "permissive")]
+fn test_rejects(mode: EnforcementMode) -> bool {
+    let enforcement = Enforcement::new(
+        Arc::new(SchemaEnforcementModes::default()),
+        None,
+        &opentelemetry::global::meter("test"),
+    );
+    enforcement.rejects("photo-app", mode, &anyhow::anyhow!("denied"))
+}
@@ -0,0 +1,103 @@
+use anyhow::Result;
+use async_trait::async_trait;
+use boxer_core::http::middleware::audit::audit_recorder::audit_writer::AuditWriter;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use log::error;
+use std::path::Path;
+use tokio::fs::{File, OpenOptions};
+use tokio::io::{AsyncWriteExt, Stdout};
+use tokio::sync::{mpsc, oneshot};
+
+/// Destination of a [`JsonLinesWriter`]. Every line is flushed once it is appended.
+#[async_trait]
+pub trait LineSink: Send + 'static {
+    async fn append(&mut self, line: &[u8]) -> Result<()>;
+}
+
+#[async_trait]
+impl LineSink for File {
+    async fn append(&mut self, line: &[u8]) -> Result<()> {
+        self.write_all(line).await?;
+        self.flush().await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl LineSink for Stdout {
+    async fn append(&mut self, line: &[u8]) -> Result<()> {
+        self.write_all(line).await?;
+        self.flush().await?;
+        Ok(())
+    }
+}
+
+/// Opens a file for appending, creating it if it does not exist.
+pub async fn append_to(path: impl AsRef<Path>) -> Result<File> {
+    Ok(OpenOptions::new().create(true).append(true).open(path).await?)
+}
+
+enum Message {
+    Event(AuditEvent),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Writes audit events to a [`LineSink`], one JSON document per line.
+/// Lines are appended by a background task, so `write` never blocks the calling request.
+pub struct JsonLinesWriter {
+    sender: mpsc::UnboundedSender<Message>,
+}
+
+impl JsonLinesWriter {
+    /// Spawns the task appending to `sink`. Must be called from within a Tokio runtime.
+    pub fn spawn(sink: impl LineSink) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(run(sink, receiver));
+        JsonLinesWriter { sender }
+    }
+
+    /// Waits until every event written so far has been appended.
+    pub async fn flush(&self) {
+        let (done, flushed) = oneshot::channel();
+        if self.sender.send(Message::Flush(done)).is_ok() {
+            let _ = flushed.await;
+        }
+    }
+}
+
+async fn run(mut sink: impl LineSink, mut receiver: mpsc::UnboundedReceiver<Message>) {
+    while let Some(message) = receiver.recv().await {
+        match message {
+            Message::Event(event) => {
+                if let Err(e) = append(&mut sink, &event).await {
+                    error!("Failed to write audit event {:?}: {}", event, e);
+                }
+            }
+            Message::Flush(done) => {
+                let _ = done.send(());
+            }
+        }
+    }
+}
+
+async fn append(sink: &mut impl LineSink, event: &AuditEvent) -> Result<()> {
+    let mut line = serde_json::to_vec(event)?;
+    line.push(b'\n');
+    sink.append(&line).await
+}
+
+impl AuditWriter for JsonLinesWriter {
+    fn write(&self, event: AuditEvent) {
+        if self.sender.send(Message::Event(event)).is_err() {
+            error!("Audit event was dropped, the JSON-lines writer has stopped");
+        }
+    }
+}
+
+/// A unique path in the temporary directory for files written by tests.
+#[cfg(test)]
+pub(crate) fn temp_path(prefix: &str) -> std::path::PathBuf {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    let nanos = SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_nanos();
+    std::env::temp_dir().join(format!("{}-{}-{}.jsonl", prefix, std::process::id(), nanos))
+}
@@ -0,0 +1,130 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::decision_log::Decision;
+use crate::services::readiness::Readiness;
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use crate::services::repositories::lookup_trie::route_table::RouteTables;
+use crate::services::review_stages::ReviewStage;
+use async_trait::async_trait;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Histogram, Meter};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Instruments recorded by the token review endpoint.
+pub struct TokenReviewMetrics {
+    decisions: Counter<u64>,
+    stage_duration: Histogram<f64>,
+}
+
+impl TokenReviewMetrics {
+    pub fn new(meter: &Meter) -> Self {
+        TokenReviewMetrics {
+            decisions: meter
+                .u64_counter("token_review_decisions")
+                .with_description("Token review decisions, by schema and outcome")
+                .build(),
+            stage_duration: meter
+                .f64_histogram("token_review_stage_duration")
+                .with_unit("s")
+                .with_description("Latency of the stages of a token review, by stage, schema and outcome")
+                .build(),
+        }
+    }
+
+    pub fn record_review(&self, schema: &str, decision: Decision, stages: &[(ReviewStage, Duration)]) {
+        let attributes = [
+            KeyValue::new("schema", schema.to_string()),
+            KeyValue::new("outcome", decision.as_str()),
+        ];
+        self.decisions.add(1, &attributes);
+        for (stage, duration) in stages {
+            let mut stage_attributes = attributes.to_vec();
+            stage_attributes.push(KeyValue::new("stage", stage.as_str()));
+            self.stage_duration.record(duration.as_secs_f64(), &stage_attributes);
+        }
+    }
+}
+
+/// Records the latency of every lookup against the wrapped trie.
+pub struct TimedLookup<Key, Value> {
+    inner: Arc<AssociatedRepository<Key, Value>>,
+    lookup: &'static str,
+    duration: Histogram<f64>,
+}
+
+impl<Key, Value> TimedLookup<Key, Value> {
+    /// `lookup` names the trie in the recorded attributes, e.g. `action`.
+    pub fn new(inner: Arc<AssociatedRepository<Key, Value>>, lookup: &'static str, meter: &Meter) -> Self {
+        TimedLookup {
+            inner,
+            lookup,
+            duration: meter
+                .f64_histogram("lookup_trie_duration")
+                .with_unit("s")
+                .with_description("Latency of lookup trie reads, by trie and whether an entry was found")
+                .build(),
+        }
+    }
+}
+
+#[async_trait]
+impl<Key, Value> ReadOnlyRepository<Key, Value> for TimedLookup<Key, Value>
+where
+    Key: Send + Sync + 'static,
+    Value: Send + Sync + 'static,
+{
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: Key) -> Result<Value, Self::ReadError> {
+        let started = Instant::now();
+        let result = self.inner.get(key).await;
+        self.duration.record(
+            started.elapsed().as_secs_f64(),
+            &[
+                KeyValue::new("lookup", self.lookup),
+                KeyValue::new("found", result.is_ok()),
+            ],
+        );
+        result
+    }
+}
+
+/// Reports watcher event and error counts and the trie sizes on every collection.
+pub fn observe_backend(meter: &Meter, readiness: Arc<Readiness>, route_tables: Arc<RouteTables>) {
+    let events = readiness.clone();
+    meter
+        .u64_observable_counter("watcher_events")
+        .with_description("Watch events applied to the in-memory state, by watcher")
+        .with_callback(move |observer| {
+            for component in events.report().components {
+                observer.observe(component.events, &[KeyValue::new("watcher", component.name)]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_counter("watcher_errors")
+        .with_description("Watch stream errors, by watcher")
+        .with_callback(move |observer| {
+            for component in readiness.report().components {
+                observer.observe(component.errors, &[KeyValue::new("watcher", component.name)]);
+            }
+        })
+        .build();
+    meter
+        .u64_observable_gauge("lookup_trie_routes")
+        .with_description("Routes registered in the lookup tries, by trie and schema")
+        .with_callback(move |observer| {
+            for (lookup, table) in [("action", &route_tables.actions), ("resource", &route_tables.resources)] {
+                for (schema, count) in table.route_counts() {
+                    observer.observe(
+                        count as u64,
+                        &[KeyValue::new("lookup", lookup), KeyValue::new("schema", schema)],
+                    );
+                }
+            }
+        })
+        .build();
+}
@@ -0,0 +1,29 @@
+use super::*;
+use anyhow::anyhow;
+use std::collections::HashMap;
+
+struct StaticLookup(HashMap<String, String>);
+
+#[async_trait]
+impl ReadOnlyRepository<String, String> for StaticLookup {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: String) -> Result<String, Self::ReadError> {
+        self.0.get(&key).cloned().ok_or_else(|| anyhow!("not found: {}", key))
+    }
+}
+
+#[tokio::test]
+async fn test_timed_lookup_returns_inner_result() {
+    let inner = Arc::new(StaticLookup(HashMap::from([(
+        "photos".to_string(),
+        "PhotoApp::Action::\"viewPhoto\"".to_string(),
+    )])));
+    let lookup = TimedLookup::new(inner, "action", &opentelemetry::global::meter("test"));
+
+    assert_eq!(
+        lookup.get("photos".to_string()).await.unwrap(),
+        "PhotoApp::Action::\"viewPhoto\""
+    );
+    assert!(lookup.get("videos".to_string()).await.is_err());
+}
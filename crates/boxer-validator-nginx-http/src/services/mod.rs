@@ -1,5 +1,24 @@
+pub mod admin_authorization;
+pub mod api_server;
+pub mod audit_trail;
 pub mod backends;
 pub mod configuration;
+pub mod decision_log;
+pub mod enforcement_mode;
+pub mod json_lines;
+pub mod metrics;
+pub mod policy_simulation;
 pub mod prefix_tree;
+pub mod prometheus;
+pub mod readiness;
 pub mod repositories;
+pub mod review_stages;
+pub mod route_template;
+pub mod schema_catalog;
 pub mod schema_provider;
+pub mod shadow_policies;
+pub mod shutdown;
+pub mod signing_keys;
+pub mod telemetry;
+pub mod tls;
+pub mod unmatched_routes;
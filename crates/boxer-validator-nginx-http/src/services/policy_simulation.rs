@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::decision_log::{Decision, DecisionRecord, determining_policies};
+use crate::services::enforcement_mode::EnforcementMode;
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use crate::services::review_stages::{ReviewStage, ReviewTrace};
+use crate::services::unmatched_routes::{RouteFallback, UnmatchedRoutes};
+use anyhow::Result;
+use async_trait::async_trait;
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use boxer_core::services::observability::open_telemetry::metrics::provider::MetricsProvider;
+use boxer_core::services::validation_service::ValidationService;
+use boxer_core::services::validation_service::cedar_validation_service::CedarValidationService;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+use boxer_core::services::validation_service::required_claims::RequiredClaims;
+use boxer_core::services::validation_service::schema_provider::SchemaProvider;
+use cedar_policy::{EntityUid, PolicySet, Schema, SchemaFragment};
+use serde::Serialize;
+use serde_json::Value;
+use std::str::FromStr;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+/// A policy set and schema that are not rolled out yet.
+pub struct CandidatePolicies {
+    schema_id: String,
+    schema: SchemaFragment,
+    policies: PolicySet,
+}
+
+impl CandidatePolicies {
+    pub fn new(schema_id: &str, schema: &Value, policies: &str) -> Result<Self> {
+        Ok(CandidatePolicies {
+            schema_id: schema_id.to_string(),
+            schema: SchemaFragment::from_json_value(schema.clone())?,
+            policies: PolicySet::from_str(policies)?,
+        })
+    }
+}
+
+/// Combines the candidate schema with the principal schema carried by the replayed token.
+#[async_trait]
+impl SchemaProvider<BoxerClaims> for CandidatePolicies {
+    async fn get_schema(&self, boxer_claims: &BoxerClaims) -> Result<Schema> {
+        let principal_schema = boxer_claims.get_schema().clone();
+        Schema::from_schema_fragments(vec![self.schema.clone(), principal_schema]).map_err(anyhow::Error::from)
+    }
+}
+
+/// Always reads the candidate policy set; records of other schemas are never replayed.
+#[async_trait]
+impl ReadOnlyRepository<String, PolicySet> for CandidatePolicies {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, _: String) -> Result<PolicySet, Self::ReadError> {
+        Ok(self.policies.clone())
+    }
+}
+
+/// A recorded request whose decision would change under the candidate policies.
+#[derive(ToSchema, Serialize, Debug, PartialEq)]
+pub struct DecisionChange {
+    /// Line of the decision log the request was read from, starting at 1.
+    pub line: usize,
+    pub timestamp: String,
+    pub principal: String,
+    pub url: Option<String>,
+    pub action: Option<String>,
+    pub resource: Option<String>,
+    pub recorded: Decision,
+    /// `permissive` if the recorded request was let through although it was denied.
+    pub enforcement: EnforcementMode,
+    /// Fallback applied by the validator if the request matched no registered action or resource.
+    pub recorded_fallback: Option<RouteFallback>,
+    pub simulated: Decision,
+    /// Fallback applied by the simulation if the request matches no registered action or resource.
+    pub simulated_fallback: Option<RouteFallback>,
+    /// Candidate policies that determined the simulated decision.
+    pub determining_policies: Vec<String>,
+    /// Explanation reported by the validator for a request denied by the simulation.
+    pub reason: Option<String>,
+}
+
+/// A decision log line that could not be read.
+#[derive(ToSchema, Serialize, Debug, PartialEq)]
+pub struct InvalidRecord {
+    pub line: usize,
+    pub error: String,
+}
+
+#[derive(ToSchema, Serialize, Debug, Default, PartialEq)]
+pub struct SimulationReport {
+    /// Number of requests evaluated against the candidate policies.
+    pub replayed: usize,
+    /// Number of requests bound to other schemas.
+    pub skipped: usize,
+    /// Number of requests recorded without the URL, method or token needed to replay them.
+    pub not_replayable: usize,
+    pub invalid: Vec<InvalidRecord>,
+    pub changes: Vec<DecisionChange>,
+}
+
+/// Replays recorded decisions the way the validator makes them: the token from the record is validated
+/// by a [`CedarValidationService`] that resolves actions and resources with the current lookup tries,
+/// and requests that match none of them get the configured unmatched route fallback.
+/// The tries must be read through a [`FallbackLookup`](crate::services::unmatched_routes::FallbackLookup),
+/// as they are for token reviews.
+pub struct PolicySimulator {
+    actions: Arc<AssociatedRepository<(String, Vec<RequestSegment>), EntityUid>>,
+    resources: Arc<AssociatedRepository<(String, Vec<PathSegment>), EntityUid>>,
+    unmatched_routes: Arc<UnmatchedRoutes>,
+    metrics_namespace: &'static str,
+    instance: String,
+}
+
+impl PolicySimulator {
+    pub fn new(
+        actions: Arc<AssociatedRepository<(String, Vec<RequestSegment>), EntityUid>>,
+        resources: Arc<AssociatedRepository<(String, Vec<PathSegment>), EntityUid>>,
+        unmatched_routes: Arc<UnmatchedRoutes>,
+        metrics_namespace: &'static str,
+        instance: String,
+    ) -> Self {
+        PolicySimulator {
+            actions,
+            resources,
+            unmatched_routes,
+            metrics_namespace,
+            instance,
+        }
+    }
+
+    fn validation_service(&self, candidate: Arc<CandidatePolicies>) -> CedarValidationService {
+        CedarValidationService::new(
+            candidate.clone(),
+            self.actions.clone(),
+            self.resources.clone(),
+            candidate,
+            MetricsProvider::new(self.metrics_namespace, self.instance.clone()),
+        )
+    }
+
+    /// Replays a decision log in the JSON-lines format against candidate policies and reports
+    /// every request whose decision would change. Only records written with the `replayable` setting
+    /// carry the token, and with it the principal entity, that is needed to replay them.
+    pub async fn simulate(&self, decision_log: &str, candidate: CandidatePolicies) -> SimulationReport {
+        let candidate = Arc::new(candidate);
+        let validation_service = self.validation_service(candidate.clone());
+        let mut report = SimulationReport::default();
+        for (index, line) in decision_log.lines().enumerate() {
+            let line_number = index + 1;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let record = match DecisionRecord::from_json_line(line) {
+                Ok(record) => record,
+                Err(e) => {
+                    report.invalid.push(InvalidRecord {
+                        line: line_number,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+            if record.schema != candidate.schema_id {
+                report.skipped += 1;
+                continue;
+            }
+            let (Some(url), Some(method), Some(token)) = (&record.url, &record.request.verb, &record.token) else {
+                report.not_replayable += 1;
+                continue;
+            };
+            let boxer_claims: BoxerClaims = match serde_json::from_value(token.clone()) {
+                Ok(boxer_claims) => boxer_claims,
+                Err(e) => {
+                    report.invalid.push(InvalidRecord {
+                        line: line_number,
+                        error: format!("Recorded token is invalid: {}", e),
+                    });
+                    continue;
+                }
+            };
+
+            let review = ReviewTrace::replay(&record.schema);
+            let unmatched = self
+                .unmatched_routes
+                .start(&record.schema, record.request.hostname.as_deref());
+            let mut event = AuditEvent::default();
+            let result = unmatched
+                .run(review.validate(validation_service.validate(
+                    boxer_claims,
+                    RequestContext::new(url.clone(), method.clone()),
+                    &mut event,
+                )))
+                .await;
+            // Replayed decisions are not reported in the unmatched route metrics
+            let result = unmatched.apply(result);
+            let simulated = Decision::of(&result);
+            review.finish(simulated);
+            report.replayed += 1;
+
+            if simulated != record.decision {
+                report.changes.push(DecisionChange {
+                    line: line_number,
+                    timestamp: record.timestamp,
+                    principal: record.principal,
+                    url: record.url,
+                    action: review.resolved(ReviewStage::ActionLookup),
+                    resource: review.resolved(ReviewStage::ResourceLookup),
+                    recorded: record.decision,
+                    enforcement: record.enforcement,
+                    recorded_fallback: record.fallback,
+                    simulated,
+                    simulated_fallback: unmatched.fallback(),
+                    determining_policies: determining_policies(&event),
+                    reason: result.err().map(|e| e.to_string()),
+                });
+            }
+        }
+        report
+    }
+}
@@ -0,0 +1,178 @@
+use super::*;
+use crate::services::configuration::models::{UnmatchedRouteBehavior, UnmatchedRouteSettings};
+use crate::services::enforcement_mode::EnforcementMode;
+use crate::services::repositories::lookup_trie::route_table::RouteDescription;
+use crate::services::unmatched_routes::FallbackLookup;
+use anyhow::anyhow;
+use serde_json::{Map, json};
+use std::sync::Arc;
+use test_case::test_case;
+
+struct NoRoutes;
+
+#[async_trait]
+impl<Key: Send + 'static> ReadOnlyRepository<Key, EntityUid> for NoRoutes {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, _: Key) -> Result<EntityUid, Self::ReadError> {
+        Err(anyhow!("No route is registered for the request"))
+    }
+}
+
+fn simulator() -> PolicySimulator {
+    simulator_with(UnmatchedRouteBehavior::default())
+}
+
+fn simulator_with(fallback: UnmatchedRouteBehavior) -> PolicySimulator {
+    let settings = UnmatchedRouteSettings {
+        default: fallback,
+        ..Default::default()
+    };
+    PolicySimulator::new(
+        Arc::new(FallbackLookup::actions(Arc::new(NoRoutes))),
+        Arc::new(FallbackLookup::resources(Arc::new(NoRoutes))),
+        Arc::new(UnmatchedRoutes::new(&settings, &opentelemetry::global::meter("test")).unwrap()),
+        "boxer-validator-simulation",
+        "test".to_string(),
+    )
+}
+
+fn candidate(policies: &str) -> CandidatePolicies {
+    let schema = json!({
+        "PhotoApp": {
+            "entityTypes": {
+                "Photo": {}
+            },
+            "actions": {
+                "viewPhoto": {}
+            }
+        }
+    });
+    CandidatePolicies::new("photo-app", &schema, policies).unwrap()
+}
+
+fn record(schema: &str, url: Option<&str>, token: Option<Value>) -> String {
+    serde_json::to_string(&AuditEvent::from(decision_record(schema, url, token))).unwrap()
+}
+
+fn decision_record(schema: &str, url: Option<&str>, token: Option<Value>) -> DecisionRecord {
+    DecisionRecord {
+        timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+        instance: "test".to_string(),
+        principal: "PhotoApp::User::\"root\"".to_string(),
+        claims: Map::new(),
+        token,
+        schema: schema.to_string(),
+        url: url.map(String::from),
+        request: RouteDescription {
+            hostname: Some("www.example.com".to_string()),
+            verb: Some("GET".to_string()),
+            path: "/photos/1".to_string(),
+        },
+        action: Some("PhotoApp::Action::\"viewPhoto\"".to_string()),
+        resource: Some("PhotoApp::Photo::\"1\"".to_string()),
+        decision: Decision::Deny,
+        determining_policies: Vec::new(),
+        enforcement: EnforcementMode::Enforce,
+        fallback: None,
+        reason: None,
+        shadow: None,
+        latency_ms: 1.0,
+    }
+}
+
+/// A replayable record of a request that matched no registered action or resource.
+fn fallback_record(decision: Decision, fallback: RouteFallback) -> String {
+    let token = serde_json::to_value(BoxerClaims::default()).unwrap();
+    let record = DecisionRecord {
+        decision,
+        fallback: Some(fallback),
+        ..decision_record("photo-app", Some("https://www.example.com/photos/1"), Some(token))
+    };
+    serde_json::to_string(&AuditEvent::from(record)).unwrap()
+}
+
+#[tokio::test]
+async fn test_simulate_reports_invalid_and_skipped_records() {
+    let log = [record("other-app", None, None), "not json".to_string(), String::new()].join("\n");
+
+    let report = simulator().simulate(&log, candidate("")).await;
+
+    assert_eq!(report.replayed, 0);
+    assert_eq!(report.skipped, 1);
+    assert_eq!(report.invalid.len(), 1);
+    assert_eq!(report.invalid[0].line, 2);
+    assert!(report.changes.is_empty());
+}
+
+#[tokio::test]
+async fn test_simulate_does_not_replay_records_without_token_or_url() {
+    let log = [
+        record("photo-app", Some("https://www.example.com/photos/1"), None),
+        record("photo-app", None, Some(json!({ "sub": "root" }))),
+    ]
+    .join("\n");
+
+    let report = simulator().simulate(&log, candidate("")).await;
+
+    assert_eq!(report.replayed, 0);
+    assert_eq!(report.not_replayable, 2);
+    assert!(report.invalid.is_empty());
+    assert!(report.changes.is_empty());
+}
+
+#[tokio::test]
+async fn test_simulate_reports_invalid_recorded_tokens() {
+    let log = record(
+        "photo-app",
+        Some("https://www.example.com/photos/1"),
+        Some(json!("not a token")),
+    );
+
+    let report = simulator().simulate(&log, candidate("")).await;
+
+    assert_eq!(report.replayed, 0);
+    assert_eq!(report.invalid.len(), 1);
+    assert!(report.invalid[0].error.starts_with("Recorded token is invalid"));
+}
+
+#[test_case(UnmatchedRouteBehavior::Allow, Decision::Allow, RouteFallback::Allow; "allow")]
+#[test_case(UnmatchedRouteBehavior::Deny, Decision::Deny, RouteFallback::Deny; "deny")]
+#[tokio::test]
+async fn test_simulate_replays_the_unmatched_route_fallback(
+    behavior: UnmatchedRouteBehavior,
+    decision: Decision,
+    fallback: RouteFallback,
+) {
+    let report = simulator_with(behavior)
+        .simulate(&fallback_record(decision, fallback), candidate(""))
+        .await;
+
+    assert_eq!(report.replayed, 1);
+    assert!(report.invalid.is_empty());
+    assert!(report.changes.is_empty());
+}
+
+#[tokio::test]
+async fn test_simulate_reports_a_changed_fallback() {
+    let report = simulator_with(UnmatchedRouteBehavior::Deny)
+        .simulate(&fallback_record(Decision::Allow, RouteFallback::Allow), candidate(""))
+        .await;
+
+    assert_eq!(report.changes.len(), 1);
+    assert_eq!(report.changes[0].recorded_fallback, Some(RouteFallback::Allow));
+    assert_eq!(report.changes[0].simulated, Decision::Deny);
+    assert_eq!(report.changes[0].simulated_fallback, Some(RouteFallback::Deny));
+}
+
+#[test]
+fn test_candidate_rejects_invalid_policies() {
+    let schema = json!({ "PhotoApp": {} });
+    assert!(CandidatePolicies::new("photo-app", &schema, "not a policy").is_err());
+}
+
+#[test]
+fn test_candidate_rejects_invalid_schema() {
+    let schema = json!({ "PhotoApp": { "entityTypes": "not an object" } });
+    assert!(CandidatePolicies::new("photo-app", &schema, "").is_err());
+}
@@ -7,6 +7,14 @@ pub mod parametrized_matcher;
 mod tests;
 pub mod trie_bucket;
 
+/// An edge of the trie: either an exact key or the parameter slot that matches any key,
+/// along with the parameter key it was registered under.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrieEdge<K> {
+    Exact(K),
+    Parameter(K),
+}
+
 #[async_trait]
 /// A prefix tree (trie) structure for storing and retrieving values based on keys.
 pub trait PrefixTree<K, V> {
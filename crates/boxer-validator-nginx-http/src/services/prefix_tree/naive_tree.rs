@@ -1,5 +1,6 @@
 use crate::services::prefix_tree::MutablePrefixTree;
 use crate::services::prefix_tree::PrefixTree;
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
 use crate::services::prefix_tree::trie_bucket::TrieBucket;
 use async_trait::async_trait;
@@ -26,27 +27,72 @@ where
 #[async_trait]
 impl<Key, Value, Bucket> PrefixTree<Key, Value> for NaiveTrie<Bucket>
 where
-    Key: Hash + ParametrizedMatcher + Sync + Debug,
+    Key: Hash + ParametrizedMatcher + Clone + Send + Sync + Debug,
     Bucket: TrieBucket<Key, Value> + Send + Sync + Debug,
     Value: Send + Sync + 'static,
 {
     async fn get(&self, key: impl AsRef<[Key]> + Send) -> Option<Value> {
+        self.resolve(key).await.map(|(_, value)| value)
+    }
+}
+
+impl<Bucket> NaiveTrie<Bucket> {
+    /// Looks up a key and returns the branch that matched it along with the value.
+    pub async fn resolve<Key, Value>(&self, key: impl AsRef<[Key]> + Send) -> Option<(Vec<TrieEdge<Key>>, Value)>
+    where
+        Bucket: TrieBucket<Key, Value>,
+        Key: Clone,
+    {
         let keys = key.as_ref();
         if keys.is_empty() {
             return None;
         }
-        let mut current = self.root.clone();
+        let last = keys.last().expect("keys should always have at least one key");
 
-        for k in key.as_ref() {
-            let child = current.child(k).await;
-            match child {
-                Some(child) => current = child,
-                None => return None,
+        // Depth-first search: exact branches are tried before parameterized ones,
+        // and a dead end in an exact branch falls back to the next candidate.
+        let mut pending = vec![(self.root.clone(), Vec::new())];
+        while let Some((current, path)) = pending.pop() {
+            let depth = path.len();
+            if depth == keys.len() {
+                match current.get_value(last).await {
+                    Some(value) => return Some((path, value)),
+                    None => continue,
+                }
+            }
+
+            let children = current.matching_children(&keys[depth]).await;
+            for (edge, child) in children.into_iter().rev() {
+                let mut child_path = path.clone();
+                child_path.push(edge);
+                pending.push((child, child_path));
             }
         }
 
-        let last = keys.last().expect("keys should always have at least one key");
-        current.get_value(last).await
+        None
+    }
+
+    /// Returns every value in the trie with the branch it is stored under.
+    pub async fn entries<Key, Value>(&self) -> Vec<(Vec<TrieEdge<Key>>, Value)>
+    where
+        Bucket: TrieBucket<Key, Value>,
+        Key: Clone,
+    {
+        let mut entries = Vec::new();
+        let mut pending = vec![(self.root.clone(), Vec::new())];
+        while let Some((current, path)) = pending.pop() {
+            if !path.is_empty() {
+                for value in current.values().await {
+                    entries.push((path.clone(), value));
+                }
+            }
+            for (edge, child) in current.children().await {
+                let mut child_path = path.clone();
+                child_path.push(edge);
+                pending.push((child, child_path));
+            }
+        }
+        entries
     }
 }
 
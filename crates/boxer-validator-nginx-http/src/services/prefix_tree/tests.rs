@@ -1,9 +1,11 @@
 use crate::services::prefix_tree::MutablePrefixTree;
 use crate::services::prefix_tree::PrefixTree;
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::naive_tree::NaiveTrie;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
 use crate::services::prefix_tree::trie_bucket::hash_bucket::HashTrieBucket;
 use crate::services::prefix_tree::trie_bucket::request_segment_bucket::PrioritizedBucket;
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
 use boxer_core::services::validation_service::http_method::HTTPMethod;
 use boxer_core::services::validation_service::path_segment::PathSegment;
 use boxer_core::services::validation_service::request_context::RequestContext;
@@ -169,6 +171,71 @@ async fn test_path_segment_matchers(key: &str) -> String {
     trie.get(&rc).await.unwrap_or("".to_string())
 }
 
+fn route(verb: ActionSegment, path: &[&str]) -> Vec<ActionSegment> {
+    let mut key = vec![ActionSegment::Hostname("www.example.com".to_string()), verb];
+    key.extend(path.iter().map(|segment| match *segment {
+        "{}" => ActionSegment::Path(PathSegment::Parameter),
+        other => ActionSegment::Path(PathSegment::Static(other.to_string())),
+    }));
+    key
+}
+
+#[test_case("GET", "www.example.com/api/v1/items" => using wrapped_pretty_assert("get-items".to_string()); "exact verb wins over wildcard")]
+#[test_case("HEAD", "www.example.com/api/v1/items" => using wrapped_pretty_assert("any-items".to_string()); "wildcard verb matches other methods")]
+#[test_case("POST", "www.example.com/api/v1/other" => using wrapped_pretty_assert("any-other".to_string()); "falls back to wildcard when exact verb branch has no match")]
+#[test_case("GET", "www.example.com/api/v1/items/1" => using wrapped_pretty_assert("get-item".to_string()); "exact verb with parameter")]
+#[test_case("DELETE", "www.example.com/api/v1/items/1" => using wrapped_pretty_assert("".to_string()); "no match")]
+#[tokio::test]
+async fn test_verb_wildcard(method: &str, key: &str) -> String {
+    let mut trie = NaiveTrie::<PrioritizedBucket<ActionSegment, String>>::new();
+    trie.insert(
+        route(ActionSegment::AnyVerb, &["api", "v1", "items"]),
+        "any-items".to_string(),
+    )
+    .await;
+    trie.insert(
+        route(ActionSegment::AnyVerb, &["api", "v1", "other"]),
+        "any-other".to_string(),
+    )
+    .await;
+    trie.insert(
+        route(ActionSegment::Verb(HTTPMethod::Get), &["api", "v1", "items"]),
+        "get-items".to_string(),
+    )
+    .await;
+    trie.insert(
+        route(ActionSegment::Verb(HTTPMethod::Get), &["api", "v1", "items", "{}"]),
+        "get-item".to_string(),
+    )
+    .await;
+
+    let rc: Vec<RequestSegment> = RequestContext::new(format!("http://{key}"), method.to_string())
+        .try_into()
+        .unwrap();
+    let rc: Vec<ActionSegment> = rc.into_iter().map(ActionSegment::from).collect();
+    trie.get(&rc).await.unwrap_or("".to_string())
+}
+
+#[tokio::test]
+async fn test_exact_insert_does_not_extend_parameter_branch() {
+    let mut trie = NaiveTrie::<PrioritizedBucket<ActionSegment, String>>::new();
+    let verb = ActionSegment::Verb(HTTPMethod::Get);
+    trie.insert(route(verb.clone(), &["api", "{}", "x"]), "param".to_string())
+        .await;
+    trie.insert(route(verb.clone(), &["api", "static", "y"]), "static".to_string())
+        .await;
+
+    assert_eq!(trie.get(route(verb.clone(), &["api", "other", "y"])).await, None);
+    assert_eq!(
+        trie.get(route(verb.clone(), &["api", "static", "y"])).await,
+        Some("static".to_string())
+    );
+    assert_eq!(
+        trie.get(route(verb, &["api", "static", "x"])).await,
+        Some("param".to_string())
+    );
+}
+
 impl ParametrizedMatcher for u8 {
     fn is_parameter(&self) -> bool {
         false
@@ -180,3 +247,40 @@ impl ParametrizedMatcher for String {
         false
     }
 }
+
+#[tokio::test]
+async fn test_resolve_returns_matched_branch() {
+    let mut trie = NaiveTrie::<PrioritizedBucket<ActionSegment, String>>::new();
+    let verb = ActionSegment::Verb(HTTPMethod::Get);
+    trie.insert(route(verb.clone(), &["api", "{}"]), "param".to_string())
+        .await;
+
+    let (branch, value) = trie.resolve(route(verb.clone(), &["api", "photos"])).await.unwrap();
+
+    assert_eq!(value, "param");
+    assert_eq!(
+        branch,
+        vec![
+            TrieEdge::Exact(ActionSegment::Hostname("www.example.com".to_string())),
+            TrieEdge::Exact(verb),
+            TrieEdge::Exact(ActionSegment::Path(PathSegment::Static("api".to_string()))),
+            TrieEdge::Parameter(ActionSegment::Path(PathSegment::Parameter)),
+        ]
+    );
+}
+
+#[tokio::test]
+async fn test_entries_lists_every_value() {
+    let mut trie = NaiveTrie::<PrioritizedBucket<ActionSegment, String>>::new();
+    let verb = ActionSegment::Verb(HTTPMethod::Get);
+    trie.insert(route(verb.clone(), &["api"]), "api".to_string()).await;
+    trie.insert(route(verb.clone(), &["api", "{}"]), "param".to_string())
+        .await;
+    trie.insert(route(ActionSegment::AnyVerb, &["other"]), "other".to_string())
+        .await;
+
+    let mut values: Vec<String> = trie.entries().await.into_iter().map(|(_, value)| value).collect();
+    values.sort();
+
+    assert_eq!(values, vec!["api", "other", "param"]);
+}
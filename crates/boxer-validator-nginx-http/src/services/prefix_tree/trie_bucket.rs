@@ -1,3 +1,4 @@
+use crate::services::prefix_tree::TrieEdge;
 use async_trait::async_trait;
 use std::sync::Arc;
 
@@ -6,12 +7,22 @@ pub mod request_segment_bucket;
 
 #[async_trait]
 pub trait TrieBucket<Key, Value> {
+    /// Returns the child stored under exactly this key.
     async fn child(&self, key: &Key) -> Option<Arc<Self>>;
 
+    /// Returns the children that can continue a lookup for the key, ordered by priority.
+    async fn matching_children(&self, key: &Key) -> Vec<(TrieEdge<Key>, Arc<Self>)>;
+
+    /// Returns every child of this bucket.
+    async fn children(&self) -> Vec<(TrieEdge<Key>, Arc<Self>)>;
+
     async fn create_child(&self, key: &Key);
 
     async fn get_value(&self, key: &Key) -> Option<Value>;
 
+    /// Returns every value stored in this bucket.
+    async fn values(&self) -> Vec<Value>;
+
     async fn clear(&self, key: &Key) -> Option<Value>;
 
     async fn set_value(&self, value: Value, key: &Key);
@@ -1,3 +1,4 @@
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::trie_bucket::TrieBucket;
 use async_trait::async_trait;
 use std::collections::HashMap;
@@ -35,6 +36,23 @@ where
         self.children.read().await.get(key).map(|v| v.clone())
     }
 
+    async fn matching_children(&self, key: &Key) -> Vec<(TrieEdge<Key>, Arc<Self>)> {
+        self.child(key)
+            .await
+            .map(|child| (TrieEdge::Exact(key.clone()), child))
+            .into_iter()
+            .collect()
+    }
+
+    async fn children(&self) -> Vec<(TrieEdge<Key>, Arc<Self>)> {
+        self.children
+            .read()
+            .await
+            .iter()
+            .map(|(key, child)| (TrieEdge::Exact(key.clone()), child.clone()))
+            .collect()
+    }
+
     async fn create_child(&self, key: &Key) {
         self.children
             .write()
@@ -46,6 +64,10 @@ where
         self.value.read().await.clone()
     }
 
+    async fn values(&self) -> Vec<Value> {
+        self.value.read().await.iter().cloned().collect()
+    }
+
     async fn clear(&self, _key: &Key) -> Option<Value> {
         self.value.write().await.take()
     }
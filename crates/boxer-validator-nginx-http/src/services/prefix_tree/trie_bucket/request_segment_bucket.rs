@@ -1,3 +1,4 @@
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
 use crate::services::prefix_tree::trie_bucket::TrieBucket;
 use async_trait::async_trait;
@@ -42,17 +43,47 @@ where
     Key: ParametrizedMatcher + Send + Sync + Debug + Clone + Eq + Hash,
 {
     async fn child(&self, key: &Key) -> Option<Arc<Self>> {
-        let exact_match = self.next.exact_match.read().await.get(key).map(|c| c.clone());
-        if exact_match.is_some() {
-            return exact_match;
+        if key.is_parameter() {
+            self.next
+                .parameter
+                .read()
+                .await
+                .as_ref()
+                .map(|(_, child)| child.clone())
+        } else {
+            self.next.exact_match.read().await.get(key).cloned()
+        }
+    }
+
+    async fn matching_children(&self, key: &Key) -> Vec<(TrieEdge<Key>, Arc<Self>)> {
+        let exact_match = self.next.exact_match.read().await.get(key).cloned();
+        let parameter = self.next.parameter.read().await.clone();
+        exact_match
+            .map(|child| (TrieEdge::Exact(key.clone()), child))
+            .into_iter()
+            .chain(parameter.map(|(parameter, child)| (TrieEdge::Parameter(parameter), child)))
+            .collect()
+    }
+
+    async fn children(&self) -> Vec<(TrieEdge<Key>, Arc<Self>)> {
+        let mut children: Vec<(TrieEdge<Key>, Arc<Self>)> = self
+            .next
+            .exact_match
+            .read()
+            .await
+            .iter()
+            .map(|(key, child)| (TrieEdge::Exact(key.clone()), child.clone()))
+            .collect();
+        if let Some((parameter, child)) = self.next.parameter.read().await.clone() {
+            children.push((TrieEdge::Parameter(parameter), child));
         }
-        self.next.parameter.read().await.clone()
+        children
     }
 
     async fn create_child(&self, key: &Key) {
         if key.is_parameter() {
             let mut lock = self.next.parameter.write().await;
-            lock.replace(Arc::new(Self::default()));
+            lock.replace((key.clone(), Arc::new(Self::default())));
         } else {
             let mut lock = self.next.exact_match.write().await;
             lock.insert(key.clone(), Arc::new(Self::default()));
@@ -67,6 +98,12 @@ where
         self.parameter_value.read().await.clone()
     }
 
+    async fn values(&self) -> Vec<Value> {
+        let mut values: Vec<Value> = self.exact_labels.read().await.values().cloned().collect();
+        values.extend(self.parameter_value.read().await.clone());
+        values
+    }
+
     async fn clear(&self, key: &Key) -> Option<Value> {
         if key.is_parameter() {
             self.parameter_value.write().await.take()
@@ -84,6 +121,9 @@ where
     }
 }
 
+/// A parameter child together with the key it was registered under.
+type ParameterChild<Key, Value> = (Key, Arc<PrioritizedBucket<Key, Value>>);
+
 #[derive(Debug)]
 struct NextReference<Key, Value>
 where
@@ -91,7 +131,7 @@ where
     Value: Send + Sync,
 {
     exact_match: RwLock<HashMap<Key, Arc<PrioritizedBucket<Key, Value>>>>,
-    parameter: RwLock<Option<Arc<PrioritizedBucket<Key, Value>>>>,
+    parameter: RwLock<Option<ParameterChild<Key, Value>>>,
 }
 
 impl<Key, Value> NextReference<Key, Value>
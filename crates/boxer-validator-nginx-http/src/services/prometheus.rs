@@ -0,0 +1,28 @@
+use anyhow::Result;
+use opentelemetry_prometheus::PrometheusExporter;
+use prometheus::{Encoder, Registry, TextEncoder};
+
+/// Collects the OpenTelemetry instruments into a Prometheus registry for the `/metrics` endpoint.
+#[derive(Default)]
+pub struct PrometheusMetrics {
+    registry: Registry,
+}
+
+impl PrometheusMetrics {
+    /// Creates the registry and the reader that collects the instruments of a meter provider into it.
+    pub fn new() -> Result<(Self, PrometheusExporter)> {
+        let registry = Registry::new();
+        let exporter = opentelemetry_prometheus::exporter()
+            .with_registry(registry.clone())
+            .build()?;
+        Ok((PrometheusMetrics { registry }, exporter))
+    }
+
+    /// Renders the current values in the Prometheus text format.
+    pub fn render(&self) -> Result<(String, Vec<u8>)> {
+        let encoder = TextEncoder::new();
+        let mut buffer = Vec::new();
+        encoder.encode(&self.registry.gather(), &mut buffer)?;
+        Ok((encoder.format_type().to_string(), buffer))
+    }
+}
@@ -0,0 +1,217 @@
+#[cfg(test)]
+mod tests;
+
+use async_trait::async_trait;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::ResourceUpdateHandler;
+use kube::runtime::watcher;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use utoipa::ToSchema;
+
+#[derive(Default)]
+struct WatcherState {
+    /// `None` if the watch task is not owned by the validator and cannot be tracked.
+    running: Option<bool>,
+    synced: bool,
+    last_event: Option<Instant>,
+    failing_since: Option<Instant>,
+    last_error: Option<String>,
+    events: u64,
+    errors: u64,
+}
+
+/// Live status of a background watcher, updated from its event stream.
+pub struct WatcherStatus {
+    name: String,
+    state: Mutex<WatcherState>,
+}
+
+/// Marks the watch task as stopped when dropped, including when the task panics.
+pub struct RunningGuard(Arc<WatcherStatus>);
+
+impl Drop for RunningGuard {
+    fn drop(&mut self) {
+        self.0.state.lock().unwrap().running = Some(false);
+    }
+}
+
+impl WatcherStatus {
+    pub fn new(name: &str) -> Self {
+        WatcherStatus {
+            name: name.to_string(),
+            state: Mutex::new(WatcherState::default()),
+        }
+    }
+
+    /// Call at the start of the watch task and keep the guard for the lifetime of the task.
+    pub fn running(self: &Arc<Self>) -> RunningGuard {
+        self.state.lock().unwrap().running = Some(true);
+        RunningGuard(self.clone())
+    }
+
+    /// Marks the initial sync as done, for watchers that only report startup.
+    pub fn mark_synced(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.synced = true;
+        state.last_event = Some(Instant::now());
+    }
+
+    /// Records an event once it has been applied; `init_done` marks the end of the initial list.
+    pub fn applied(&self, init_done: bool) {
+        let mut state = self.state.lock().unwrap();
+        state.synced |= init_done;
+        state.events += 1;
+        state.last_event = Some(Instant::now());
+        state.failing_since = None;
+        state.last_error = None;
+    }
+
+    pub fn failed(&self, err: &watcher::Error) {
+        let mut state = self.state.lock().unwrap();
+        state.errors += 1;
+        state.failing_since.get_or_insert_with(Instant::now);
+        state.last_error = Some(err.to_string());
+    }
+
+    fn report(
+        &self,
+        now: Instant,
+        max_error_duration: Duration,
+        max_event_age: Option<Duration>,
+    ) -> ComponentReadiness {
+        let state = self.state.lock().unwrap();
+        let failing_for = state.failing_since.map(|since| now.saturating_duration_since(since));
+        let event_age = state
+            .last_event
+            .map(|last_event| now.saturating_duration_since(last_event));
+        let ready = state.running != Some(false)
+            && state.synced
+            && failing_for.is_none_or(|failing_for| failing_for <= max_error_duration)
+            && max_event_age.is_none_or(|max_event_age| event_age.is_some_and(|event_age| event_age <= max_event_age));
+        ComponentReadiness {
+            name: self.name.clone(),
+            ready,
+            running: state.running,
+            synced: state.synced,
+            last_event_age_seconds: event_age.map(|event_age| event_age.as_secs_f64()),
+            failing_for_seconds: failing_for.map(|failing_for| failing_for.as_secs_f64()),
+            last_error: state.last_error.clone(),
+            events: state.events,
+            errors: state.errors,
+        }
+    }
+}
+
+/// Reports the events of a watch stream owned by boxer-core to readiness before handing them to `inner`.
+pub struct ReportingUpdateHandler<H> {
+    inner: H,
+    status: Arc<WatcherStatus>,
+}
+
+impl<H> ReportingUpdateHandler<H> {
+    pub fn new(inner: H, status: Arc<WatcherStatus>) -> Self {
+        ReportingUpdateHandler { inner, status }
+    }
+}
+
+#[async_trait]
+impl<S, H> ResourceUpdateHandler<S> for ReportingUpdateHandler<H>
+where
+    S: Send + 'static,
+    H: ResourceUpdateHandler<S>,
+{
+    async fn handle_update(&self, event: Result<S, watcher::Error>) {
+        match &event {
+            Ok(_) => self.status.applied(false),
+            Err(err) => self.status.failed(err),
+        }
+        self.inner.handle_update(event).await
+    }
+}
+
+/// Readiness of a single component, as reported by `/health/ready`.
+#[derive(ToSchema, Serialize, Debug, PartialEq)]
+pub struct ComponentReadiness {
+    pub name: String,
+    pub ready: bool,
+    /// Whether the watch task is alive; omitted for watchers owned by boxer-core.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub running: Option<bool>,
+    /// Whether the initial list has completed.
+    pub synced: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_event_age_seconds: Option<f64>,
+    /// How long the watch stream has been failing, if it is in backoff.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failing_for_seconds: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_error: Option<String>,
+    /// Watch events applied since startup.
+    pub events: u64,
+    /// Watch stream errors since startup.
+    pub errors: u64,
+}
+
+#[derive(ToSchema, Serialize, Debug)]
+pub struct ReadinessReport {
+    pub ready: bool,
+    /// Set once a graceful shutdown has started; the service stays not ready from then on.
+    pub shutting_down: bool,
+    pub components: Vec<ComponentReadiness>,
+}
+
+/// Computes readiness from the live status of every registered watcher.
+pub struct Readiness {
+    components: RwLock<Vec<Arc<WatcherStatus>>>,
+    max_error_duration: Duration,
+    max_event_age: Option<Duration>,
+    shutting_down: AtomicBool,
+}
+
+impl Readiness {
+    /// A watcher that keeps failing for longer than `max_error_duration` makes the service not ready.
+    pub fn new(max_error_duration: Duration) -> Self {
+        Readiness {
+            components: RwLock::new(Vec::new()),
+            max_error_duration,
+            max_event_age: None,
+            shutting_down: AtomicBool::new(false),
+        }
+    }
+
+    /// A watcher that has not received an event for longer than `max_event_age` makes the service not ready.
+    pub fn with_max_event_age(mut self, max_event_age: Option<Duration>) -> Self {
+        self.max_event_age = max_event_age;
+        self
+    }
+
+    /// Reports the service as not ready regardless of the watchers, so it can be drained before stopping.
+    pub fn begin_shutdown(&self) {
+        self.shutting_down.store(true, Ordering::Relaxed);
+    }
+
+    pub fn register(&self, name: &str) -> Arc<WatcherStatus> {
+        let status = Arc::new(WatcherStatus::new(name));
+        self.components.write().unwrap().push(status.clone());
+        status
+    }
+
+    pub fn report(&self) -> ReadinessReport {
+        let now = Instant::now();
+        let components: Vec<ComponentReadiness> = self
+            .components
+            .read()
+            .unwrap()
+            .iter()
+            .map(|status| status.report(now, self.max_error_duration, self.max_event_age))
+            .collect();
+        let shutting_down = self.shutting_down.load(Ordering::Relaxed);
+        ReadinessReport {
+            ready: !shutting_down && components.iter().all(|component| component.ready),
+            shutting_down,
+            components,
+        }
+    }
+}
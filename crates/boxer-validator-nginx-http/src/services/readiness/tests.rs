@@ -0,0 +1,148 @@
+use super::*;
+use test_case::test_case;
+
+const MAX_ERROR_DURATION: Duration = Duration::from_secs(60);
+
+fn synced(name: &str) -> Arc<WatcherStatus> {
+    let status = Arc::new(WatcherStatus::new(name));
+    status.applied(false);
+    status.applied(true);
+    status
+}
+
+#[test]
+fn test_watcher_is_not_ready_before_initial_sync() {
+    let status = WatcherStatus::new("action_lookup");
+    status.applied(false);
+
+    let report = status.report(Instant::now(), MAX_ERROR_DURATION, None);
+
+    assert!(!report.ready);
+    assert!(!report.synced);
+}
+
+#[test]
+fn test_watcher_is_ready_after_initial_sync() {
+    let status = synced("action_lookup");
+
+    assert!(status.report(Instant::now(), MAX_ERROR_DURATION, None).ready);
+}
+
+#[test]
+fn test_watcher_failing_longer_than_allowed_is_not_ready() {
+    let status = synced("action_lookup");
+    status.failed(&watcher::Error::NoResourceVersion);
+    let now = Instant::now();
+
+    let within_grace = status.report(now, MAX_ERROR_DURATION, None);
+    let after_grace = status.report(now + MAX_ERROR_DURATION * 2, MAX_ERROR_DURATION, None);
+
+    assert!(within_grace.ready);
+    assert!(!after_grace.ready);
+    assert!(after_grace.last_error.is_some());
+    assert_eq!((after_grace.events, after_grace.errors), (2, 1));
+}
+
+#[test]
+fn test_watcher_recovers_after_successful_event() {
+    let status = synced("action_lookup");
+    status.failed(&watcher::Error::NoResourceVersion);
+    status.applied(false);
+
+    let report = status.report(Instant::now() + MAX_ERROR_DURATION * 2, MAX_ERROR_DURATION, None);
+
+    assert!(report.ready);
+    assert_eq!(report.failing_for_seconds, None);
+}
+
+#[test]
+fn test_stopped_watch_task_is_not_ready() {
+    let status = synced("action_lookup");
+    let running = status.running();
+    assert_eq!(
+        status.report(Instant::now(), MAX_ERROR_DURATION, None).running,
+        Some(true)
+    );
+
+    drop(running);
+
+    let report = status.report(Instant::now(), MAX_ERROR_DURATION, None);
+    assert_eq!(report.running, Some(false));
+    assert!(!report.ready);
+}
+
+#[tokio::test]
+async fn test_panicking_watch_task_is_not_ready() {
+    let status = synced("action_lookup");
+    let task_status = status.clone();
+
+    let result = tokio::spawn(async move {
+        let _running = task_status.running();
+        panic!("watch stream failed");
+    })
+    .await;
+
+    assert!(result.is_err());
+    assert!(!status.report(Instant::now(), MAX_ERROR_DURATION, None).ready);
+}
+
+#[test]
+fn test_report_lists_every_component() {
+    let readiness = Readiness::new(MAX_ERROR_DURATION);
+    readiness.register("schema_repository").mark_synced();
+    readiness.register("policy_lookup");
+
+    let report = readiness.report();
+
+    assert!(!report.ready);
+    let components: Vec<(&str, bool)> = report
+        .components
+        .iter()
+        .map(|component| (component.name.as_str(), component.ready))
+        .collect();
+    assert_eq!(components, vec![("schema_repository", true), ("policy_lookup", false)]);
+}
+
+#[test]
+fn test_shutdown_makes_service_not_ready() {
+    let readiness = Readiness::new(MAX_ERROR_DURATION);
+    readiness.register("schema_repository").mark_synced();
+
+    readiness.begin_shutdown();
+
+    let report = readiness.report();
+    assert!(report.shutting_down);
+    assert!(!report.ready);
+    assert!(report.components[0].ready);
+}
+
+#[test_case(Duration::from_secs(30) => true; "recent event")]
+#[test_case(Duration::from_secs(120) => false; "stale watcher")]
+fn test_watcher_event_age(age: Duration) -> bool {
+    let status = synced("action_lookup");
+    let max_event_age = Some(Duration::from_secs(60));
+
+    status
+        .report(Instant::now() + age, MAX_ERROR_DURATION, max_event_age)
+        .ready
+}
+
+struct NoopHandler;
+
+#[async_trait]
+impl ResourceUpdateHandler<String> for NoopHandler {
+    async fn handle_update(&self, _event: Result<String, watcher::Error>) {}
+}
+
+#[tokio::test]
+async fn test_reporting_update_handler_tracks_events() {
+    let status = Arc::new(WatcherStatus::new("schema_repository"));
+    let handler = ReportingUpdateHandler::new(NoopHandler, status.clone());
+
+    handler.handle_update(Ok("schema".to_string())).await;
+    handler.handle_update(Err(watcher::Error::NoResourceVersion)).await;
+
+    let report = status.report(Instant::now(), MAX_ERROR_DURATION, None);
+    assert_eq!((report.events, report.errors), (1, 1));
+    assert!(report.failing_for_seconds.is_some());
+}
@@ -1,10 +1,27 @@
 pub mod action_discovery_document;
+pub mod action_segment;
+pub mod public_routes;
 pub mod read_write;
 
 #[cfg(test)]
 mod tests;
 
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
 use crate::services::repositories::lookup_trie::schema_bound_trie_repository::SchemaBoundedTrieRepositoryData;
+use async_trait::async_trait;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
 use boxer_core::services::validation_service::request_segment::RequestSegment;
+use cedar_policy::EntityUid;
 
-pub type ActionReadOnlyRepository = SchemaBoundedTrieRepositoryData<RequestSegment>;
+pub type ActionReadOnlyRepository = SchemaBoundedTrieRepositoryData<ActionSegment>;
+
+/// Looks up the request segments built by the validation service.
+#[async_trait]
+impl ReadOnlyRepository<(String, Vec<RequestSegment>), EntityUid> for ActionReadOnlyRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, (schema, segments): (String, Vec<RequestSegment>)) -> Result<EntityUid, Self::ReadError> {
+        let key: Vec<ActionSegment> = segments.into_iter().map(ActionSegment::from).collect();
+        ReadOnlyRepository::<(String, Vec<ActionSegment>), EntityUid>::get(self, (schema, key)).await
+    }
+}
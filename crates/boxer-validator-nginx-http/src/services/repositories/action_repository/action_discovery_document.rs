@@ -1,11 +1,14 @@
-use crate::http::controllers::v1::action_set::models::{ActionRouteRegistration, SchemaBoundActionSetRegistration};
+use crate::http::controllers::v1::action_set::models::{
+    ActionMethodRegistration, ActionRouteRegistration, SchemaBoundActionSetRegistration,
+};
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
+use crate::services::repositories::list_repository::ListableResource;
 use crate::services::repositories::lookup_trie::{EntityCollectionResource, SchemaBoundResource};
+use crate::services::route_template::{normalize_hostname, parse_route_template};
+use anyhow::bail;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::soft_delete_resource::SoftDeleteResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::UpdateLabels;
 use boxer_core::services::validation_service::http_method::HTTPMethod;
-use boxer_core::services::validation_service::path_segment::PathSegment::{Parameter, Static};
-use boxer_core::services::validation_service::request_segment::RequestSegment;
-use boxer_core::services::validation_service::request_segment::RequestSegment::{Hostname, Path, Verb};
 use cedar_policy::EntityUid;
 use futures::Stream;
 use futures::StreamExt;
@@ -17,30 +20,97 @@ use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
 use std::str::FromStr;
 
+/// Marker for the `"*"` method of an action route.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq)]
+pub enum MethodWildcard {
+    #[serde(rename = "*")]
+    Any,
+}
+
+/// HTTP methods an action route applies to.
+#[derive(Serialize, Deserialize, Clone, Debug, JsonSchema, PartialEq)]
+#[serde(untagged)]
+pub enum ActionRouteMethod {
+    Any(MethodWildcard),
+    Single(HTTPMethod),
+    List(Vec<HTTPMethod>),
+}
+
+impl ActionRouteMethod {
+    /// Returns the verb keys this route should be registered under in the lookup trie.
+    pub fn verbs(&self) -> Vec<ActionSegment> {
+        match self {
+            ActionRouteMethod::Any(_) => vec![ActionSegment::AnyVerb],
+            ActionRouteMethod::Single(method) => vec![ActionSegment::Verb(method.clone())],
+            ActionRouteMethod::List(methods) => {
+                let mut verbs: Vec<ActionSegment> = Vec::with_capacity(methods.len());
+                for method in methods {
+                    let verb = ActionSegment::Verb(method.clone());
+                    if !verbs.contains(&verb) {
+                        verbs.push(verb);
+                    }
+                }
+                verbs
+            }
+        }
+    }
+}
+
+impl FromStr for ActionRouteMethod {
+    type Err = anyhow::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        if value.trim() == "*" {
+            return Ok(ActionRouteMethod::Any(MethodWildcard::Any));
+        }
+        Ok(ActionRouteMethod::Single(HTTPMethod::from_str(value.trim())?))
+    }
+}
+
+impl TryFrom<&[String]> for ActionRouteMethod {
+    type Error = anyhow::Error;
+
+    fn try_from(values: &[String]) -> Result<Self, Self::Error> {
+        if values.is_empty() {
+            bail!("Method list must not be empty");
+        }
+        if values.iter().any(|value| value.trim() == "*") {
+            return Ok(ActionRouteMethod::Any(MethodWildcard::Any));
+        }
+        let methods = values
+            .iter()
+            .map(|value| HTTPMethod::from_str(value.trim()))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(ActionRouteMethod::List(methods))
+    }
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug, JsonSchema)]
 #[serde(rename_all = "camelCase")]
 pub struct ActionRoute {
-    pub method: HTTPMethod,
+    pub method: ActionRouteMethod,
     pub route_template: String,
     pub action_uid: String,
+    /// Requests matching a public route are allowed without a token, if the schema is the only one
+    /// registering routes on the hostname.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub public: bool,
 }
 
-impl TryInto<Vec<RequestSegment>> for ActionRoute {
+impl TryInto<Vec<Vec<ActionSegment>>> for ActionRoute {
     type Error = anyhow::Error;
 
-    fn try_into(self) -> Result<Vec<RequestSegment>, Self::Error> {
-        let mut segments: Vec<RequestSegment> = vec![Verb(self.method.try_into()?)];
-        for segment in self.route_template.split('/') {
-            if segment.is_empty() {
-                continue;
-            }
-            if segment.starts_with('{') && segment.ends_with('}') {
-                segments.push(Path(Parameter))
-            } else {
-                segments.push(Path(Static(segment.to_string())))
-            }
-        }
-        Ok(segments)
+    fn try_into(self) -> Result<Vec<Vec<ActionSegment>>, Self::Error> {
+        let path: Vec<ActionSegment> = parse_route_template(&self.route_template)?
+            .into_iter()
+            .map(ActionSegment::Path)
+            .collect();
+        Ok(self
+            .method
+            .verbs()
+            .into_iter()
+            .map(|verb| std::iter::once(verb).chain(path.iter().cloned()).collect())
+            .collect())
     }
 }
 
@@ -51,33 +121,66 @@ impl TryInto<Vec<RequestSegment>> for ActionRoute {
     kind = "ActionDiscoveryDocument",
     plural = "action-discovery-documents",
     singular = "action-discovery-document",
-    namespaced
+    namespaced,
+    selectable = ".spec.schema",
+    selectable = ".spec.active"
 )]
 
 pub struct ActionDiscoveryDocumentSpec {
+    /// The id the document is registered under; unset on documents written before ids were stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub active: bool,
     pub hostname: String,
     pub routes: Vec<ActionRoute>,
     pub schema: String,
 }
 
-impl EntityCollectionResource<RequestSegment> for ActionDiscoveryDocument {
-    fn stream(self) -> impl Stream<Item = Result<(Vec<RequestSegment>, EntityUid, bool), anyhow::Error>> + Send + Sync {
+impl EntityCollectionResource<ActionSegment> for ActionDiscoveryDocument {
+    fn stream(self) -> impl Stream<Item = Result<(Vec<ActionSegment>, EntityUid, bool), anyhow::Error>> + Send + Sync {
         let hostname = self.spec.hostname.clone();
         let active = self.spec.active;
         stream::iter(self.spec.routes)
             .zip(stream::repeat(hostname))
             .zip(stream::repeat(active))
-            .map(move |((route, hostname), active)| {
-                let action_uid: EntityUid = EntityUid::from_str(&route.action_uid).map_err(anyhow::Error::from)?;
-                let mut key: Vec<RequestSegment> = vec![Hostname(hostname)];
-                let segments: Vec<RequestSegment> = route.try_into()?;
-                key.extend(segments);
-                Ok((key, action_uid, active))
-            })
+            .flat_map(move |((route, hostname), active)| stream::iter(route_entries(route, hostname, active)))
     }
 }
 
+impl ActionDiscoveryDocument {
+    /// Streams the routes of the document for the public route trie, with whether each of them is public.
+    /// A deleted document has no routes.
+    pub fn route_access(self) -> impl Stream<Item = RouteEntry> + Send + Sync {
+        let hostname = self.spec.hostname.clone();
+        let routes = if self.spec.active { self.spec.routes } else { Vec::new() };
+        stream::iter(routes).flat_map(move |route| {
+            let public = route.public;
+            stream::iter(route_entries(route, hostname.clone(), public))
+        })
+    }
+}
+
+type RouteEntry = Result<(Vec<ActionSegment>, EntityUid, bool), anyhow::Error>;
+
+/// Expands a route into one lookup entry per HTTP method it applies to.
+fn route_entries(route: ActionRoute, hostname: String, active: bool) -> Vec<RouteEntry> {
+    let action_uid = match EntityUid::from_str(&route.action_uid) {
+        Ok(action_uid) => action_uid,
+        Err(e) => return vec![Err(anyhow::Error::from(e))],
+    };
+    let keys: Vec<Vec<ActionSegment>> = match route.try_into() {
+        Ok(keys) => keys,
+        Err(e) => return vec![Err(e)],
+    };
+    keys.into_iter()
+        .map(|segments| {
+            let mut key: Vec<ActionSegment> = vec![ActionSegment::Hostname(normalize_hostname(&hostname))];
+            key.extend(segments);
+            Ok((key, action_uid.clone(), active))
+        })
+        .collect()
+}
+
 impl Default for ActionDiscoveryDocument {
     fn default() -> Self {
         ActionDiscoveryDocument {
@@ -94,15 +197,17 @@ impl TryFrom<&SchemaBoundActionSetRegistration> for ActionDiscoveryDocumentSpec
         let mut routes = Vec::<ActionRoute>::new();
 
         for route in &value.routes {
-            let method = HTTPMethod::from_str(&route.method)?;
+            let method = ActionRouteMethod::try_from(&route.method)?;
             let action_route = ActionRoute {
                 method,
                 route_template: route.route_template.clone(),
                 action_uid: route.action_uid.to_string(),
+                public: route.public,
             };
             routes.push(action_route)
         }
         Ok(ActionDiscoveryDocumentSpec {
+            id: Some(value.id.clone()),
             active: true,
             hostname: value.hostname.clone(),
             routes,
@@ -117,9 +222,10 @@ impl Into<SchemaBoundActionSetRegistration> for ActionDiscoveryDocumentSpec {
             .routes
             .into_iter()
             .map(|route| ActionRouteRegistration {
-                method: route.method.to_string(),
+                method: route.method.into(),
                 route_template: route.route_template,
                 action_uid: route.action_uid,
+                public: route.public,
             })
             .collect();
 
@@ -127,6 +233,30 @@ impl Into<SchemaBoundActionSetRegistration> for ActionDiscoveryDocumentSpec {
             hostname: self.hostname,
             routes,
             schema: self.schema,
+            id: self.id.unwrap_or_default(),
+        }
+    }
+}
+
+impl TryFrom<&ActionMethodRegistration> for ActionRouteMethod {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &ActionMethodRegistration) -> Result<Self, Self::Error> {
+        match value {
+            ActionMethodRegistration::Single(method) => ActionRouteMethod::from_str(method),
+            ActionMethodRegistration::List(methods) => ActionRouteMethod::try_from(methods.as_slice()),
+        }
+    }
+}
+
+impl From<ActionRouteMethod> for ActionMethodRegistration {
+    fn from(value: ActionRouteMethod) -> Self {
+        match value {
+            ActionRouteMethod::Any(_) => ActionMethodRegistration::Single("*".to_string()),
+            ActionRouteMethod::Single(method) => ActionMethodRegistration::Single(method.to_string()),
+            ActionRouteMethod::List(methods) => {
+                ActionMethodRegistration::List(methods.iter().map(|method| method.to_string()).collect())
+            }
         }
     }
 }
@@ -159,3 +289,13 @@ impl SchemaBoundResource for ActionDiscoveryDocument {
         self.spec.schema.clone()
     }
 }
+
+impl ListableResource for ActionDiscoveryDocument {
+    fn id(&self) -> Option<String> {
+        self.spec.id.clone()
+    }
+
+    fn hostname(&self) -> Option<String> {
+        Some(self.spec.hostname.clone())
+    }
+}
@@ -0,0 +1,45 @@
+use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
+use crate::services::route_template::{NormalizeSegment, normalize_hostname};
+use boxer_core::services::validation_service::http_method::HTTPMethod;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+
+/// A segment of an action lookup key: `[hostname, verb, path...]`.
+/// Mirrors `RequestSegment`, with a dedicated value for a method wildcard.
+#[derive(Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ActionSegment {
+    Hostname(String),
+    Verb(HTTPMethod),
+    /// Matches any HTTP method; only used when no route is registered for the exact method.
+    AnyVerb,
+    Path(PathSegment),
+}
+
+impl From<RequestSegment> for ActionSegment {
+    fn from(segment: RequestSegment) -> Self {
+        match segment {
+            RequestSegment::Hostname(hostname) => ActionSegment::Hostname(hostname),
+            RequestSegment::Verb(method) => ActionSegment::Verb(method),
+            RequestSegment::Path(segment) => ActionSegment::Path(segment),
+        }
+    }
+}
+
+impl ParametrizedMatcher for ActionSegment {
+    fn is_parameter(&self) -> bool {
+        matches!(
+            self,
+            ActionSegment::AnyVerb | ActionSegment::Path(PathSegment::Parameter)
+        )
+    }
+}
+
+impl NormalizeSegment for ActionSegment {
+    fn normalize(self) -> Option<Self> {
+        match self {
+            ActionSegment::Hostname(hostname) => Some(ActionSegment::Hostname(normalize_hostname(&hostname))),
+            ActionSegment::Path(segment) => segment.normalize().map(ActionSegment::Path),
+            verb => Some(verb),
+        }
+    }
+}
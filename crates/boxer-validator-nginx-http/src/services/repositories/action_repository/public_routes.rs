@@ -0,0 +1,181 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::prefix_tree::naive_tree::NaiveTrie;
+use crate::services::prefix_tree::trie_bucket::request_segment_bucket::PrioritizedBucket;
+use crate::services::prefix_tree::{MutablePrefixTree, PrefixTree};
+use crate::services::repositories::action_repository::action_discovery_document::ActionDiscoveryDocument;
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
+use crate::services::route_template::{normalize_hostname, normalize_key};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::ResourceUpdateHandler;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+use cedar_policy::EntityUid;
+use futures::StreamExt;
+use kube::ResourceExt;
+use kube::runtime::watcher;
+use log::{info, warn};
+use std::collections::{HashMap, HashSet};
+use std::pin::pin;
+use tokio::sync::RwLock;
+
+/// Whether requests matching a route may skip the token review.
+#[derive(Debug, Clone, PartialEq)]
+enum RouteAccess {
+    Public(EntityUid),
+    Protected,
+}
+
+/// The routes an action discovery document registers, with whether each of them is public.
+struct DocumentRoutes {
+    schema: String,
+    hostname: ActionSegment,
+    routes: HashMap<Vec<ActionSegment>, (EntityUid, bool)>,
+}
+
+/// Action routes of all schemas, public and protected, so the most specific match decides
+/// whether a request is allowed without a token. There is no schema to look them up in before
+/// the token is read.
+///
+/// A route is public only if every document registering it marks it public, and only if its schema
+/// is the only one registering routes on the hostname.
+pub struct PublicRouteRepository {
+    routes: RwLock<NaiveTrie<PrioritizedBucket<ActionSegment, RouteAccess>>>,
+    documents: RwLock<HashMap<String, DocumentRoutes>>,
+}
+
+impl PublicRouteRepository {
+    pub fn new() -> Self {
+        PublicRouteRepository {
+            routes: RwLock::new(NaiveTrie::new()),
+            documents: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Returns true if the most specific route matching the request is public.
+    pub async fn is_public(&self, request_context: RequestContext) -> bool {
+        match request_context.try_into() {
+            Ok(key) => self.get(key).await.is_ok(),
+            Err(_) => false,
+        }
+    }
+}
+
+impl Default for PublicRouteRepository {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// The schema owning the hostname, if it is the only schema registering routes on it.
+fn owner<'a>(documents: &'a HashMap<String, DocumentRoutes>, hostname: &ActionSegment) -> Option<&'a str> {
+    let mut schemas = documents
+        .values()
+        .filter(|document| &document.hostname == hostname)
+        .map(|document| document.schema.as_str());
+    let schema = schemas.next()?;
+    schemas.all(|other| other == schema).then_some(schema)
+}
+
+/// Resolves the access of a route from every document registering it; a protected registration wins.
+fn access(documents: &HashMap<String, DocumentRoutes>, key: &Vec<ActionSegment>) -> Option<RouteAccess> {
+    let mut access = None;
+    for document in documents.values() {
+        let Some((uid, public)) = document.routes.get(key) else {
+            continue;
+        };
+        if !public || owner(documents, &document.hostname) != Some(document.schema.as_str()) {
+            return Some(RouteAccess::Protected);
+        }
+        access = Some(RouteAccess::Public(uid.clone()));
+    }
+    access
+}
+
+#[async_trait]
+impl ReadOnlyRepository<Vec<RequestSegment>, EntityUid> for PublicRouteRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: Vec<RequestSegment>) -> Result<EntityUid, Self::ReadError> {
+        let key: Vec<ActionSegment> = normalize_key(key.into_iter().map(ActionSegment::from).collect());
+        match self.routes.read().await.get(&key).await {
+            Some(RouteAccess::Public(uid)) => Ok(uid),
+            Some(RouteAccess::Protected) => Err(anyhow!("Route is protected: {:?}", key)),
+            None => Err(anyhow!("Route not found: {:?}", key)),
+        }
+    }
+}
+
+#[async_trait]
+impl ResourceUpdateHandler<ActionDiscoveryDocument> for PublicRouteRepository {
+    async fn handle_update(&self, result: Result<ActionDiscoveryDocument, watcher::Error>) -> () {
+        let document = match result {
+            Ok(document) => document,
+            Err(e) => {
+                warn!("Error handling public route update: {:?}", e);
+                return;
+            }
+        };
+        let name = document.name_any();
+        let schema = document.spec.schema.clone();
+        let hostname = ActionSegment::Hostname(normalize_hostname(&document.spec.hostname));
+        let mut routes = HashMap::new();
+        let mut entries = pin!(document.route_access());
+        while let Some(entry) = entries.next().await {
+            match entry {
+                Ok((key, uid, public)) => {
+                    routes.insert(key, (uid, public));
+                }
+                Err(e) => warn!(resource_id = name; "Failed to read public route: {}", e),
+            }
+        }
+        let publishes = routes.values().any(|(_, public)| *public);
+
+        let mut documents = self.documents.write().await;
+        let previous = if routes.is_empty() {
+            documents.remove(&name)
+        } else {
+            documents.insert(
+                name.clone(),
+                DocumentRoutes {
+                    schema: schema.clone(),
+                    hostname: hostname.clone(),
+                    routes,
+                },
+            )
+        };
+        // Ownership of the hostnames may have changed, so every route on them is resolved again
+        let mut hostnames = HashSet::from([hostname.clone()]);
+        let mut keys: HashSet<Vec<ActionSegment>> = HashSet::new();
+        if let Some(previous) = previous {
+            hostnames.insert(previous.hostname);
+            keys.extend(previous.routes.into_keys());
+        }
+        for document in documents
+            .values()
+            .filter(|document| hostnames.contains(&document.hostname))
+        {
+            keys.extend(document.routes.keys().cloned());
+        }
+
+        let mut trie = self.routes.write().await;
+        for key in keys {
+            match access(&documents, &key) {
+                Some(access) => trie.insert(key, access).await,
+                None => {
+                    trie.delete(key).await;
+                }
+            }
+        }
+        if publishes && owner(&documents, &hostname) != Some(schema.as_str()) {
+            warn!(
+                resource_id = name;
+                "Public routes are ignored, the hostname is also registered by another schema"
+            );
+        }
+        info!(resource_id = name; "Finished updating public routes");
+    }
+}
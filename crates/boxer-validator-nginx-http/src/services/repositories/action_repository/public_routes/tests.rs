@@ -0,0 +1,237 @@
+use super::*;
+use crate::services::repositories::action_repository::action_discovery_document::{
+    ActionDiscoveryDocumentSpec, ActionRoute, ActionRouteMethod,
+};
+use std::str::FromStr;
+use test_case::test_case;
+
+fn route(method: &str, route_template: &str, public: bool) -> ActionRoute {
+    ActionRoute {
+        method: ActionRouteMethod::from_str(method).unwrap(),
+        route_template: route_template.to_string(),
+        action_uid: "PhotoApp::Action::\"viewPhoto\"".to_string(),
+        public,
+    }
+}
+
+fn document(active: bool, routes: Vec<ActionRoute>) -> ActionDiscoveryDocument {
+    ActionDiscoveryDocument::new(
+        "photo-app-actions",
+        ActionDiscoveryDocumentSpec {
+            id: Some("actions".to_string()),
+            active,
+            hostname: "www.example.com".to_string(),
+            routes,
+            schema: "photo-app".to_string(),
+        },
+    )
+}
+
+fn request(url: &str, method: &str) -> RequestContext {
+    RequestContext::new(url.to_string(), method.to_string())
+}
+
+#[test_case("https://www.example.com/health", "GET" => true; "public route")]
+#[test_case("https://www.example.com/static/app.js", "GET" => true; "public route with parameter")]
+#[test_case("https://www.example.com/health", "POST" => false; "other method")]
+#[test_case("https://www.example.com/photos/1", "GET" => false; "protected route")]
+#[test_case("https://api.example.com/health", "GET" => false; "other hostname")]
+#[tokio::test]
+async fn test_is_public(url: &str, method: &str) -> bool {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(
+            true,
+            vec![
+                route("GET", "health", true),
+                route("*", "static/{file}", true),
+                route("GET", "photos/{photoId}", false),
+            ],
+        )))
+        .await;
+
+    public_routes.is_public(request(url, method)).await
+}
+
+#[tokio::test]
+async fn test_route_is_removed_once_no_longer_public() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "health", true)])))
+        .await;
+
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "health", false)])))
+        .await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_deleted_document_removes_public_routes() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "health", true)])))
+        .await;
+
+    public_routes
+        .handle_update(Ok(document(false, vec![route("GET", "health", true)])))
+        .await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_route_dropped_from_document_is_removed() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(
+            true,
+            vec![route("GET", "health", true), route("GET", "ready", true)],
+        )))
+        .await;
+
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "ready", true)])))
+        .await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+    assert!(
+        public_routes
+            .is_public(request("https://www.example.com/ready", "GET"))
+            .await
+    );
+}
+
+fn other_document(name: &str, schema: &str, routes: Vec<ActionRoute>) -> ActionDiscoveryDocument {
+    let mut other = document(true, routes);
+    other.metadata.name = Some(name.to_string());
+    other.spec.schema = schema.to_string();
+    other
+}
+
+#[tokio::test]
+async fn test_protected_route_of_another_document_wins() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "health", true)])))
+        .await;
+
+    public_routes
+        .handle_update(Ok(other_document(
+            "photo-app-other-actions",
+            "photo-app",
+            vec![route("GET", "health", false)],
+        )))
+        .await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+}
+
+#[test_case("https://www.example.com/photos/1", "GET" => true; "public parameter route")]
+#[test_case("https://www.example.com/photos/private", "GET" => false; "more specific protected route")]
+#[tokio::test]
+async fn test_most_specific_route_wins(url: &str, method: &str) -> bool {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "photos/{photoId}", true)])))
+        .await;
+    public_routes
+        .handle_update(Ok(other_document(
+            "photo-app-other-actions",
+            "photo-app",
+            vec![route("GET", "photos/private", false)],
+        )))
+        .await;
+
+    public_routes.is_public(request(url, method)).await
+}
+
+#[tokio::test]
+async fn test_public_routes_require_the_schema_to_own_the_hostname() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "static/{file}", true)])))
+        .await;
+    let other = other_document("other-app-actions", "other-app", vec![route("GET", "admin", false)]);
+    public_routes.handle_update(Ok(other.clone())).await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/static/app.js", "GET"))
+            .await
+    );
+
+    let mut deleted = other;
+    deleted.spec.active = false;
+    public_routes.handle_update(Ok(deleted)).await;
+
+    assert!(
+        public_routes
+            .is_public(request("https://www.example.com/static/app.js", "GET"))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_public_route_of_a_schema_not_owning_the_hostname_is_ignored() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "photos/{photoId}", false)])))
+        .await;
+
+    public_routes
+        .handle_update(Ok(other_document(
+            "other-app-actions",
+            "other-app",
+            vec![route("GET", "{path}", true)],
+        )))
+        .await;
+
+    assert!(
+        !public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+}
+
+#[tokio::test]
+async fn test_route_published_by_two_documents_stays_public_until_both_drop_it() {
+    let public_routes = PublicRouteRepository::new();
+    public_routes
+        .handle_update(Ok(other_document(
+            "photo-app-other-actions",
+            "photo-app",
+            vec![route("GET", "health", true)],
+        )))
+        .await;
+    public_routes
+        .handle_update(Ok(document(true, vec![route("GET", "health", true)])))
+        .await;
+
+    public_routes
+        .handle_update(Ok(document(false, vec![route("GET", "health", true)])))
+        .await;
+
+    assert!(
+        public_routes
+            .is_public(request("https://www.example.com/health", "GET"))
+            .await
+    );
+}
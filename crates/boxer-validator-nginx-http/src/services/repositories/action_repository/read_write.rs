@@ -1,5 +1,6 @@
 use crate::http::controllers::v1::action_set::models::SchemaBoundActionSetRegistration;
 use crate::services::repositories::action_repository::action_discovery_document::ActionDiscoveryDocument;
+use crate::services::repositories::list_repository::ListRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::KubernetesRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::GenericKubernetesResourceManager;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
@@ -13,6 +14,8 @@ pub type ActionDataRepository = dyn UpsertRepositoryWithDelete<
         ReadError = Status,
     >;
 
+pub type ActionSetListRepository = dyn ListRepository<SchemaBoundActionSetRegistration>;
+
 impl UpsertRepositoryWithDelete<(String, String), SchemaBoundActionSetRegistration>
     for KubernetesRepository<ActionDiscoveryDocument, GenericKubernetesResourceManager<ActionDiscoveryDocument>>
 {
@@ -23,7 +23,7 @@ struct KubernetesActionRepositoryTest {
     api: Api<ActionDiscoveryDocument>,
     namespace: String,
     lookup: ReadOnlyRepositoryBackend<
-        SchemaBoundedTrieRepositoryData<RequestSegment>,
+        ActionReadOnlyRepository,
         ActionDiscoveryDocument,
         (String, Vec<RequestSegment>),
         EntityUid,
@@ -41,7 +41,7 @@ impl AsyncTestContext for KubernetesActionRepositoryTest {
             owner_mark,
             operation_timeout: operation_timeout.clone(),
         };
-        let lookup_trie = Arc::new(SchemaBoundedTrieRepositoryData::<RequestSegment>::new());
+        let lookup_trie = Arc::new(ActionReadOnlyRepository::new());
         let mut lookup = ReadOnlyRepositoryBackend::new(lookup_trie.clone(), lookup_trie.clone());
         lookup.start(config).await.unwrap();
         let repository = Arc::new(
@@ -174,11 +174,13 @@ async fn insert_schema_document(ctx: &KubernetesActionRepositoryTest, name: &str
     let registration = SchemaBoundActionSetRegistration {
         hostname: "www.example.com".to_string(),
         routes: vec![ActionRouteRegistration {
-            method: "GET".to_string(),
+            method: "GET".into(),
             route_template: route.to_string(),
             action_uid: action_uid.to_string(),
+            public: false,
         }],
         schema: "schema".to_string(),
+        id: name.to_string(),
     };
 
     ctx.repository
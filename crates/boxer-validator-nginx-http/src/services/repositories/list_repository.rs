@@ -0,0 +1,373 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::repositories::lookup_trie::SchemaBoundResource;
+use crate::services::route_template::normalize_hostname;
+use actix_web::http::StatusCode;
+use actix_web::{HttpResponse, ResponseError};
+use async_trait::async_trait;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::KubernetesRepository;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::schema_repository::SchemaDocument;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::soft_delete_resource::SoftDeleteResource;
+use boxer_core::services::backends::kubernetes::kubernetes_repository::try_from_resource::TryFromResource;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::GenericKubernetesResourceManager;
+use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::status::Status;
+use kube::ResourceExt;
+use kube::api::ListParams;
+use kube::core::ObjectList;
+use log::warn;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::sync::Arc;
+use utoipa::{IntoParams, ToSchema};
+
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 500;
+
+/// Filters and pagination for list endpoints.
+/// Registrations are selected by schema, `active` and `labels` on the Kubernetes API server.
+/// `hostname` is applied to each page after it is read, so a filtered page may hold fewer than `limit` items.
+#[derive(Deserialize, IntoParams, Default, Debug, Clone)]
+#[into_params(parameter_in = Query)]
+pub struct ListQuery {
+    /// Maximum number of items to read per page (default 100, at most 500).
+    pub limit: Option<u32>,
+    /// Continuation token returned by the previous page.
+    #[serde(rename = "continue")]
+    #[param(rename = "continue")]
+    pub continue_token: Option<String>,
+    /// Only return documents registered for this hostname.
+    pub hostname: Option<String>,
+    /// Kubernetes label selector, e.g. `team=photos,tier!=dev`.
+    pub labels: Option<String>,
+    /// Only return active (`true`) or soft-deleted (`false`) documents.
+    pub active: Option<bool>,
+}
+
+impl ListQuery {
+    fn page_size(&self) -> u32 {
+        self.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+    }
+
+    /// Returns true if a document with these properties passes the filters.
+    pub fn matches(&self, hostname: Option<&str>, active: bool) -> bool {
+        if self.active.is_some_and(|expected| expected != active) {
+            return false;
+        }
+        match (&self.hostname, hostname) {
+            (None, _) => true,
+            (Some(expected), Some(actual)) => normalize_hostname(expected) == normalize_hostname(actual),
+            (Some(_), None) => false,
+        }
+    }
+
+    /// Builds the Kubernetes list request for one page of the query.
+    pub fn list_params(&self) -> Result<ListParams, ListError> {
+        let mut params = ListParams::default().limit(self.page_size());
+        if let Some(labels) = self.labels.as_deref().filter(|labels| !labels.trim().is_empty()) {
+            validate_label_selector(labels).map_err(ListError::InvalidQuery)?;
+            params = params.labels(labels);
+        }
+        if let Some(token) = self.continue_token.as_deref() {
+            params = params.continue_token(token);
+        }
+        Ok(params)
+    }
+
+    /// Builds the list request for one page of registrations, selecting the schema and `active` on the server
+    /// through the selectable fields of the registration documents.
+    pub fn registration_list_params(&self, schema: Option<&str>) -> Result<ListParams, ListError> {
+        let params = self.list_params()?;
+        let fields: Vec<String> = schema
+            .map(|schema| format!("spec.schema={}", escape_field_value(schema)))
+            .into_iter()
+            .chain(self.active.map(|active| format!("spec.active={}", active)))
+            .collect();
+        if fields.is_empty() {
+            return Ok(params);
+        }
+        Ok(params.fields(&fields.join(",")))
+    }
+}
+
+/// Escapes the characters that separate field selector requirements.
+fn escape_field_value(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        if matches!(c, '\\' | ',' | '=' | '!') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// A page of list results.
+#[derive(ToSchema, Serialize, Debug)]
+pub struct ListPage<T> {
+    pub items: Vec<T>,
+    /// Pass as `continue` to fetch the next page; absent on the last page.
+    #[serde(rename = "continue", skip_serializing_if = "Option::is_none")]
+    #[schema(rename = "continue")]
+    pub continue_token: Option<String>,
+}
+
+impl<T> ListPage<T> {
+    pub fn map<U>(self, f: impl FnMut(T) -> U) -> ListPage<U> {
+        ListPage {
+            items: self.items.into_iter().map(f).collect(),
+            continue_token: self.continue_token,
+        }
+    }
+}
+
+/// A listed document together with its bookkeeping fields.
+#[derive(ToSchema, Serialize, Debug)]
+pub struct ListedItem<T> {
+    pub id: String,
+    pub active: bool,
+    pub labels: BTreeMap<String, String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registration: Option<T>,
+    /// Set instead of `registration` when the stored document cannot be read as a registration.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl<T> ListedItem<T> {
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> ListedItem<U> {
+        ListedItem {
+            id: self.id,
+            active: self.active,
+            labels: self.labels,
+            registration: self.registration.map(f),
+            error: self.error,
+        }
+    }
+}
+
+/// Errors returned by list endpoints.
+#[derive(Debug)]
+pub enum ListError {
+    /// The query is malformed and is rejected with `400 Bad Request`.
+    InvalidQuery(String),
+    Backend(anyhow::Error),
+}
+
+impl Display for ListError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListError::InvalidQuery(message) => write!(f, "Invalid list query: {}", message),
+            ListError::Backend(e) => write!(f, "Failed to list documents: {}", e),
+        }
+    }
+}
+
+impl ResponseError for ListError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            ListError::InvalidQuery(_) => StatusCode::BAD_REQUEST,
+            ListError::Backend(_) => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+
+    fn error_response(&self) -> HttpResponse {
+        HttpResponse::build(self.status_code()).body(self.to_string())
+    }
+}
+
+impl From<Status> for ListError {
+    fn from(status: Status) -> Self {
+        ListError::Backend(anyhow::anyhow!("{}", status))
+    }
+}
+
+/// Lists documents, optionally scoped to a schema.
+#[async_trait]
+pub trait ListRepository<Item>: Send + Sync {
+    async fn list(&self, schema: Option<String>, query: ListQuery) -> Result<ListPage<ListedItem<Item>>, ListError>;
+}
+
+/// Schema bodies are not included in list results; fetch them with `GET /schema/{id}`.
+pub type SchemaListRepository = dyn ListRepository<Value>;
+
+/// Resources that can be filtered by list endpoints.
+pub trait ListableResource: SchemaBoundResource + SoftDeleteResource {
+    /// The id the document was registered under.
+    fn id(&self) -> Option<String>;
+
+    fn hostname(&self) -> Option<String> {
+        None
+    }
+}
+
+fn continue_token<R>(objects: &ObjectList<R>) -> Option<String>
+where
+    R: Clone,
+{
+    objects.metadata.continue_.clone().filter(|token| !token.is_empty())
+}
+
+/// Reads a listed document as a registration. A document that cannot be read is still listed, with the
+/// conversion error in place of the registration, so one broken document does not fail the whole page.
+fn listed_item<R, V>(resource: R) -> ListedItem<V>
+where
+    R: kube::Resource + ListableResource,
+    V: TryFromResource<R, Error = Status>,
+{
+    // Documents written before ids were stored only carry their object name
+    let id = resource.id().unwrap_or_else(|| resource.name_any());
+    let active = !resource.is_deleted();
+    let labels = resource.labels().clone();
+    let (registration, error) = match V::try_from_resource(Arc::new(resource)) {
+        Ok(registration) => (Some(registration), None),
+        Err(e) => {
+            warn!("Listed document {} cannot be read as a registration: {}", id, e);
+            (None, Some(e.to_string()))
+        }
+    };
+    ListedItem {
+        id,
+        active,
+        labels,
+        registration,
+        error,
+    }
+}
+
+#[async_trait]
+impl<R, V> ListRepository<V> for KubernetesRepository<R, GenericKubernetesResourceManager<R>>
+where
+    R: kube::Resource + ListableResource + Clone + DeserializeOwned + Debug + Send + Sync + 'static,
+    V: TryFromResource<R, Error = Status> + Send + 'static,
+{
+    async fn list(&self, schema: Option<String>, query: ListQuery) -> Result<ListPage<ListedItem<V>>, ListError> {
+        let params = query.registration_list_params(schema.as_deref())?;
+        let objects = KubernetesRepository::list(self, &params).await?;
+        let continue_token = continue_token(&objects);
+
+        let items = objects
+            .items
+            .into_iter()
+            .filter(|resource| query.matches(resource.hostname().as_deref(), !resource.is_deleted()))
+            .map(listed_item)
+            .collect();
+        Ok(ListPage { items, continue_token })
+    }
+}
+
+#[async_trait]
+impl ListRepository<Value> for KubernetesRepository<SchemaDocument, GenericKubernetesResourceManager<SchemaDocument>> {
+    async fn list(&self, _schema: Option<String>, query: ListQuery) -> Result<ListPage<ListedItem<Value>>, ListError> {
+        let objects = KubernetesRepository::list(self, &query.list_params()?).await?;
+        let continue_token = continue_token(&objects);
+
+        let items: Vec<ListedItem<Value>> = objects
+            .items
+            .into_iter()
+            .map(|document| ListedItem {
+                id: document.name_any(),
+                active: !document.is_deleted(),
+                labels: document.labels().clone(),
+                registration: None,
+                error: None,
+            })
+            .filter(|item| query.active.is_none_or(|active| active == item.active))
+            .collect();
+        Ok(ListPage { items, continue_token })
+    }
+}
+
+/// Checks the syntax of a Kubernetes label selector, so a malformed one is reported as a bad request
+/// instead of failing the list call.
+fn validate_label_selector(selector: &str) -> Result<(), String> {
+    let mut depth = 0;
+    let mut start = 0;
+    let mut requirements = Vec::new();
+    for (i, c) in selector.char_indices() {
+        match c {
+            '(' => depth += 1,
+            ')' if depth == 0 => return Err(format!("unbalanced parentheses in '{}'", selector)),
+            ')' => depth -= 1,
+            ',' if depth == 0 => {
+                requirements.push(&selector[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if depth != 0 {
+        return Err(format!("unbalanced parentheses in '{}'", selector));
+    }
+    requirements.push(&selector[start..]);
+    requirements.into_iter().try_for_each(validate_requirement)
+}
+
+fn validate_requirement(requirement: &str) -> Result<(), String> {
+    let requirement = requirement.trim();
+    if let Some(key) = requirement.strip_prefix('!') {
+        return validate_label_key(key.trim());
+    }
+    for operator in [" notin ", " in "] {
+        if let Some((key, values)) = requirement.split_once(operator) {
+            validate_label_key(key.trim())?;
+            let values = values
+                .trim()
+                .strip_prefix('(')
+                .and_then(|values| values.strip_suffix(')'))
+                .ok_or_else(|| format!("expected a parenthesized value list in '{}'", requirement))?;
+            return values
+                .split(',')
+                .try_for_each(|value| validate_label_value(value.trim()));
+        }
+    }
+    for operator in ["!=", "==", "="] {
+        if let Some((key, value)) = requirement.split_once(operator) {
+            validate_label_key(key.trim())?;
+            return validate_label_value(value.trim());
+        }
+    }
+    validate_label_key(requirement)
+}
+
+fn validate_label_key(key: &str) -> Result<(), String> {
+    let name = match key.split_once('/') {
+        Some((prefix, name)) => {
+            let valid_prefix = !prefix.is_empty()
+                && prefix.len() <= 253
+                && prefix
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '.');
+            if !valid_prefix {
+                return Err(format!("invalid label key prefix '{}'", prefix));
+            }
+            name
+        }
+        None => key,
+    };
+    if name.is_empty() || !is_label_name(name) {
+        return Err(format!("invalid label key '{}'", key));
+    }
+    Ok(())
+}
+
+fn validate_label_value(value: &str) -> Result<(), String> {
+    if value.is_empty() || is_label_name(value) {
+        Ok(())
+    } else {
+        Err(format!("invalid label value '{}'", value))
+    }
+}
+
+/// Label names and values: at most 63 characters, alphanumeric at both ends, with `-`, `_` and `.` in between.
+fn is_label_name(value: &str) -> bool {
+    value.len() <= 63
+        && value.starts_with(|c: char| c.is_ascii_alphanumeric())
+        && value.ends_with(|c: char| c.is_ascii_alphanumeric())
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.')
+}
@@ -0,0 +1,152 @@
+use super::*;
+use crate::http::controllers::v1::action_set::models::SchemaBoundActionSetRegistration;
+use crate::services::repositories::action_repository::action_discovery_document::{
+    ActionDiscoveryDocument, ActionDiscoveryDocumentSpec,
+};
+use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
+use crate::services::repositories::resource_repository::resource_discovery_document::ResourceDiscoveryDocument;
+use k8s_openapi::apiextensions_apiserver::pkg::apis::apiextensions::v1::CustomResourceDefinition;
+use kube::CustomResourceExt;
+use test_case::test_case;
+
+#[test_case(None, None => (Some(100), None); "default page size")]
+#[test_case(Some(0), None => (Some(1), None); "page size is at least one")]
+#[test_case(Some(1000), None => (Some(500), None); "page size is capped")]
+#[test_case(Some(2), Some("token") => (Some(2), Some("token".to_string())); "continue token is passed through")]
+fn test_list_params_pagination(limit: Option<u32>, continue_token: Option<&str>) -> (Option<u32>, Option<String>) {
+    let query = ListQuery {
+        limit,
+        continue_token: continue_token.map(str::to_string),
+        ..Default::default()
+    };
+    let params = query.list_params().unwrap();
+    (params.limit, params.continue_token)
+}
+
+#[test_case(None, None, Some("www.example.com"), true => true; "no filters")]
+#[test_case(Some(true), None, Some("www.example.com"), false => false; "inactive filtered out")]
+#[test_case(Some(false), None, Some("www.example.com"), false => true; "only inactive")]
+#[test_case(None, Some("WWW.example.com"), Some("www.example.com"), true => true; "hostname is case-insensitive")]
+#[test_case(None, Some("api.example.com"), Some("www.example.com"), true => false; "other hostname")]
+#[test_case(None, Some("www.example.com"), None, true => false; "document without hostname")]
+fn test_matches(
+    active: Option<bool>,
+    hostname: Option<&str>,
+    document_hostname: Option<&str>,
+    document_active: bool,
+) -> bool {
+    let query = ListQuery {
+        active,
+        hostname: hostname.map(str::to_string),
+        ..Default::default()
+    };
+    query.matches(document_hostname, document_active)
+}
+
+#[test_case("team=photos" => true; "equality")]
+#[test_case("team==photos,tier!=dev" => true; "several requirements")]
+#[test_case("app.kubernetes.io/name=boxer" => true; "prefixed key")]
+#[test_case("team in (photos, videos),!legacy" => true; "set and absence")]
+#[test_case("tier notin (dev)" => true; "notin")]
+#[test_case("team" => true; "existence")]
+#[test_case("team=" => true; "empty value")]
+#[test_case("team=photos," => false; "trailing comma")]
+#[test_case("=photos" => false; "missing key")]
+#[test_case("team=pho tos" => false; "space in value")]
+#[test_case("team in photos" => false; "set without parentheses")]
+#[test_case("team in (photos" => false; "unbalanced parentheses")]
+#[test_case("Example.com/team=photos" => false; "uppercase prefix")]
+fn test_validate_label_selector(selector: &str) -> bool {
+    validate_label_selector(selector).is_ok()
+}
+
+#[test_case(Some("team=photos") => Some("team=photos".to_string()); "selector is passed through")]
+#[test_case(Some(" ") => None; "blank selector is ignored")]
+#[test_case(None => None; "no selector")]
+fn test_list_params_labels(labels: Option<&str>) -> Option<String> {
+    let query = ListQuery {
+        labels: labels.map(str::to_string),
+        ..Default::default()
+    };
+    query.list_params().unwrap().label_selector
+}
+
+#[test]
+fn test_invalid_label_selector_is_a_bad_request() {
+    let query = ListQuery {
+        labels: Some("team in photos".to_string()),
+        ..Default::default()
+    };
+    let error = query.list_params().unwrap_err();
+
+    assert_eq!(error.status_code(), StatusCode::BAD_REQUEST);
+}
+
+#[test_case(None, None => None; "no selection")]
+#[test_case(Some("photos"), None => Some("spec.schema=photos".to_string()); "schema")]
+#[test_case(None, Some(false) => Some("spec.active=false".to_string()); "active")]
+#[test_case(Some("photos"), Some(true) => Some("spec.schema=photos,spec.active=true".to_string()); "schema and active")]
+#[test_case(Some("a,b=c"), None => Some("spec.schema=a\\,b\\=c".to_string()); "separators are escaped")]
+fn test_registration_list_params_fields(schema: Option<&str>, active: Option<bool>) -> Option<String> {
+    let query = ListQuery {
+        active,
+        ..Default::default()
+    };
+    query.registration_list_params(schema).unwrap().field_selector
+}
+
+struct Unreadable;
+
+impl TryFromResource<ActionDiscoveryDocument> for Unreadable {
+    type Error = Status;
+
+    fn try_from_resource(_resource: Arc<ActionDiscoveryDocument>) -> Result<Self, Self::Error> {
+        Err(Status::ConversionError(anyhow::anyhow!("unreadable")))
+    }
+}
+
+fn action_document() -> ActionDiscoveryDocument {
+    ActionDiscoveryDocument::new(
+        "document",
+        ActionDiscoveryDocumentSpec {
+            id: Some("photos".to_string()),
+            active: true,
+            hostname: "www.example.com".to_string(),
+            routes: vec![],
+            schema: "schema".to_string(),
+        },
+    )
+}
+
+#[test]
+fn test_listed_item_reads_the_registration() {
+    let item: ListedItem<SchemaBoundActionSetRegistration> = listed_item(action_document());
+
+    assert_eq!(item.id, "photos");
+    assert!(item.error.is_none());
+    assert_eq!(item.registration.unwrap().hostname, "www.example.com");
+}
+
+#[test]
+fn test_unreadable_document_is_listed_with_its_error() {
+    let item: ListedItem<Unreadable> = listed_item(action_document());
+
+    assert_eq!(item.id, "photos");
+    assert!(item.active);
+    assert!(item.registration.is_none());
+    assert!(item.error.is_some());
+}
+
+#[test_case(ActionDiscoveryDocument::crd(); "action discovery documents")]
+#[test_case(ResourceDiscoveryDocument::crd(); "resource discovery documents")]
+#[test_case(PolicyDocument::crd(); "policy documents")]
+fn test_registration_fields_are_selectable(crd: CustomResourceDefinition) {
+    let fields: Vec<String> = crd.spec.versions[0]
+        .selectable_fields
+        .iter()
+        .flatten()
+        .map(|field| field.json_path.clone())
+        .collect();
+
+    assert_eq!(fields, vec![".spec.schema", ".spec.active"]);
+}
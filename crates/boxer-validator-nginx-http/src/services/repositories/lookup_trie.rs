@@ -2,6 +2,7 @@ use log::{info, warn};
 
 use crate::services::prefix_tree::MutablePrefixTree;
 use crate::services::prefix_tree::PrefixTree;
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::naive_tree::NaiveTrie;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
 use crate::services::prefix_tree::trie_bucket::request_segment_bucket::PrioritizedBucket;
@@ -13,11 +14,13 @@ use cedar_policy::EntityUid;
 use futures::StreamExt;
 use kube::Resource;
 use kube::runtime::watcher::Error;
+use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
 use tokio::sync::RwLock;
 
 pub mod backend;
+pub mod route_table;
 pub mod schema_bound_trie_repository;
 
 pub struct TrieData<Key, Value>
@@ -28,7 +31,7 @@ where
     trie: NaiveTrie<PrioritizedBucket<Key, Value>>,
 }
 
-struct TrieRepositoryData<Key, Value>
+pub(crate) struct TrieRepositoryData<Key, Value>
 where
     Key: Debug + Send + Sync,
     Value: Send + Sync,
@@ -60,6 +63,12 @@ pub trait SchemaBoundResource {
     fn schema(&self) -> String;
 }
 
+/// Lists the entity UIDs registered for a schema, grouped by the document that registered them.
+#[async_trait]
+pub trait RegisteredEntities: Send + Sync {
+    async fn registered_entities(&self, schema: &str) -> HashMap<String, Vec<EntityUid>>;
+}
+
 #[async_trait]
 impl<Key> ReadOnlyRepository<Vec<Key>, EntityUid> for TrieRepositoryData<Key, EntityUid>
 where
@@ -78,6 +87,21 @@ where
     }
 }
 
+impl<Key> TrieRepositoryData<Key, EntityUid>
+where
+    Key: Hash + ParametrizedMatcher + Sync + Send + Debug + Clone + Eq,
+{
+    /// Returns every entry of the trie with the branch it is stored under.
+    pub async fn entries(&self) -> Vec<(Vec<TrieEdge<Key>>, EntityUid)> {
+        self.rw_lock.read().await.trie.entries().await
+    }
+
+    /// Looks up a key and returns the matched branch along with the entity UID.
+    pub async fn resolve(&self, key: Vec<Key>) -> Option<(Vec<TrieEdge<Key>>, EntityUid)> {
+        self.rw_lock.read().await.trie.resolve(&key).await
+    }
+}
+
 #[async_trait]
 impl<Key> UpsertRepository<Vec<Key>, EntityUid> for TrieRepositoryData<Key, EntityUid>
 where
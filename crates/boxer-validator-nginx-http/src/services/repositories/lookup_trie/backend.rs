@@ -1,3 +1,7 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::readiness::WatcherStatus;
 use async_trait::async_trait;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::KubernetesResourceManagerConfig;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::{
@@ -7,6 +11,7 @@ use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
 use boxer_core::services::service_provider::ServiceProvider;
 use futures::stream::StreamExt;
 use k8s_openapi::NamespaceResourceScope;
+use kube::runtime::watcher::Event;
 use kube::runtime::{WatchStreamExt, reflector, watcher};
 use kube::{Api, Client, Resource};
 use log::debug;
@@ -28,6 +33,7 @@ where
     handle: Option<JoinHandle<()>>,
     update_handler: Arc<H>,
     repository: Arc<AssociatedRepository<K, V>>,
+    status: Option<Arc<WatcherStatus>>,
     _marker: std::marker::PhantomData<S>,
 }
 
@@ -43,8 +49,19 @@ where
             update_handler,
             _marker: std::marker::PhantomData,
             repository,
+            status: None,
         }
     }
+
+    /// Reports the health of the watch stream to readiness.
+    pub fn with_status(mut self, status: Arc<WatcherStatus>) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn update_handler(&self) -> Arc<H> {
+        self.update_handler.clone()
+    }
 }
 
 #[async_trait]
@@ -62,17 +79,20 @@ where
         let (reader, writer) = reflector::store();
 
         let handler = self.update_handler.clone();
-        let reflector = reflector(writer, stream)
-            .default_backoff()
-            .touched_objects()
-            .for_each(move |r| {
-                let update_handler = handler.clone();
-                async move {
-                    update_handler.handle_update(r).await;
-                }
-            });
+        let status = self.status.clone();
+        let reflector = reflector(writer, stream).default_backoff().for_each(move |event| {
+            let update_handler = handler.clone();
+            let status = status.clone();
+            async move {
+                apply_event(update_handler.as_ref(), status.as_deref(), event).await;
+            }
+        });
 
-        let handle = tokio::spawn(reflector);
+        let status = self.status.clone();
+        let handle = tokio::spawn(async move {
+            let _running = status.as_ref().map(|status| status.running());
+            reflector.await
+        });
         reader.wait_until_ready().await?;
         self.handle = Some(handle);
         Ok(())
@@ -87,6 +107,33 @@ where
     }
 }
 
+/// Hands a watch event to the update handler and reports it to readiness once it has been applied,
+/// so the watcher is only marked as synced after the initial list is in the in-memory structures.
+async fn apply_event<H, S>(handler: &H, status: Option<&WatcherStatus>, event: Result<Event<S>, watcher::Error>)
+where
+    H: ResourceUpdateHandler<S>,
+{
+    match event {
+        Ok(Event::Apply(object) | Event::InitApply(object) | Event::Delete(object)) => {
+            handler.handle_update(Ok(object)).await;
+            if let Some(status) = status {
+                status.applied(false);
+            }
+        }
+        Ok(event) => {
+            if let Some(status) = status {
+                status.applied(matches!(event, Event::InitDone));
+            }
+        }
+        Err(err) => {
+            if let Some(status) = status {
+                status.failed(&err);
+            }
+            handler.handle_update(Err(err)).await;
+        }
+    }
+}
+
 impl<H, S, K, V> ServiceProvider<Arc<AssociatedRepository<K, V>>> for ReadOnlyRepositoryBackend<H, S, K, V>
 where
     H: ResourceUpdateHandler<S> + Send + Sync + 'static,
@@ -0,0 +1,62 @@
+use super::*;
+use crate::services::readiness::Readiness;
+use crate::services::repositories::action_repository::action_discovery_document::{
+    ActionDiscoveryDocument, ActionDiscoveryDocumentSpec, ActionRoute, ActionRouteMethod,
+};
+use crate::services::repositories::action_repository::public_routes::PublicRouteRepository;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use std::str::FromStr;
+use std::time::Duration;
+
+fn document() -> ActionDiscoveryDocument {
+    ActionDiscoveryDocument::new(
+        "photo-app-actions",
+        ActionDiscoveryDocumentSpec {
+            id: Some("actions".to_string()),
+            active: true,
+            hostname: "www.example.com".to_string(),
+            routes: vec![ActionRoute {
+                method: ActionRouteMethod::from_str("GET").unwrap(),
+                route_template: "health".to_string(),
+                action_uid: "PhotoApp::Action::\"viewPhoto\"".to_string(),
+                public: true,
+            }],
+            schema: "photo-app".to_string(),
+        },
+    )
+}
+
+fn request() -> RequestContext {
+    RequestContext::new("https://www.example.com/health".to_string(), "GET".to_string())
+}
+
+#[tokio::test]
+async fn test_watcher_is_synced_once_initial_list_is_applied() {
+    let readiness = Readiness::new(Duration::from_secs(60));
+    let status = readiness.register("public_routes");
+    let handler = PublicRouteRepository::new();
+
+    apply_event(&handler, Some(&status), Ok(Event::Init)).await;
+    apply_event(&handler, Some(&status), Ok(Event::InitApply(document()))).await;
+    assert!(!readiness.report().ready);
+
+    apply_event(&handler, Some(&status), Ok(Event::InitDone)).await;
+
+    assert!(readiness.report().ready);
+    assert!(handler.is_public(request()).await);
+}
+
+#[tokio::test]
+async fn test_watch_error_is_reported_and_cleared_by_next_event() {
+    let readiness = Readiness::new(Duration::ZERO);
+    let status = readiness.register("public_routes");
+    let handler = PublicRouteRepository::new();
+    apply_event(&handler, Some(&status), Ok(Event::InitDone)).await;
+
+    apply_event(&handler, Some(&status), Err(watcher::Error::NoResourceVersion)).await;
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    assert!(!readiness.report().ready);
+
+    apply_event(&handler, Some(&status), Ok(Event::Apply(document()))).await;
+    assert!(readiness.report().ready);
+}
@@ -0,0 +1,165 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::prefix_tree::TrieEdge;
+use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
+use crate::services::repositories::action_repository::action_segment::ActionSegment;
+use crate::services::route_template::{NormalizeSegment, normalize_key};
+use anyhow::anyhow;
+use async_trait::async_trait;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+/// Human-readable form of a trie branch.
+#[derive(ToSchema, Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct RouteDescription {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+    /// HTTP method, or `*` for a method wildcard.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub verb: Option<String>,
+    /// Path pattern where `{}` marks a parameter segment.
+    pub path: String,
+}
+
+/// A single entry of a lookup trie.
+#[derive(ToSchema, Serialize, Debug, PartialEq)]
+pub struct RouteTableEntry {
+    #[serde(flatten)]
+    pub route: RouteDescription,
+    pub uid: String,
+    /// Names of the documents that registered this route.
+    pub documents: Vec<String>,
+}
+
+/// The result of running a lookup against a trie.
+#[derive(ToSchema, Serialize, Debug)]
+pub struct ResolvedRoute {
+    /// The normalized lookup key built from the request.
+    pub request: RouteDescription,
+    /// The branch that matched, if any.
+    pub matched: Option<RouteTableEntry>,
+}
+
+/// Renders trie branches for inspection.
+pub trait DescribeRoute: Sized {
+    fn describe(edges: &[TrieEdge<Self>]) -> RouteDescription;
+}
+
+/// Builds the lookup key of a request for a trie keyed by `Self`.
+pub trait LookupKey: Sized {
+    fn lookup_key(request: RequestContext) -> Result<Vec<Self>, anyhow::Error>;
+}
+
+impl LookupKey for PathSegment {
+    fn lookup_key(request: RequestContext) -> Result<Vec<Self>, anyhow::Error> {
+        request.try_into().map_err(|e| anyhow!("Invalid request: {:?}", e))
+    }
+}
+
+impl LookupKey for ActionSegment {
+    fn lookup_key(request: RequestContext) -> Result<Vec<Self>, anyhow::Error> {
+        let key: Vec<RequestSegment> = request.try_into().map_err(|e| anyhow!("Invalid request: {:?}", e))?;
+        Ok(key.into_iter().map(ActionSegment::from).collect())
+    }
+}
+
+/// Describes the normalized lookup key of a request without resolving it against a trie.
+pub fn describe_request<Key>(request: RequestContext) -> Result<RouteDescription, anyhow::Error>
+where
+    Key: LookupKey + DescribeRoute + NormalizeSegment,
+{
+    let edges: Vec<TrieEdge<Key>> = normalize_key(Key::lookup_key(request)?)
+        .into_iter()
+        .map(TrieEdge::Exact)
+        .collect();
+    Ok(Key::describe(&edges))
+}
+
+fn key_of<Key>(edge: &TrieEdge<Key>) -> &Key {
+    match edge {
+        TrieEdge::Exact(key) | TrieEdge::Parameter(key) => key,
+    }
+}
+
+fn push_path_segment(path: &mut String, segment: &PathSegment) {
+    path.push('/');
+    match segment {
+        PathSegment::Static(value) => path.push_str(value),
+        PathSegment::Parameter => path.push_str("{}"),
+    }
+}
+
+fn finish_path(mut path: String) -> String {
+    if path.is_empty() {
+        path.push('/');
+    }
+    path
+}
+
+impl DescribeRoute for PathSegment {
+    fn describe(edges: &[TrieEdge<Self>]) -> RouteDescription {
+        let mut path = String::new();
+        for edge in edges {
+            push_path_segment(&mut path, key_of(edge));
+        }
+        RouteDescription {
+            hostname: None,
+            verb: None,
+            path: finish_path(path),
+        }
+    }
+}
+
+impl DescribeRoute for ActionSegment {
+    fn describe(edges: &[TrieEdge<Self>]) -> RouteDescription {
+        let mut description = RouteDescription::default();
+        let mut path = String::new();
+        for edge in edges {
+            match key_of(edge) {
+                ActionSegment::Hostname(hostname) => description.hostname = Some(hostname.clone()),
+                ActionSegment::Verb(method) => description.verb = Some(method.to_string()),
+                ActionSegment::AnyVerb => description.verb = Some("*".to_string()),
+                ActionSegment::Path(segment) => push_path_segment(&mut path, segment),
+            }
+        }
+        description.path = finish_path(path);
+        description
+    }
+}
+
+/// Returns true if a trie branch is the one a registered key was inserted under.
+pub fn branch_matches_key<Key>(edges: &[TrieEdge<Key>], key: &[Key]) -> bool
+where
+    Key: ParametrizedMatcher + PartialEq,
+{
+    edges.len() == key.len()
+        && edges.iter().zip(key).all(|(edge, segment)| match edge {
+            TrieEdge::Exact(exact) => !segment.is_parameter() && exact == segment,
+            TrieEdge::Parameter(parameter) => parameter == segment,
+        })
+}
+
+/// Read-only view of a schema-bound lookup trie for inspection.
+#[async_trait]
+pub trait RouteTable: Send + Sync {
+    async fn schemas(&self) -> Vec<String>;
+
+    async fn routes(&self, schema: &str) -> Vec<RouteTableEntry>;
+
+    async fn resolve(&self, schema: &str, request: RequestContext) -> Result<ResolvedRoute, anyhow::Error>;
+
+    /// Number of routes per schema. Does not wait on the trie locks, so it can be read from metric callbacks.
+    fn route_counts(&self) -> HashMap<String, usize>;
+}
+
+/// The action and resource lookup tries.
+pub struct RouteTables {
+    pub actions: Arc<dyn RouteTable>,
+    pub resources: Arc<dyn RouteTable>,
+}
@@ -0,0 +1,73 @@
+use super::*;
+use boxer_core::services::validation_service::http_method::HTTPMethod;
+use pretty_assertions::assert_eq;
+
+fn exact_path(segment: &str) -> TrieEdge<ActionSegment> {
+    TrieEdge::Exact(ActionSegment::Path(PathSegment::Static(segment.to_string())))
+}
+
+#[test]
+fn test_describe_action_branch() {
+    let edges = vec![
+        TrieEdge::Exact(ActionSegment::Hostname("www.example.com".to_string())),
+        TrieEdge::Exact(ActionSegment::Verb(HTTPMethod::Get)),
+        exact_path("api"),
+        TrieEdge::Parameter(ActionSegment::Path(PathSegment::Parameter)),
+    ];
+
+    assert_eq!(
+        ActionSegment::describe(&edges),
+        RouteDescription {
+            hostname: Some("www.example.com".to_string()),
+            verb: Some(HTTPMethod::Get.to_string()),
+            path: "/api/{}".to_string(),
+        }
+    );
+}
+
+#[test]
+fn test_describe_method_wildcard() {
+    let edges = vec![
+        TrieEdge::Exact(ActionSegment::Hostname("www.example.com".to_string())),
+        TrieEdge::Parameter(ActionSegment::AnyVerb),
+        exact_path("api"),
+    ];
+
+    assert_eq!(ActionSegment::describe(&edges).verb, Some("*".to_string()));
+}
+
+#[test]
+fn test_describe_resource_branch() {
+    let edges = vec![
+        TrieEdge::Exact(PathSegment::Static("photos".to_string())),
+        TrieEdge::Parameter(PathSegment::Parameter),
+    ];
+
+    assert_eq!(PathSegment::describe(&edges).path, "/photos/{}");
+    assert_eq!(PathSegment::describe(&[]).path, "/");
+}
+
+#[test]
+fn test_branch_matches_key() {
+    let key = vec![
+        ActionSegment::Hostname("www.example.com".to_string()),
+        ActionSegment::AnyVerb,
+        ActionSegment::Path(PathSegment::Static("api".to_string())),
+    ];
+    let branch = vec![
+        TrieEdge::Exact(ActionSegment::Hostname("www.example.com".to_string())),
+        TrieEdge::Parameter(ActionSegment::AnyVerb),
+        exact_path("api"),
+    ];
+
+    assert!(branch_matches_key(&branch, &key));
+    assert!(!branch_matches_key(&branch[..2], &key));
+    assert!(!branch_matches_key(
+        &[
+            branch[0].clone(),
+            TrieEdge::Exact(ActionSegment::Verb(HTTPMethod::Get)),
+            branch[2].clone()
+        ],
+        &key
+    ));
+}
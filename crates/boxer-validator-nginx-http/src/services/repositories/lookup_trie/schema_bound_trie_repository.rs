@@ -1,17 +1,27 @@
-use crate::services::repositories::lookup_trie::{EntityCollectionResource, SchemaBoundResource, TrieRepositoryData};
+use crate::services::repositories::lookup_trie::{
+    EntityCollectionResource, RegisteredEntities, SchemaBoundResource, TrieRepositoryData,
+};
 use anyhow::anyhow;
 
+use crate::services::prefix_tree::TrieEdge;
 use crate::services::prefix_tree::parametrized_matcher::ParametrizedMatcher;
+use crate::services::repositories::lookup_trie::route_table::{
+    DescribeRoute, LookupKey, ResolvedRoute, RouteTable, RouteTableEntry, branch_matches_key,
+};
+use crate::services::route_template::{NormalizeSegment, normalize_key};
 use async_trait::async_trait;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::ResourceUpdateHandler;
 use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use boxer_core::services::validation_service::request_context::RequestContext;
 use cedar_policy::EntityUid;
+use futures::StreamExt;
 use kube::Resource;
 use kube::runtime::watcher;
 use log::{info, warn};
 use std::collections::HashMap;
 use std::fmt::Debug;
 use std::hash::Hash;
+use std::pin::pin;
 use tokio::sync::RwLock;
 
 pub struct SchemaBoundedTrieRepositoryData<Key>
@@ -19,8 +29,15 @@ where
     Key: Ord + Debug + Send + Sync,
 {
     buckets: RwLock<HashMap<String, TrieRepositoryData<Key, EntityUid>>>,
+    documents: RwLock<HashMap<String, DocumentIndex<Key>>>,
+    route_counts: std::sync::RwLock<HashMap<String, usize>>,
 }
 
+type DocumentEntry<Key> = (Vec<Key>, EntityUid);
+
+/// Registered keys per document name within a schema.
+type DocumentIndex<Key> = HashMap<String, Vec<DocumentEntry<Key>>>;
+
 impl<Key> SchemaBoundedTrieRepositoryData<Key>
 where
     Key: Ord + Debug + Send + Sync,
@@ -28,6 +45,8 @@ where
     pub fn new() -> Self {
         SchemaBoundedTrieRepositoryData {
             buckets: RwLock::new(HashMap::new()),
+            documents: RwLock::new(HashMap::new()),
+            route_counts: std::sync::RwLock::new(HashMap::new()),
         }
     }
 }
@@ -35,12 +54,13 @@ where
 #[async_trait]
 impl<Key> ReadOnlyRepository<(String, Vec<Key>), EntityUid> for SchemaBoundedTrieRepositoryData<Key>
 where
-    Key: Ord + Send + Sync + Debug + Hash + 'static + ParametrizedMatcher + Clone,
+    Key: Ord + Send + Sync + Debug + Hash + 'static + ParametrizedMatcher + NormalizeSegment + Clone,
 {
     type ReadError = anyhow::Error;
 
     async fn get(&self, key: (String, Vec<Key>)) -> Result<EntityUid, Self::ReadError> {
         let (schema, segments) = key;
+        let segments = normalize_key(segments);
         let guard = self.buckets.read().await;
         let bucket = guard.get(&schema);
         match bucket {
@@ -54,15 +74,19 @@ where
 impl<R, Key> ResourceUpdateHandler<R> for SchemaBoundedTrieRepositoryData<Key>
 where
     Key: Ord + Send + Sync + Debug + Hash + Clone + 'static + ParametrizedMatcher,
-    R: SchemaBoundResource + Resource + EntityCollectionResource<Key> + Send + Sync + Debug + 'static,
+    R: SchemaBoundResource + Resource + EntityCollectionResource<Key> + Clone + Send + Sync + Debug + 'static,
 {
     async fn handle_update(&self, result: Result<R, watcher::Error>) -> () {
         match &result {
             Ok(document) => {
+                self.index_document(document).await;
                 let mut guard = self.buckets.write().await;
                 info!("Handling update for schema: {}", document.schema());
-                let bucket = guard.entry(document.schema()).or_insert_with(TrieRepositoryData::new);
+                let schema = document.schema();
+                let bucket = guard.entry(schema.clone()).or_insert_with(TrieRepositoryData::new);
                 bucket.handle_update(result).await;
+                let count = bucket.entries().await.len();
+                self.route_counts.write().unwrap().insert(schema, count);
             }
             Err(e) => {
                 warn!("Error handling update: {:?}", e);
@@ -70,3 +94,147 @@ where
         }
     }
 }
+
+impl<Key> SchemaBoundedTrieRepositoryData<Key>
+where
+    Key: Ord + Debug + Send + Sync,
+{
+    /// Remembers which routes an active document registers, so that schema updates can be
+    /// checked against them and trie entries can be traced back to their source.
+    async fn index_document<R>(&self, document: &R)
+    where
+        R: SchemaBoundResource + Resource + EntityCollectionResource<Key> + Clone,
+    {
+        let Some(name) = document.meta().name.clone() else {
+            return;
+        };
+        let mut entries: Vec<DocumentEntry<Key>> = Vec::new();
+        let mut active = true;
+        let mut stream = pin!(document.clone().stream());
+        while let Some(entry) = stream.next().await {
+            if let Ok((key, uid, is_active)) = entry {
+                active &= is_active;
+                entries.push((key, uid));
+            }
+        }
+
+        let mut guard = self.documents.write().await;
+        let documents = guard.entry(document.schema()).or_default();
+        if active && !entries.is_empty() {
+            documents.insert(name, entries);
+        } else {
+            documents.remove(&name);
+        }
+    }
+}
+
+#[async_trait]
+impl<Key> RegisteredEntities for SchemaBoundedTrieRepositoryData<Key>
+where
+    Key: Ord + Debug + Send + Sync,
+{
+    async fn registered_entities(&self, schema: &str) -> HashMap<String, Vec<EntityUid>> {
+        let guard = self.documents.read().await;
+        let Some(documents) = guard.get(schema) else {
+            return HashMap::new();
+        };
+        documents
+            .iter()
+            .map(|(name, entries)| {
+                let mut uids: Vec<EntityUid> = Vec::new();
+                for (_, uid) in entries {
+                    if !uids.contains(uid) {
+                        uids.push(uid.clone());
+                    }
+                }
+                (name.clone(), uids)
+            })
+            .collect()
+    }
+}
+
+impl<Key> SchemaBoundedTrieRepositoryData<Key>
+where
+    Key: Ord + Debug + Send + Sync + ParametrizedMatcher + DescribeRoute,
+{
+    async fn describe_entry(&self, schema: &str, edges: &[TrieEdge<Key>], uid: EntityUid) -> RouteTableEntry {
+        let mut documents: Vec<String> = self
+            .documents
+            .read()
+            .await
+            .get(schema)
+            .into_iter()
+            .flatten()
+            .filter(|(_, entries)| {
+                entries
+                    .iter()
+                    .any(|(key, registered)| *registered == uid && branch_matches_key(edges, key))
+            })
+            .map(|(name, _)| name.clone())
+            .collect();
+        documents.sort();
+        RouteTableEntry {
+            route: Key::describe(edges),
+            uid: uid.to_string(),
+            documents,
+        }
+    }
+}
+
+#[async_trait]
+impl<Key> RouteTable for SchemaBoundedTrieRepositoryData<Key>
+where
+    Key: Ord
+        + Send
+        + Sync
+        + Debug
+        + Hash
+        + Clone
+        + 'static
+        + ParametrizedMatcher
+        + NormalizeSegment
+        + DescribeRoute
+        + LookupKey,
+{
+    async fn schemas(&self) -> Vec<String> {
+        let mut schemas: Vec<String> = self.buckets.read().await.keys().cloned().collect();
+        schemas.sort();
+        schemas
+    }
+
+    async fn routes(&self, schema: &str) -> Vec<RouteTableEntry> {
+        let entries = match self.buckets.read().await.get(schema) {
+            Some(trie_data) => trie_data.entries().await,
+            None => return Vec::new(),
+        };
+        let mut routes = Vec::with_capacity(entries.len());
+        for (edges, uid) in entries {
+            routes.push(self.describe_entry(schema, &edges, uid).await);
+        }
+        routes.sort_by(|a, b| {
+            (&a.route.hostname, &a.route.path, &a.route.verb).cmp(&(&b.route.hostname, &b.route.path, &b.route.verb))
+        });
+        routes
+    }
+
+    async fn resolve(&self, schema: &str, request: RequestContext) -> Result<ResolvedRoute, anyhow::Error> {
+        let key = normalize_key(Key::lookup_key(request)?);
+        let request_edges: Vec<TrieEdge<Key>> = key.iter().cloned().map(TrieEdge::Exact).collect();
+        let resolved = match self.buckets.read().await.get(schema) {
+            Some(trie_data) => trie_data.resolve(key).await,
+            None => None,
+        };
+        let matched = match resolved {
+            Some((edges, uid)) => Some(self.describe_entry(schema, &edges, uid).await),
+            None => None,
+        };
+        Ok(ResolvedRoute {
+            request: Key::describe(&request_edges),
+            matched,
+        })
+    }
+
+    fn route_counts(&self) -> HashMap<String, usize> {
+        self.route_counts.read().unwrap().clone()
+    }
+}
@@ -1,4 +1,5 @@
 pub mod action_repository;
+pub mod list_repository;
 pub mod lookup_trie;
 pub mod policy_repository;
 pub mod resource_repository;
@@ -1,4 +1,6 @@
 use crate::http::controllers::v1::policy_set::models::SchemaBoundPolicySetRegistration;
+use crate::services::repositories::list_repository::ListableResource;
+use crate::services::repositories::lookup_trie::SchemaBoundResource;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::soft_delete_resource::SoftDeleteResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::UpdateLabels;
 use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
@@ -6,6 +8,51 @@ use kube::CustomResource;
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
 use std::collections::BTreeMap;
+use utoipa::ToSchema;
+
+/// How the validator uses a policy document.
+#[derive(ToSchema, Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyMode {
+    /// Policies decide every token review.
+    #[default]
+    Enforce,
+    /// Policies are evaluated next to the enforced set, but only their would-be decision is recorded.
+    Shadow,
+}
+
+/// What a policy document registers.
+#[derive(ToSchema, Debug, Serialize, Deserialize, Default, Clone, Copy, PartialEq, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum PolicyKind {
+    /// Static Cedar policies.
+    #[default]
+    Static,
+    /// A single Cedar policy template with `?principal` and/or `?resource` slots.
+    Template,
+    /// A link of a policy template registered in the same schema.
+    Link,
+}
+
+/// Instantiates a policy template with concrete entities.
+#[derive(ToSchema, Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[schema(rename_all = "camelCase")]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateLink {
+    /// Id of the template policy set within the schema.
+    pub template: String,
+    /// Entity UID bound to `?principal`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub principal: Option<String>,
+    /// Entity UID bound to `?resource`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resource: Option<String>,
+}
+
+/// Name of the policy document registered as `id` within `schema`, used as its Cedar policy id.
+pub fn policy_document_name(schema: &str, id: &str) -> String {
+    format!("{}-{}", schema, id)
+}
 
 #[derive(CustomResource, Debug, Serialize, Deserialize, Default, Clone, JsonSchema)]
 #[kube(
@@ -14,12 +61,24 @@ use std::collections::BTreeMap;
     kind = "PolicyDocument",
     plural = "policy-documents",
     singular = "policy-document",
-    namespaced
+    namespaced,
+    selectable = ".spec.schema",
+    selectable = ".spec.active"
 )]
 pub struct PolicyDocumentSpec {
+    /// The id the document is registered under; unset on documents written before ids were stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub active: bool,
     pub policies: String,
     pub schema: String,
+    #[serde(default)]
+    pub mode: PolicyMode,
+    #[serde(default)]
+    pub kind: PolicyKind,
+    /// Set on `link` documents, which have no `policies`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub link: Option<TemplateLink>,
 }
 
 impl Default for PolicyDocument {
@@ -31,9 +90,13 @@ impl Default for PolicyDocument {
                 ..Default::default()
             },
             spec: PolicyDocumentSpec {
+                id: None,
                 active: true,
                 policies: String::new(),
                 schema: Default::default(),
+                mode: PolicyMode::Enforce,
+                kind: PolicyKind::Static,
+                link: None,
             },
         }
     }
@@ -42,9 +105,13 @@ impl Default for PolicyDocument {
 impl From<SchemaBoundPolicySetRegistration> for PolicyDocumentSpec {
     fn from(value: SchemaBoundPolicySetRegistration) -> Self {
         PolicyDocumentSpec {
+            id: Some(value.id),
             active: true,
             policies: value.policy.to_string(),
             schema: value.schema.to_string(),
+            mode: value.mode,
+            kind: value.kind,
+            link: value.link,
         }
     }
 }
@@ -54,6 +121,10 @@ impl Into<SchemaBoundPolicySetRegistration> for PolicyDocumentSpec {
         SchemaBoundPolicySetRegistration {
             policy: self.policies,
             schema: self.schema,
+            id: self.id.unwrap_or_default(),
+            mode: self.mode,
+            kind: self.kind,
+            link: self.link,
         }
     }
 }
@@ -80,3 +151,15 @@ impl UpdateLabels for PolicyDocument {
         self
     }
 }
+
+impl SchemaBoundResource for PolicyDocument {
+    fn schema(&self) -> String {
+        self.spec.schema.clone()
+    }
+}
+
+impl ListableResource for PolicyDocument {
+    fn id(&self) -> Option<String> {
+        self.spec.id.clone()
+    }
+}
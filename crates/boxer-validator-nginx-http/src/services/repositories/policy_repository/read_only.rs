@@ -1,10 +1,13 @@
-use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
+use crate::services::repositories::policy_repository::policy_document::{
+    PolicyDocument, PolicyDocumentSpec, PolicyKind, PolicyMode, policy_document_name,
+};
+use anyhow::anyhow;
 use async_trait::async_trait;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::ResourceUpdateHandler;
 use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
-use cedar_policy::{Policy, PolicyId, PolicySet, PolicySetError};
+use cedar_policy::{EntityUid, Policy, PolicyId, PolicySet, PolicySetError, SlotId, Template};
 use kube::runtime::watcher;
-use log::{debug, warn};
+use log::{debug, info, warn};
 use std::collections::HashMap;
 use std::str::FromStr;
 use std::sync::Arc;
@@ -12,14 +15,96 @@ use tokio::sync::RwLock;
 
 pub struct PolicyRepositoryData {
     policy_set: RwLock<HashMap<String, PolicySet>>,
+    shadow_policy_set: RwLock<HashMap<String, PolicySet>>,
+    /// Template links per schema, kept so they can be linked again when their template is replaced or arrives late.
+    links: RwLock<HashMap<String, HashMap<PolicyId, DeclaredLink>>>,
+}
+
+#[derive(Clone)]
+struct DeclaredLink {
+    /// Policy id of the link, derived from the id the link document is registered under.
+    id: PolicyId,
+    template: PolicyId,
+    values: HashMap<SlotId, EntityUid>,
+}
+
+impl DeclaredLink {
+    fn from_spec(id: PolicyId, spec: &PolicyDocumentSpec) -> anyhow::Result<Self> {
+        let link = spec
+            .link
+            .as_ref()
+            .ok_or(anyhow!("Link document does not name a template"))?;
+        let mut values = HashMap::new();
+        if let Some(principal) = &link.principal {
+            values.insert(SlotId::principal(), EntityUid::from_str(principal)?);
+        }
+        if let Some(resource) = &link.resource {
+            values.insert(SlotId::resource(), EntityUid::from_str(resource)?);
+        }
+        Ok(DeclaredLink {
+            id,
+            // PolicyId::from_str returns Infallible as an error
+            template: PolicyId::from_str(&policy_document_name(&spec.schema, &link.template)).unwrap(),
+            values,
+        })
+    }
+}
+
+/// The Cedar policy id of a document: the name it is registered under within its schema,
+/// or the resource name for documents written before ids were stored.
+fn policy_id(name: &str, spec: &PolicyDocumentSpec) -> PolicyId {
+    let name = match &spec.id {
+        Some(id) => policy_document_name(&spec.schema, id),
+        None => name.to_string(),
+    };
+    // PolicyId::from_str returns Infallible as an error
+    PolicyId::from_str(&name).unwrap()
 }
 
 pub(crate) fn new() -> Arc<PolicyRepositoryData> {
     Arc::new(PolicyRepositoryData {
         policy_set: RwLock::new(HashMap::default()),
+        shadow_policy_set: RwLock::new(HashMap::default()),
+        links: RwLock::new(HashMap::default()),
     })
 }
 
+impl PolicyRepositoryData {
+    pub fn shadow(self: &Arc<Self>) -> Arc<ShadowPolicyRepository> {
+        Arc::new(ShadowPolicyRepository { data: self.clone() })
+    }
+}
+
+/// Read-only view of the policies evaluated in shadow mode: the enforced policy set of a schema
+/// together with the policy sets registered with `mode: shadow`.
+pub struct ShadowPolicyRepository {
+    data: Arc<PolicyRepositoryData>,
+}
+
+impl ShadowPolicyRepository {
+    pub async fn contains(&self, key: &str) -> bool {
+        let guard = self.data.shadow_policy_set.read().await;
+        guard.get(key).is_some_and(|policy_set| !policy_set.is_empty())
+    }
+}
+
+#[async_trait]
+impl ReadOnlyRepository<String, PolicySet> for ShadowPolicyRepository {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: String) -> Result<PolicySet, Self::ReadError> {
+        let enforced = self.data.policy_set.read().await;
+        let shadow = self.data.shadow_policy_set.read().await;
+        let Some(shadow_policy_set) = shadow.get(&key) else {
+            return Err(anyhow::anyhow!("Shadow policy set not found for key: {}", key));
+        };
+        let mut policy_set = enforced.get(&key).cloned().unwrap_or_default();
+        // Every document is held by exactly one of the sets, so policy ids never collide
+        policy_set.merge(shadow_policy_set, false)?;
+        Ok(policy_set)
+    }
+}
+
 #[async_trait]
 impl ReadOnlyRepository<String, PolicySet> for PolicyRepositoryData {
     type ReadError = anyhow::Error;
@@ -40,27 +125,181 @@ impl ResourceUpdateHandler<PolicyDocument> for PolicyRepositoryData {
             Err(err) => warn!("Error while fetching policy: {:?}", err),
             Ok(event) => {
                 debug!("Received policy update: {:?}", event);
-                let policy = Policy::from_str(&event.spec.policies);
-                match policy {
-                    Err(err) => warn!("Failed to parse policy set: {:?}", err),
-                    Ok(new_policy) => {
-                        // We use here unwrap because the name is guaranteed to be present by Kubernetes
-                        // and PolicyId::from_str return Infallible as an error
-                        let new_id = PolicyId::from_str(&event.metadata.name.clone().unwrap()).unwrap();
-                        let new_policy = new_policy.new_id(new_id);
-                        let mut guard = self.policy_set.write().await;
-                        insert_or_replace(&mut guard, event.spec.schema.clone(), new_policy.clone()).unwrap_or_else(
-                            |err| {
-                                warn!("Failed to insert or replace policy: {:?}", err);
-                            },
-                        );
-                    }
+                // We use here unwrap because the name is guaranteed to be present by Kubernetes
+                let id = policy_id(event.metadata.name.as_ref().unwrap(), &event.spec);
+                // The document may have switched kinds, so whatever it registered before is dropped first
+                self.remove_other_kinds(&id, &event.spec).await;
+                match event.spec.kind {
+                    PolicyKind::Static => self.update_static(id, &event.spec).await,
+                    PolicyKind::Template => self.update_template(id, &event.spec).await,
+                    PolicyKind::Link => self.update_link(id, &event.spec).await,
                 }
             }
         }
     }
 }
 
+impl PolicyRepositoryData {
+    /// Removes the policy, template or link registered under `id` that is of another kind than the document.
+    async fn remove_other_kinds(&self, id: &PolicyId, spec: &PolicyDocumentSpec) {
+        let mut enforced = self.policy_set.write().await;
+        let mut shadow = self.shadow_policy_set.write().await;
+        let mut links = self.links.write().await;
+        for sets in [&mut *enforced, &mut *shadow] {
+            let Some(policy_set) = sets.get_mut(&spec.schema) else {
+                continue;
+            };
+            if spec.kind != PolicyKind::Template {
+                remove_template(policy_set, id);
+            }
+            let is_link = policy_set
+                .policy(id)
+                .is_some_and(|policy| policy.template_id().is_some());
+            if spec.kind != PolicyKind::Link && is_link {
+                let _ = policy_set.unlink(id.clone());
+            }
+            if spec.kind != PolicyKind::Static && !is_link && policy_set.policy(id).is_some() {
+                let _ = policy_set.remove_static(id.clone());
+            }
+        }
+        if spec.kind != PolicyKind::Link
+            && let Some(schema_links) = links.get_mut(&spec.schema)
+        {
+            schema_links.remove(id);
+        }
+    }
+
+    async fn update_static(&self, id: PolicyId, spec: &PolicyDocumentSpec) {
+        let policy = Policy::from_str(&spec.policies);
+        match policy {
+            Err(err) => warn!("Failed to parse policy set: {:?}", err),
+            Ok(new_policy) => {
+                let new_policy = new_policy.new_id(id);
+                let (target, other) = match spec.mode {
+                    PolicyMode::Enforce => (&self.policy_set, &self.shadow_policy_set),
+                    PolicyMode::Shadow => (&self.shadow_policy_set, &self.policy_set),
+                };
+                // The document may have switched modes, so it is dropped from the other set first
+                remove_policy(&mut *other.write().await, &spec.schema, new_policy.id());
+                let mut guard = target.write().await;
+                insert_or_replace(&mut guard, spec.schema.clone(), new_policy.clone()).unwrap_or_else(|err| {
+                    warn!("Failed to insert or replace policy: {:?}", err);
+                });
+            }
+        }
+    }
+
+    /// Replaces the template in the set selected by its mode and links its declared links again.
+    async fn update_template(&self, id: PolicyId, spec: &PolicyDocumentSpec) {
+        let template = match Template::parse(Some(id.clone()), &spec.policies) {
+            Ok(template) => template,
+            Err(err) => {
+                warn!("Failed to parse policy template {}: {:?}", id, err);
+                return;
+            }
+        };
+        let mut enforced = self.policy_set.write().await;
+        let mut shadow = self.shadow_policy_set.write().await;
+        for sets in [&mut *enforced, &mut *shadow] {
+            if let Some(policy_set) = sets.get_mut(&spec.schema) {
+                remove_template(policy_set, &id);
+            }
+        }
+        if !spec.active {
+            return;
+        }
+
+        let target = match spec.mode {
+            PolicyMode::Enforce => &mut *enforced,
+            PolicyMode::Shadow => &mut *shadow,
+        };
+        let policy_set = target.entry(spec.schema.clone()).or_default();
+        if let Err(err) = policy_set.add_template(template) {
+            warn!("Failed to add policy template {}: {:?}", id, err);
+            return;
+        }
+        let links = self.links.read().await;
+        let declared = links.get(&spec.schema).into_iter().flat_map(HashMap::values);
+        for link in declared.filter(|link| link.template == id) {
+            if let Err(err) = policy_set.link(id.clone(), link.id.clone(), link.values.clone()) {
+                warn!("Failed to link policy {} to template {}: {:?}", link.id, id, err);
+            }
+        }
+    }
+
+    /// Links the template in whichever set holds it. Links of templates that are not registered yet
+    /// are kept and linked once the template arrives.
+    async fn update_link(&self, id: PolicyId, spec: &PolicyDocumentSpec) {
+        let declared = if spec.active {
+            match DeclaredLink::from_spec(id.clone(), spec) {
+                Ok(link) => Some(link),
+                Err(err) => {
+                    warn!("Failed to read template link {}: {:?}", id, err);
+                    None
+                }
+            }
+        } else {
+            None
+        };
+        let mut enforced = self.policy_set.write().await;
+        let mut shadow = self.shadow_policy_set.write().await;
+        let mut links = self.links.write().await;
+        for sets in [&mut *enforced, &mut *shadow] {
+            if let Some(policy_set) = sets.get_mut(&spec.schema)
+                && policy_set.policy(&id).is_some()
+            {
+                let _ = policy_set.unlink(id.clone());
+            }
+        }
+
+        let schema_links = links.entry(spec.schema.clone()).or_default();
+        let Some(link) = declared else {
+            schema_links.remove(&id);
+            return;
+        };
+        let policy_set = [&mut *enforced, &mut *shadow]
+            .into_iter()
+            .filter_map(|sets| sets.get_mut(&spec.schema))
+            .find(|policy_set| policy_set.template(&link.template).is_some());
+        match policy_set {
+            Some(policy_set) => {
+                if let Err(err) = policy_set.link(link.template.clone(), link.id.clone(), link.values.clone()) {
+                    warn!(
+                        "Failed to link policy {} to template {}: {:?}",
+                        link.id, link.template, err
+                    );
+                }
+            }
+            None => info!("Template {} for link {} is not registered yet", link.template, link.id),
+        }
+        schema_links.insert(link.id.clone(), link);
+    }
+}
+
+/// Removes a template along with its links; the links stay declared and are linked again with the next template.
+fn remove_template(policy_set: &mut PolicySet, id: &PolicyId) {
+    if policy_set.template(id).is_none() {
+        return;
+    }
+    let linked: Vec<PolicyId> = policy_set
+        .policies()
+        .filter(|policy| policy.template_id() == Some(id))
+        .map(|policy| policy.id().clone())
+        .collect();
+    for link in linked {
+        let _ = policy_set.unlink(link);
+    }
+    let _ = policy_set.remove_template(id.clone());
+}
+
+fn remove_policy(map: &mut HashMap<String, PolicySet>, key: &str, policy_id: &PolicyId) {
+    if let Some(existing) = map.get_mut(key)
+        && existing.policy(policy_id).is_some()
+    {
+        let _ = existing.remove_static(policy_id.clone());
+    }
+}
+
 fn insert_or_replace(
     map: &mut HashMap<String, PolicySet>,
     key: String,
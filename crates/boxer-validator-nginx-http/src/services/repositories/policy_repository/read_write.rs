@@ -1,4 +1,5 @@
 use crate::http::controllers::v1::policy_set::models::SchemaBoundPolicySetRegistration;
+use crate::services::repositories::list_repository::ListRepository;
 use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::KubernetesRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::GenericKubernetesResourceManager;
@@ -13,6 +14,8 @@ pub type PolicyDataRepository = dyn UpsertRepositoryWithDelete<
         ReadError = Status,
     >;
 
+pub type PolicySetListRepository = dyn ListRepository<SchemaBoundPolicySetRegistration>;
+
 impl UpsertRepositoryWithDelete<(String, String), SchemaBoundPolicySetRegistration>
     for KubernetesRepository<PolicyDocument, GenericKubernetesResourceManager<PolicyDocument>>
 {
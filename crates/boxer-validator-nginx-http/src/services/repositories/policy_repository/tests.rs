@@ -1,17 +1,21 @@
 use crate::http::controllers::v1::policy_set::models::SchemaBoundPolicySetRegistration;
 use crate::services::repositories::lookup_trie::backend::ReadOnlyRepositoryBackend;
 use crate::services::repositories::policy_repository;
-use crate::services::repositories::policy_repository::policy_document::PolicyDocument;
+use crate::services::repositories::policy_repository::policy_document::{
+    PolicyDocument, PolicyKind, PolicyMode, TemplateLink,
+};
 use crate::services::repositories::policy_repository::read_only::PolicyRepositoryData;
 use crate::services::repositories::policy_repository::read_write::PolicyDataRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::KubernetesRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_watcher::KubernetesResourceWatcherRunner;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
 use boxer_core::services::service_provider::ServiceProvider;
 use boxer_core::testing::api_extensions::WaitForResource;
 use boxer_core::testing::spin_lock_kubernetes_resource_manager_context::GenericKubernetesResourceManagerTestContext;
-use cedar_policy::PolicySet;
+use cedar_policy::{PolicyId, PolicySet};
 use kube::Api;
 use log::LevelFilter;
+use std::str::FromStr;
 use std::sync::Arc;
 use std::time::Duration;
 use test_context::{AsyncTestContext, test_context};
@@ -62,7 +66,11 @@ async fn test_create_policy(ctx: &mut KubernetesSchemaRepositoryTest) {
     let schema = "test-schema";
     let reg = SchemaBoundPolicySetRegistration {
         schema: schema.to_string(),
+        id: name.to_string(),
         policy: policy_str.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
     };
 
     ctx.readwrite_repository
@@ -104,7 +112,11 @@ async fn test_create_multiple_policies(ctx: &mut KubernetesSchemaRepositoryTest)
     let schema = "test-schema";
     let reg = SchemaBoundPolicySetRegistration {
         schema: schema.to_string(),
+        id: name.to_string(),
         policy: policy_1.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
     };
 
     ctx.readwrite_repository
@@ -114,7 +126,11 @@ async fn test_create_multiple_policies(ctx: &mut KubernetesSchemaRepositoryTest)
 
     let reg2 = SchemaBoundPolicySetRegistration {
         schema: schema.to_string(),
+        id: name.to_string(),
         policy: policy_2.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
     };
     ctx.readwrite_repository
         .upsert((schema.to_string(), format!("{}-2", name)), reg2)
@@ -156,7 +172,11 @@ async fn test_modify_policy(ctx: &mut KubernetesSchemaRepositoryTest) {
 
     let reg = SchemaBoundPolicySetRegistration {
         schema: schema.to_string(),
+        id: name.to_string(),
         policy: policy_initial.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
     };
 
     ctx.readwrite_repository
@@ -178,7 +198,11 @@ async fn test_modify_policy(ctx: &mut KubernetesSchemaRepositoryTest) {
 
     let reg = SchemaBoundPolicySetRegistration {
         schema: schema.to_string(),
+        id: name.to_string(),
         policy: policy_updated.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
     };
 
     ctx.readwrite_repository
@@ -198,3 +222,207 @@ async fn test_modify_policy(ctx: &mut KubernetesSchemaRepositoryTest) {
 
     assert_eq!(policy.to_cedar().unwrap(), policy_updated.to_string());
 }
+
+#[test_context(KubernetesSchemaRepositoryTest)]
+#[tokio::test]
+async fn test_create_shadow_policy(ctx: &mut KubernetesSchemaRepositoryTest) {
+    let _ = env_logger::builder().filter_level(LevelFilter::Debug).try_init();
+
+    let policy_str = r#"permit(
+    principal == User::"alice",
+    action == Action::"read",
+    resource == Document::"secret"
+);"#;
+
+    let name = "test-shadow-policy";
+    let schema = "test-shadow-schema";
+    let reg = SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: name.to_string(),
+        policy: policy_str.to_string(),
+        mode: PolicyMode::Shadow,
+        kind: PolicyKind::Static,
+        link: None,
+    };
+
+    ctx.readwrite_repository
+        .upsert((schema.to_string(), name.to_string()), reg)
+        .await
+        .expect("Failed to upsert policy");
+
+    ctx.api
+        .wait_for_creation(
+            "test-shadow-schema-test-shadow-policy".to_string(),
+            ctx.namespace.to_string(),
+            DEFAULT_TEST_TIMEOUT,
+        )
+        .await;
+
+    let shadow = ctx.readonly_repository.update_handler().shadow();
+    let policy = shadow.get(schema.to_string()).await.unwrap();
+
+    assert_eq!(policy.to_cedar().unwrap(), policy_str.to_string());
+    assert!(ctx.readonly_repository.get().get(schema.to_string()).await.is_err());
+}
+
+#[test_context(KubernetesSchemaRepositoryTest)]
+#[tokio::test]
+async fn test_shadow_policies_include_enforced_policies(ctx: &mut KubernetesSchemaRepositoryTest) {
+    let _ = env_logger::builder().filter_level(LevelFilter::Debug).try_init();
+
+    let schema = "test-shadow-union-schema";
+    let enforced = SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: "enforced".to_string(),
+        policy: r#"permit(principal, action == Action::"read", resource);"#.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Static,
+        link: None,
+    };
+    let shadow = SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: "shadow".to_string(),
+        policy: r#"forbid(principal, action == Action::"read", resource == Document::"secret");"#.to_string(),
+        mode: PolicyMode::Shadow,
+        kind: PolicyKind::Static,
+        link: None,
+    };
+
+    for reg in [enforced, shadow] {
+        let name = format!("{}-{}", schema, reg.id);
+        ctx.readwrite_repository
+            .upsert((schema.to_string(), reg.id.clone()), reg)
+            .await
+            .expect("Failed to upsert policy");
+        ctx.api
+            .wait_for_creation(name, ctx.namespace.to_string(), DEFAULT_TEST_TIMEOUT)
+            .await;
+    }
+
+    let shadow = ctx.readonly_repository.update_handler().shadow();
+    let policy = shadow.get(schema.to_string()).await.unwrap();
+    let enforced = ctx.readonly_repository.get().get(schema.to_string()).await.unwrap();
+
+    assert_eq!(policy.policies().count(), 2);
+    assert_eq!(enforced.policies().count(), 1);
+}
+
+#[test_context(KubernetesSchemaRepositoryTest)]
+#[tokio::test]
+async fn test_link_policy_template(ctx: &mut KubernetesSchemaRepositoryTest) {
+    let _ = env_logger::builder().filter_level(LevelFilter::Debug).try_init();
+
+    let template_str = r#"permit(
+    principal == ?principal,
+    action == Action::"read",
+    resource == ?resource
+);"#;
+
+    let schema = "test-template-schema";
+    let link = SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: "alice-owns-secret".to_string(),
+        policy: String::new(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Link,
+        link: Some(TemplateLink {
+            template: "owner".to_string(),
+            principal: Some(r#"User::"alice""#.to_string()),
+            resource: Some(r#"Document::"secret""#.to_string()),
+        }),
+    };
+    let template = SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: "owner".to_string(),
+        policy: template_str.to_string(),
+        mode: PolicyMode::Enforce,
+        kind: PolicyKind::Template,
+        link: None,
+    };
+
+    // The link is registered first and must be linked once the template arrives
+    ctx.readwrite_repository
+        .upsert((schema.to_string(), "alice-owns-secret".to_string()), link)
+        .await
+        .expect("Failed to upsert link");
+    ctx.readwrite_repository
+        .upsert((schema.to_string(), "owner".to_string()), template)
+        .await
+        .expect("Failed to upsert template");
+
+    ctx.api
+        .wait_for_creation(
+            "test-template-schema-owner".to_string(),
+            ctx.namespace.to_string(),
+            DEFAULT_TEST_TIMEOUT,
+        )
+        .await;
+
+    let policy_set = ctx.readonly_repository.get().get(schema.to_string()).await.unwrap();
+    let link_id = PolicyId::from_str("test-template-schema-alice-owns-secret").unwrap();
+    let template_id = PolicyId::from_str("test-template-schema-owner").unwrap();
+
+    assert!(policy_set.template(&template_id).is_some());
+    assert_eq!(
+        policy_set.policy(&link_id).and_then(|policy| policy.template_id()),
+        Some(&template_id)
+    );
+}
+
+#[test_context(KubernetesSchemaRepositoryTest)]
+#[tokio::test]
+async fn test_switch_policy_kind(ctx: &mut KubernetesSchemaRepositoryTest) {
+    let _ = env_logger::builder().filter_level(LevelFilter::Debug).try_init();
+
+    let static_str = r#"permit(
+    principal == User::"alice",
+    action == Action::"read",
+    resource == Document::"secret"
+);"#;
+    let template_str = r#"permit(
+    principal == ?principal,
+    action == Action::"read",
+    resource == Document::"secret"
+);"#;
+
+    let schema = "test-switch-schema";
+    let name = "test-switch-schema-owner";
+    let registration = |kind: PolicyKind, policy: &str| SchemaBoundPolicySetRegistration {
+        schema: schema.to_string(),
+        id: "owner".to_string(),
+        policy: policy.to_string(),
+        mode: PolicyMode::Enforce,
+        kind,
+        link: None,
+    };
+    let id = PolicyId::from_str(name).unwrap();
+
+    for (kind, policy) in [(PolicyKind::Static, static_str), (PolicyKind::Template, template_str)] {
+        ctx.readwrite_repository
+            .upsert((schema.to_string(), "owner".to_string()), registration(kind, policy))
+            .await
+            .expect("Failed to upsert policy");
+        ctx.api
+            .wait_for_creation(name.to_string(), ctx.namespace.to_string(), DEFAULT_TEST_TIMEOUT)
+            .await;
+    }
+
+    let policy_set = ctx.readonly_repository.get().get(schema.to_string()).await.unwrap();
+    assert!(policy_set.template(&id).is_some());
+    assert!(policy_set.policy(&id).is_none());
+
+    ctx.readwrite_repository
+        .upsert(
+            (schema.to_string(), "owner".to_string()),
+            registration(PolicyKind::Static, static_str),
+        )
+        .await
+        .expect("Failed to upsert policy");
+    ctx.api
+        .wait_for_creation(name.to_string(), ctx.namespace.to_string(), DEFAULT_TEST_TIMEOUT)
+        .await;
+
+    let policy_set = ctx.readonly_repository.get().get(schema.to_string()).await.unwrap();
+    assert!(policy_set.template(&id).is_none());
+    assert_eq!(policy_set.to_cedar().unwrap(), static_str.to_string());
+}
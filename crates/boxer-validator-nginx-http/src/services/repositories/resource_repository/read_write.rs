@@ -1,4 +1,5 @@
 use crate::http::controllers::v1::resource_set::models::SchemaBoundResourceSetRegistration;
+use crate::services::repositories::list_repository::ListRepository;
 use crate::services::repositories::resource_repository::resource_discovery_document::ResourceDiscoveryDocument;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::KubernetesRepository;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::GenericKubernetesResourceManager;
@@ -13,6 +14,8 @@ pub type ResourceDiscoveryDocumentRepository = dyn UpsertRepositoryWithDelete<
         ReadError = Status,
     >;
 
+pub type ResourceSetListRepository = dyn ListRepository<SchemaBoundResourceSetRegistration>;
+
 impl UpsertRepositoryWithDelete<(String, String), SchemaBoundResourceSetRegistration>
     for KubernetesRepository<ResourceDiscoveryDocument, GenericKubernetesResourceManager<ResourceDiscoveryDocument>>
 {
@@ -1,11 +1,12 @@
 use crate::http::controllers::v1::resource_set::models::{
     ResourceRouteRegistration, SchemaBoundResourceSetRegistration,
 };
+use crate::services::repositories::list_repository::ListableResource;
 use crate::services::repositories::lookup_trie::{EntityCollectionResource, SchemaBoundResource};
+use crate::services::route_template::parse_route_template;
 use boxer_core::services::backends::kubernetes::kubernetes_repository::soft_delete_resource::SoftDeleteResource;
 use boxer_core::services::backends::kubernetes::kubernetes_resource_manager::UpdateLabels;
 use boxer_core::services::validation_service::path_segment::PathSegment;
-use boxer_core::services::validation_service::path_segment::PathSegment::{Parameter, Static};
 use cedar_policy::EntityUid;
 use futures::Stream;
 use futures::StreamExt;
@@ -24,10 +25,15 @@ use std::str::FromStr;
     kind = "ResourceDiscoveryDocument",
     plural = "resource-discovery-documents",
     singular = "resource-discovery-document",
-    namespaced
+    namespaced,
+    selectable = ".spec.schema",
+    selectable = ".spec.active"
 )]
 
 pub struct ResourceDiscoveryDocumentSpec {
+    /// The id the document is registered under; unset on documents written before ids were stored.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub active: bool,
     pub hostname: String,
     pub routes: Vec<ResourceRoute>,
@@ -70,6 +76,7 @@ impl From<&SchemaBoundResourceSetRegistration> for ResourceDiscoveryDocumentSpec
             routes.push(action_route)
         }
         ResourceDiscoveryDocumentSpec {
+            id: Some(value.id.clone()),
             active: true,
             hostname: value.hostname.clone(),
             routes,
@@ -93,6 +100,7 @@ impl Into<SchemaBoundResourceSetRegistration> for ResourceDiscoveryDocumentSpec
             hostname: self.hostname,
             routes,
             schema: self.schema.clone(),
+            id: self.id.unwrap_or_default(),
         }
     }
 }
@@ -131,18 +139,7 @@ impl TryInto<Vec<PathSegment>> for ResourceRoute {
     type Error = anyhow::Error;
 
     fn try_into(self) -> Result<Vec<PathSegment>, Self::Error> {
-        let mut segments: Vec<PathSegment> = vec![];
-        for segment in self.route_template.split('/') {
-            if segment.is_empty() {
-                continue;
-            }
-            if segment.starts_with('{') && segment.ends_with('}') {
-                segments.push(Parameter)
-            } else {
-                segments.push(Static(segment.to_string()))
-            }
-        }
-        Ok(segments)
+        parse_route_template(&self.route_template)
     }
 }
 
@@ -151,3 +148,13 @@ impl SchemaBoundResource for ResourceDiscoveryDocument {
         self.spec.schema.clone()
     }
 }
+
+impl ListableResource for ResourceDiscoveryDocument {
+    fn id(&self) -> Option<String> {
+        self.spec.id.clone()
+    }
+
+    fn hostname(&self) -> Option<String> {
+        Some(self.spec.hostname.clone())
+    }
+}
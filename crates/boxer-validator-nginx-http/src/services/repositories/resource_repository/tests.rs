@@ -75,7 +75,7 @@ async fn test_create_resource_discovery_document(ctx: &mut KubernetesResourceRep
     ctx.repository
         .upsert(
             ("schema".to_string(), name.to_string()),
-            registration.with_schema("schema".to_string()),
+            registration.with_key("schema".to_string(), name.to_string()),
         )
         .await
         .expect("Failed to upsert schema");
@@ -0,0 +1,227 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::decision_log::Decision;
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use async_trait::async_trait;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use cedar_policy::{EntityUid, PolicySet};
+use opentelemetry::trace::{FutureExt, Span, SpanBuilder, TraceContextExt};
+use opentelemetry::{Context, KeyValue, global};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime};
+
+const TRACER_NAME: &str = "boxer-validator";
+
+/// Stages of a token review, reported as child spans of the review and in the stage latency histogram.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ReviewStage {
+    TokenDecryption,
+    RequestContext,
+    ActionLookup,
+    ResourceLookup,
+    /// Fetching the validator schema and merging it with the principal schema of the token.
+    Schema,
+    PolicyFetch,
+    /// Cedar `is_authorized`.
+    Authorization,
+}
+
+impl ReviewStage {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReviewStage::TokenDecryption => "token_decryption",
+            ReviewStage::RequestContext => "request_context",
+            ReviewStage::ActionLookup => "action_lookup",
+            ReviewStage::ResourceLookup => "resource_lookup",
+            ReviewStage::Schema => "schema",
+            ReviewStage::PolicyFetch => "policy_fetch",
+            ReviewStage::Authorization => "authorization",
+        }
+    }
+}
+
+/// A point in time, kept both monotonic for durations and as wall-clock time for span timestamps.
+#[derive(Debug, Clone, Copy)]
+pub struct StageStart {
+    instant: Instant,
+    time: SystemTime,
+}
+
+impl StageStart {
+    pub fn now() -> Self {
+        StageStart {
+            instant: Instant::now(),
+            time: SystemTime::now(),
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_REVIEW: Arc<ReviewTrace>;
+}
+
+/// The `token_review` span and the stage timings of a single token review.
+pub struct ReviewTrace {
+    schema: String,
+    context: Context,
+    stages: Mutex<Vec<(ReviewStage, Duration)>>,
+    resolved: Mutex<Vec<(ReviewStage, String)>>,
+}
+
+impl ReviewTrace {
+    /// Starts the `token_review` span as a child of the current request span.
+    pub fn start(schema: &str) -> Arc<Self> {
+        Self::named("token_review", schema)
+    }
+
+    /// Starts the `policy_replay` span of a decision replayed by the policy simulation.
+    pub fn replay(schema: &str) -> Arc<Self> {
+        Self::named("policy_replay", schema)
+    }
+
+    fn named(name: &'static str, schema: &str) -> Arc<Self> {
+        let span = SpanBuilder::from_name(name)
+            .with_attributes([KeyValue::new("schema", schema.to_string())])
+            .start_with_context(&global::tracer(TRACER_NAME), &Context::current());
+        Arc::new(ReviewTrace {
+            schema: schema.to_string(),
+            context: Context::current_with_span(span),
+            stages: Mutex::new(Vec::new()),
+            resolved: Mutex::new(Vec::new()),
+        })
+    }
+
+    fn stage_span(&self, stage: ReviewStage) -> SpanBuilder {
+        SpanBuilder::from_name(stage.as_str()).with_attributes([KeyValue::new("schema", self.schema.clone())])
+    }
+
+    fn push(&self, stage: ReviewStage, duration: Duration) {
+        self.stages.lock().unwrap().push((stage, duration));
+    }
+
+    /// Records a stage that started earlier and ends now, e.g. one that started in a middleware.
+    pub fn record_since(&self, stage: ReviewStage, started: StageStart) {
+        let mut span = self
+            .stage_span(stage)
+            .with_start_time(started.time)
+            .start_with_context(&global::tracer(TRACER_NAME), &self.context);
+        span.end();
+        self.push(stage, started.instant.elapsed());
+    }
+
+    /// Runs `future` as a stage of this review.
+    pub async fn measure<F: Future>(&self, stage: ReviewStage, future: F) -> F::Output {
+        let span = self
+            .stage_span(stage)
+            .start_with_context(&global::tracer(TRACER_NAME), &self.context);
+        let context = self.context.with_span(span);
+        let started = Instant::now();
+        let output = future.with_context(context.clone()).await;
+        context.span().end();
+        self.push(stage, started.elapsed());
+        output
+    }
+
+    /// Runs the validation of this review. Reads through a [`StagedRepository`] while it runs are recorded as stages.
+    /// Cedar evaluation runs inside the validation service, so it is timed as what is left of the validation
+    /// once those reads are taken out.
+    pub async fn validate<F: Future>(self: &Arc<Self>, future: F) -> F::Output {
+        let started = Instant::now();
+        let recorded = self.stages.lock().unwrap().len();
+        let output = CURRENT_REVIEW
+            .scope(self.clone(), future.with_context(self.context.clone()))
+            .await;
+        let reads: Duration = self.stages.lock().unwrap()[recorded..]
+            .iter()
+            .map(|(_, duration)| *duration)
+            .sum();
+        self.record_ending_now(ReviewStage::Authorization, started.elapsed().saturating_sub(reads));
+        output
+    }
+
+    /// Records a stage that took `duration` and ends now.
+    fn record_ending_now(&self, stage: ReviewStage, duration: Duration) {
+        let mut span = self
+            .stage_span(stage)
+            .with_start_time(SystemTime::now() - duration)
+            .start_with_context(&global::tracer(TRACER_NAME), &self.context);
+        span.end();
+        self.push(stage, duration);
+    }
+
+    /// The value last read in the given stage while the review was validated, e.g. the resolved action.
+    pub fn resolved(&self, stage: ReviewStage) -> Option<String> {
+        self.resolved
+            .lock()
+            .unwrap()
+            .iter()
+            .rev()
+            .find(|(resolved_stage, _)| *resolved_stage == stage)
+            .map(|(_, value)| value.clone())
+    }
+
+    /// Ends the `token_review` span and returns the recorded stages in the order they completed.
+    pub fn finish(&self, decision: Decision) -> Vec<(ReviewStage, Duration)> {
+        let span = self.context.span();
+        span.set_attribute(KeyValue::new("decision", decision.as_str()));
+        span.end();
+        self.stages.lock().unwrap().clone()
+    }
+}
+
+/// Runs `future` as a stage of the token review validated by the current task, if any.
+pub async fn measure_stage<F: Future>(stage: ReviewStage, future: F) -> F::Output {
+    match CURRENT_REVIEW.try_with(Arc::clone) {
+        Ok(review) => review.measure(stage, future).await,
+        Err(_) => future.await,
+    }
+}
+
+/// Values read through a [`StagedRepository`] that are kept by the current token review.
+pub trait ReviewedValue {
+    /// The value as kept by the review, or `None` if it is not kept.
+    fn reviewed(&self) -> Option<String>;
+}
+
+impl ReviewedValue for EntityUid {
+    fn reviewed(&self) -> Option<String> {
+        Some(self.to_string())
+    }
+}
+
+impl ReviewedValue for PolicySet {
+    fn reviewed(&self) -> Option<String> {
+        None
+    }
+}
+
+/// Records reads of the wrapped repository as a stage of the current token review.
+pub struct StagedRepository<Key, Value> {
+    inner: Arc<AssociatedRepository<Key, Value>>,
+    stage: ReviewStage,
+}
+
+impl<Key, Value> StagedRepository<Key, Value> {
+    pub fn new(inner: Arc<AssociatedRepository<Key, Value>>, stage: ReviewStage) -> Self {
+        StagedRepository { inner, stage }
+    }
+}
+
+#[async_trait]
+impl<Key, Value> ReadOnlyRepository<Key, Value> for StagedRepository<Key, Value>
+where
+    Key: Send + Sync + 'static,
+    Value: ReviewedValue + Send + Sync + 'static,
+{
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: Key) -> Result<Value, Self::ReadError> {
+        let value = measure_stage(self.stage, self.inner.get(key)).await?;
+        if let Some(reviewed) = value.reviewed() {
+            let _ = CURRENT_REVIEW.try_with(|review| review.resolved.lock().unwrap().push((self.stage, reviewed)));
+        }
+        Ok(value)
+    }
+}
@@ -0,0 +1,131 @@
+use super::*;
+use anyhow::anyhow;
+
+struct StaticLookup;
+
+#[async_trait]
+impl ReadOnlyRepository<String, String> for StaticLookup {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: String) -> Result<String, Self::ReadError> {
+        match key.as_str() {
+            "photos" => Ok("PhotoApp::Action::\"viewPhoto\"".to_string()),
+            _ => Err(anyhow!("not found: {}", key)),
+        }
+    }
+}
+
+impl ReviewedValue for String {
+    fn reviewed(&self) -> Option<String> {
+        Some(self.clone())
+    }
+}
+
+fn stages(recorded: &[(ReviewStage, Duration)]) -> Vec<ReviewStage> {
+    recorded.iter().map(|(stage, _)| *stage).collect()
+}
+
+#[tokio::test]
+async fn test_reads_during_validation_are_recorded_as_stages() {
+    let actions = StagedRepository::new(Arc::new(StaticLookup), ReviewStage::ActionLookup);
+    let policies = StagedRepository::new(Arc::new(StaticLookup), ReviewStage::PolicyFetch);
+    let review = ReviewTrace::start("photo-app");
+
+    let result = review
+        .validate(async {
+            actions.get("photos".to_string()).await?;
+            policies.get("photo-app".to_string()).await
+        })
+        .await;
+
+    assert!(result.is_err());
+    assert_eq!(
+        stages(&review.finish(Decision::Deny)),
+        vec![
+            ReviewStage::ActionLookup,
+            ReviewStage::PolicyFetch,
+            ReviewStage::Authorization
+        ]
+    );
+}
+
+/// Takes 50ms to read any key.
+struct SlowLookup;
+
+#[async_trait]
+impl ReadOnlyRepository<String, String> for SlowLookup {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: String) -> Result<String, Self::ReadError> {
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        Ok(key)
+    }
+}
+
+#[tokio::test]
+async fn test_authorization_is_what_is_left_of_the_validation() {
+    let policies = StagedRepository::new(Arc::new(SlowLookup), ReviewStage::PolicyFetch);
+    let review = ReviewTrace::start("photo-app");
+
+    review
+        .validate(async {
+            policies.get("photo-app".to_string()).await?;
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            anyhow::Ok(())
+        })
+        .await
+        .unwrap();
+
+    let recorded = review.finish(Decision::Allow);
+    assert_eq!(
+        stages(&recorded),
+        vec![ReviewStage::PolicyFetch, ReviewStage::Authorization]
+    );
+    assert!(recorded[0].1 >= Duration::from_millis(50));
+    assert!(recorded[1].1 >= Duration::from_millis(20));
+    assert!(recorded[1].1 < Duration::from_millis(50));
+}
+
+#[tokio::test]
+async fn test_reads_outside_validation_are_not_recorded() {
+    let actions = StagedRepository::new(Arc::new(StaticLookup), ReviewStage::ActionLookup);
+    let review = ReviewTrace::start("photo-app");
+
+    actions.get("photos".to_string()).await.unwrap();
+
+    assert_eq!(review.resolved(ReviewStage::ActionLookup), None);
+    assert!(review.finish(Decision::Allow).is_empty());
+}
+
+#[tokio::test]
+async fn test_values_read_during_validation_are_kept() {
+    let actions = StagedRepository::new(Arc::new(StaticLookup), ReviewStage::ActionLookup);
+    let review = ReviewTrace::start("photo-app");
+
+    review.validate(actions.get("photos".to_string())).await.unwrap();
+
+    assert_eq!(
+        review.resolved(ReviewStage::ActionLookup).as_deref(),
+        Some("PhotoApp::Action::\"viewPhoto\"")
+    );
+    assert_eq!(review.resolved(ReviewStage::ResourceLookup), None);
+}
+
+#[tokio::test]
+async fn test_record_since_measures_from_start() {
+    let started = StageStart::now();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    let review = ReviewTrace::start("photo-app");
+
+    review.record_since(ReviewStage::TokenDecryption, started);
+    review
+        .measure(ReviewStage::RequestContext, async { "https://www.example.com/photos" })
+        .await;
+
+    let recorded = review.finish(Decision::Allow);
+    assert_eq!(
+        stages(&recorded),
+        vec![ReviewStage::TokenDecryption, ReviewStage::RequestContext]
+    );
+    assert!(recorded[0].1 >= Duration::from_millis(20));
+}
@@ -0,0 +1,104 @@
+#[cfg(test)]
+mod tests;
+
+use anyhow::bail;
+use boxer_core::services::validation_service::path_segment::PathSegment;
+use boxer_core::services::validation_service::path_segment::PathSegment::{Parameter, Static};
+use boxer_core::services::validation_service::request_segment::RequestSegment;
+use percent_encoding::percent_decode_str;
+use std::collections::HashSet;
+
+/// Parses a route template (e.g. `api/v1/{object}`) into normalized path segments.
+///
+/// Leading and trailing slashes are optional, static segments are percent-decoded and
+/// parameters must be a whole segment of the form `{name}`.
+pub fn parse_route_template(template: &str) -> Result<Vec<PathSegment>, anyhow::Error> {
+    let trimmed = template.trim_matches('/');
+    if trimmed.is_empty() {
+        bail!("Route template must contain at least one segment");
+    }
+
+    let mut parameters = HashSet::new();
+    let mut segments = Vec::new();
+    for segment in trimmed.split('/') {
+        if segment.is_empty() {
+            bail!("Route template contains an empty segment");
+        }
+        if segment.starts_with('{') && segment.ends_with('}') && segment.len() > 1 {
+            let name = &segment[1..segment.len() - 1];
+            if !is_valid_parameter_name(name) {
+                bail!("Invalid parameter name '{}' in segment '{}'", name, segment);
+            }
+            if !parameters.insert(name) {
+                bail!("Duplicate parameter '{}'", name);
+            }
+            segments.push(Parameter);
+        } else if segment.contains(['{', '}']) {
+            bail!("Parameter must span the whole segment: '{}'", segment);
+        } else {
+            segments.push(Static(decode_segment(segment)?));
+        }
+    }
+    Ok(segments)
+}
+
+fn is_valid_parameter_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-' || c == '.')
+}
+
+fn decode_segment(segment: &str) -> Result<String, anyhow::Error> {
+    let bytes = segment.as_bytes();
+    for (i, byte) in bytes.iter().enumerate() {
+        if *byte == b'%' && !is_hex_pair(&bytes[i + 1..]) {
+            bail!("Invalid percent-encoding in segment '{}'", segment);
+        }
+    }
+    let decoded = percent_decode_str(segment).decode_utf8()?;
+    if decoded.contains('/') {
+        bail!("Segment '{}' decodes to a value containing '/'", segment);
+    }
+    Ok(decoded.into_owned())
+}
+
+fn is_hex_pair(bytes: &[u8]) -> bool {
+    bytes.len() >= 2 && bytes[0].is_ascii_hexdigit() && bytes[1].is_ascii_hexdigit()
+}
+
+/// Brings a lookup key segment to the form used by registered routes.
+pub trait NormalizeSegment: Sized {
+    /// Returns the normalized segment, or `None` if the segment should be dropped from the key.
+    fn normalize(self) -> Option<Self>;
+}
+
+impl NormalizeSegment for PathSegment {
+    fn normalize(self) -> Option<Self> {
+        match self {
+            Static(segment) if segment.is_empty() => None,
+            Static(segment) => Some(Static(percent_decode_str(&segment).decode_utf8_lossy().into_owned())),
+            Parameter => Some(Parameter),
+        }
+    }
+}
+
+impl NormalizeSegment for RequestSegment {
+    fn normalize(self) -> Option<Self> {
+        match self {
+            RequestSegment::Hostname(hostname) => Some(RequestSegment::Hostname(normalize_hostname(&hostname))),
+            RequestSegment::Path(segment) => segment.normalize().map(RequestSegment::Path),
+            verb => Some(verb),
+        }
+    }
+}
+
+/// Hostnames are matched case-insensitively.
+pub fn normalize_hostname(hostname: &str) -> String {
+    hostname.trim().to_ascii_lowercase()
+}
+
+/// Normalizes every segment of a lookup key.
+pub fn normalize_key<Key: NormalizeSegment>(key: Vec<Key>) -> Vec<Key> {
+    key.into_iter().filter_map(NormalizeSegment::normalize).collect()
+}
@@ -0,0 +1,79 @@
+use super::*;
+use crate::http::controllers::v1::action_set::models::{ActionRouteRegistration, ActionSetRegistration};
+use crate::http::controllers::v1::validation::ValidateRegistration;
+use test_case::test_case;
+
+fn s(segment: &str) -> PathSegment {
+    Static(segment.to_string())
+}
+
+#[test_case("api/v1/{object}" => vec![s("api"), s("v1"), Parameter]; "relative template")]
+#[test_case("/api/v1/{object}/" => vec![s("api"), s("v1"), Parameter]; "leading and trailing slashes")]
+#[test_case("api/my%20photos" => vec![s("api"), s("my photos")]; "percent-encoded segment")]
+#[test_case("api/{a}/{b}" => vec![s("api"), Parameter, Parameter]; "multiple parameters")]
+fn test_parse_valid_template(template: &str) -> Vec<PathSegment> {
+    parse_route_template(template).unwrap()
+}
+
+#[test_case(""; "empty template")]
+#[test_case("/"; "root only")]
+#[test_case("api//v1"; "empty segment")]
+#[test_case("/{a/b}"; "parameter spanning segments")]
+#[test_case("api/{}"; "empty parameter name")]
+#[test_case("api/v{version}"; "partial parameter")]
+#[test_case("api/{a}/{a}"; "duplicate parameter")]
+#[test_case("api/%zz"; "invalid percent-encoding")]
+#[test_case("api/a%2Fb"; "encoded slash")]
+fn test_parse_invalid_template(template: &str) {
+    assert!(parse_route_template(template).is_err());
+}
+
+#[test]
+fn test_lookup_key_normalization_matches_registration() {
+    let lookup = vec![
+        RequestSegment::Hostname("WWW.Example.com".to_string()),
+        RequestSegment::Path(s("api")),
+        RequestSegment::Path(s("my%20photos")),
+        RequestSegment::Path(s("")),
+    ];
+    let expected = vec![
+        RequestSegment::Hostname("www.example.com".to_string()),
+        RequestSegment::Path(s("api")),
+        RequestSegment::Path(s("my photos")),
+    ];
+    assert_eq!(normalize_key(lookup), expected);
+}
+
+#[test]
+fn test_registration_reports_every_invalid_route() {
+    let registration = ActionSetRegistration {
+        hostname: "www.example.com".to_string(),
+        routes: vec![
+            ActionRouteRegistration {
+                method: "GET".into(),
+                route_template: "api/v1/{id}".to_string(),
+                action_uid: "PhotoApp::Action::\"viewPhoto\"".to_string(),
+                public: false,
+            },
+            ActionRouteRegistration {
+                method: "FETCH".into(),
+                route_template: "api/{}".to_string(),
+                action_uid: "not a uid".to_string(),
+                public: false,
+            },
+        ],
+    };
+
+    let fields: Vec<String> = registration
+        .validate()
+        .unwrap_err()
+        .violations
+        .into_iter()
+        .map(|v| v.field)
+        .collect();
+
+    assert_eq!(
+        fields,
+        vec!["routes[1].method", "routes[1].routeTemplate", "routes[1].actionUid"]
+    );
+}
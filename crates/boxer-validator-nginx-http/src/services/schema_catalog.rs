@@ -0,0 +1,112 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::repositories::lookup_trie::RegisteredEntities;
+use anyhow::bail;
+use cedar_policy::EntityUid;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashSet;
+use std::sync::Arc;
+use utoipa::ToSchema;
+
+/// Entity types and actions declared in a Cedar schema fragment (JSON format).
+#[derive(Debug, Default)]
+pub struct SchemaCatalog {
+    entity_types: HashSet<String>,
+    actions: HashSet<(String, String)>,
+}
+
+impl SchemaCatalog {
+    pub fn from_json(schema: &Value) -> Result<Self, anyhow::Error> {
+        let Some(namespaces) = schema.as_object() else {
+            bail!("Schema must be a JSON object keyed by namespace");
+        };
+
+        let mut catalog = SchemaCatalog::default();
+        for (namespace, body) in namespaces {
+            let prefix = if namespace.is_empty() {
+                String::new()
+            } else {
+                format!("{namespace}::")
+            };
+            if let Some(entity_types) = body.get("entityTypes").and_then(Value::as_object) {
+                catalog
+                    .entity_types
+                    .extend(entity_types.keys().map(|name| format!("{prefix}{name}")));
+            }
+            if let Some(actions) = body.get("actions").and_then(Value::as_object) {
+                let action_type = format!("{prefix}Action");
+                catalog
+                    .actions
+                    .extend(actions.keys().map(|name| (action_type.clone(), name.clone())));
+            }
+        }
+        Ok(catalog)
+    }
+
+    /// Checks that the UID names an action declared in the schema.
+    pub fn check_action(&self, uid: &EntityUid) -> Result<(), anyhow::Error> {
+        let key = (uid.type_name().to_string(), uid.id().unescaped().to_string());
+        if !self.actions.contains(&key) {
+            bail!("Action {} is not declared in the schema", uid);
+        }
+        Ok(())
+    }
+
+    /// Checks that the UID's entity type is declared in the schema.
+    pub fn check_entity(&self, uid: &EntityUid) -> Result<(), anyhow::Error> {
+        let type_name = uid.type_name().to_string();
+        if !self.entity_types.contains(&type_name) {
+            bail!("Entity type {} is not declared in the schema", type_name);
+        }
+        Ok(())
+    }
+}
+
+/// A registered route whose action or resource UID would no longer exist in the schema.
+#[derive(ToSchema, Serialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OrphanedRegistration {
+    /// `action_set` or `resource_set`.
+    pub kind: String,
+    pub document: String,
+    pub uid: String,
+}
+
+/// Finds registrations that depend on a schema.
+pub struct SchemaUsageIndex {
+    actions: Arc<dyn RegisteredEntities>,
+    resources: Arc<dyn RegisteredEntities>,
+}
+
+impl SchemaUsageIndex {
+    pub fn new(actions: Arc<dyn RegisteredEntities>, resources: Arc<dyn RegisteredEntities>) -> Self {
+        SchemaUsageIndex { actions, resources }
+    }
+
+    /// Returns the registrations for the schema that the new catalog would orphan.
+    pub async fn orphans(&self, schema: &str, catalog: &SchemaCatalog) -> Vec<OrphanedRegistration> {
+        let mut orphans = Vec::new();
+        for (document, uids) in self.actions.registered_entities(schema).await {
+            for uid in uids.iter().filter(|uid| catalog.check_action(uid).is_err()) {
+                orphans.push(OrphanedRegistration {
+                    kind: "action_set".to_string(),
+                    document: document.clone(),
+                    uid: uid.to_string(),
+                });
+            }
+        }
+        for (document, uids) in self.resources.registered_entities(schema).await {
+            for uid in uids.iter().filter(|uid| catalog.check_entity(uid).is_err()) {
+                orphans.push(OrphanedRegistration {
+                    kind: "resource_set".to_string(),
+                    document: document.clone(),
+                    uid: uid.to_string(),
+                });
+            }
+        }
+        orphans.sort_by(|a, b| (&a.kind, &a.document, &a.uid).cmp(&(&b.kind, &b.document, &b.uid)));
+        orphans
+    }
+}
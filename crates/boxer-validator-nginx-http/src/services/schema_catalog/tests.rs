@@ -0,0 +1,117 @@
+use super::*;
+use async_trait::async_trait;
+use serde_json::json;
+use std::collections::HashMap;
+use std::str::FromStr;
+use test_case::test_case;
+
+fn schema() -> Value {
+    json!({
+        "PhotoApp": {
+            "entityTypes": {
+                "User": {},
+                "Photo": {}
+            },
+            "actions": {
+                "viewPhoto": {}
+            }
+        }
+    })
+}
+
+fn uid(value: &str) -> EntityUid {
+    EntityUid::from_str(value).unwrap()
+}
+
+#[test_case("PhotoApp::Action::\"viewPhoto\"" => true; "declared action")]
+#[test_case("PhotoApp::Action::\"deletePhoto\"" => false; "unknown action")]
+#[test_case("OtherApp::Action::\"viewPhoto\"" => false; "action from another namespace")]
+fn test_check_action(value: &str) -> bool {
+    let catalog = SchemaCatalog::from_json(&schema()).unwrap();
+    catalog.check_action(&uid(value)).is_ok()
+}
+
+#[test_case("PhotoApp::Photo::\"vacation.jpg\"" => true; "declared entity type")]
+#[test_case("PhotoApp::Album::\"vacation\"" => false; "unknown entity type")]
+fn test_check_entity(value: &str) -> bool {
+    let catalog = SchemaCatalog::from_json(&schema()).unwrap();
+    catalog.check_entity(&uid(value)).is_ok()
+}
+
+#[test]
+fn test_schema_must_be_an_object() {
+    assert!(SchemaCatalog::from_json(&json!(["PhotoApp"])).is_err());
+}
+
+struct StaticEntities(HashMap<String, Vec<EntityUid>>);
+
+#[async_trait]
+impl RegisteredEntities for StaticEntities {
+    async fn registered_entities(&self, schema: &str) -> HashMap<String, Vec<EntityUid>> {
+        if schema == "photo-app" {
+            self.0.clone()
+        } else {
+            HashMap::new()
+        }
+    }
+}
+
+#[tokio::test]
+async fn test_orphans_are_reported_for_removed_declarations() {
+    let actions = StaticEntities(HashMap::from([(
+        "photo-app-actions".to_string(),
+        vec![
+            uid("PhotoApp::Action::\"viewPhoto\""),
+            uid("PhotoApp::Action::\"deletePhoto\""),
+        ],
+    )]));
+    let resources = StaticEntities(HashMap::from([(
+        "photo-app-resources".to_string(),
+        vec![uid("PhotoApp::Photo::\"vacation.jpg\"")],
+    )]));
+    let index = SchemaUsageIndex::new(Arc::new(actions), Arc::new(resources));
+    let catalog = SchemaCatalog::from_json(&schema()).unwrap();
+
+    let orphans = index.orphans("photo-app", &catalog).await;
+
+    assert_eq!(
+        orphans,
+        vec![OrphanedRegistration {
+            kind: "action_set".to_string(),
+            document: "photo-app-actions".to_string(),
+            uid: "PhotoApp::Action::\"deletePhoto\"".to_string(),
+        }]
+    );
+    assert!(index.orphans("other", &catalog).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_an_empty_catalog_orphans_every_registration() {
+    let actions = StaticEntities(HashMap::from([(
+        "photo-app-actions".to_string(),
+        vec![uid("PhotoApp::Action::\"viewPhoto\"")],
+    )]));
+    let resources = StaticEntities(HashMap::from([(
+        "photo-app-resources".to_string(),
+        vec![uid("PhotoApp::Photo::\"vacation.jpg\"")],
+    )]));
+    let index = SchemaUsageIndex::new(Arc::new(actions), Arc::new(resources));
+
+    let orphans = index.orphans("photo-app", &SchemaCatalog::default()).await;
+
+    assert_eq!(
+        orphans,
+        vec![
+            OrphanedRegistration {
+                kind: "action_set".to_string(),
+                document: "photo-app-actions".to_string(),
+                uid: "PhotoApp::Action::\"viewPhoto\"".to_string(),
+            },
+            OrphanedRegistration {
+                kind: "resource_set".to_string(),
+                document: "photo-app-resources".to_string(),
+                uid: "PhotoApp::Photo::\"vacation.jpg\"".to_string(),
+            },
+        ]
+    );
+}
@@ -1,3 +1,4 @@
+use crate::services::review_stages::{ReviewStage, measure_stage};
 use anyhow::{Error, Result};
 use async_trait::async_trait;
 use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
@@ -6,29 +7,46 @@ use boxer_core::services::validation_service::required_claims::RequiredClaims;
 use boxer_core::services::validation_service::schema_provider::SchemaProvider;
 use cedar_policy::Schema;
 use log::debug;
+use opentelemetry::metrics::{Histogram, Meter};
 use std::sync::Arc;
+use std::time::Instant;
 
 pub struct KubernetesSchemaProvider {
     schema_repository: Arc<SchemaRepository>,
+    merge_duration: Histogram<f64>,
 }
 
 #[async_trait]
 impl SchemaProvider<BoxerClaims> for KubernetesSchemaProvider {
     async fn get_schema(&self, boxer_claims: &BoxerClaims) -> Result<Schema> {
-        let actions_schema = self
-            .schema_repository
-            .get(boxer_claims.get_validator_schema_id().clone())
-            .await
-            .map_err(Error::from)?;
-        let principal_schema = boxer_claims.get_schema().clone();
-        debug!("Kubernetes schema actions: {:?}", actions_schema.to_json_string());
-        debug!("Kubernetes schema principal: {:?}", principal_schema.to_json_string());
-        Schema::from_schema_fragments(vec![actions_schema, principal_schema]).map_err(anyhow::Error::from)
+        measure_stage(ReviewStage::Schema, async {
+            let actions_schema = self
+                .schema_repository
+                .get(boxer_claims.get_validator_schema_id().clone())
+                .await
+                .map_err(Error::from)?;
+            let principal_schema = boxer_claims.get_schema().clone();
+            debug!("Kubernetes schema actions: {:?}", actions_schema.to_json_string());
+            debug!("Kubernetes schema principal: {:?}", principal_schema.to_json_string());
+            let started = Instant::now();
+            let schema =
+                Schema::from_schema_fragments(vec![actions_schema, principal_schema]).map_err(anyhow::Error::from);
+            self.merge_duration.record(started.elapsed().as_secs_f64(), &[]);
+            schema
+        })
+        .await
     }
 }
 
 impl KubernetesSchemaProvider {
-    pub fn new(schema_repository: Arc<SchemaRepository>) -> Self {
-        KubernetesSchemaProvider { schema_repository }
+    pub fn new(schema_repository: Arc<SchemaRepository>, meter: &Meter) -> Self {
+        KubernetesSchemaProvider {
+            schema_repository,
+            merge_duration: meter
+                .f64_histogram("schema_merge_duration")
+                .with_unit("s")
+                .with_description("Latency of merging the validator schema with the principal schema of a token")
+                .build(),
+        }
     }
 }
@@ -0,0 +1,105 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::decision_log::Decision;
+use crate::services::repositories::policy_repository::read_only::ShadowPolicyRepository;
+use anyhow::Result;
+use boxer_core::contracts::internal_token::v2::boxer_claims::BoxerClaims;
+use boxer_core::services::audit::chained::audit_event::AuditEvent;
+use boxer_core::services::validation_service::ValidationService;
+use boxer_core::services::validation_service::request_context::RequestContext;
+use log::info;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Meter};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+/// The would-be decision of the shadow policies for a token review.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ShadowDecision {
+    pub decision: Decision,
+    /// True if the shadow policies disagree with the enforced decision.
+    pub diverged: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+impl ShadowDecision {
+    pub fn new(enforced: &Result<()>, shadow: &Result<()>) -> Self {
+        ShadowDecision {
+            decision: if shadow.is_ok() {
+                Decision::Allow
+            } else {
+                Decision::Deny
+            },
+            diverged: enforced.is_ok() != shadow.is_ok(),
+            reason: shadow.as_ref().err().map(|e| e.to_string()),
+        }
+    }
+}
+
+/// Evaluates the enforced policies together with the policy sets registered with `mode: shadow`,
+/// so a shadow decision shows the outcome of rolling the shadow policies out.
+/// Shadow decisions are only recorded and never change the token review response.
+pub struct ShadowEvaluator {
+    policies: Arc<ShadowPolicyRepository>,
+    validation_service: Arc<dyn ValidationService<BoxerClaims>>,
+    decisions: Counter<u64>,
+}
+
+impl ShadowEvaluator {
+    /// The validation service must read its policies from the shadow policy repository.
+    pub fn new(
+        policies: Arc<ShadowPolicyRepository>,
+        validation_service: Arc<dyn ValidationService<BoxerClaims>>,
+        meter: &Meter,
+    ) -> Self {
+        ShadowEvaluator {
+            policies,
+            validation_service,
+            decisions: meter
+                .u64_counter("shadow_decisions")
+                .with_description("Token reviews evaluated against shadow policies, by schema, decision and divergence")
+                .build(),
+        }
+    }
+
+    /// Returns `None` if no shadow policies are registered for the schema.
+    /// The audit event is a copy, so shadow evaluation does not leak into the enforced audit record.
+    pub async fn evaluate(
+        &self,
+        schema: &str,
+        boxer_claims: BoxerClaims,
+        request_context: RequestContext,
+        mut event: AuditEvent,
+    ) -> Option<Result<()>> {
+        if !self.policies.contains(schema).await {
+            return None;
+        }
+        Some(
+            self.validation_service
+                .validate(boxer_claims, request_context, &mut event)
+                .await,
+        )
+    }
+
+    /// Compares the shadow result with the enforced one and reports the outcome in metrics.
+    pub fn compare(&self, schema: &str, enforced: &Result<()>, shadow: &Result<()>) -> ShadowDecision {
+        let decision = ShadowDecision::new(enforced, shadow);
+        self.decisions.add(
+            1,
+            &[
+                KeyValue::new("schema", schema.to_string()),
+                KeyValue::new("decision", decision.decision.as_str()),
+                KeyValue::new("diverged", decision.diverged),
+            ],
+        );
+        if decision.diverged {
+            info!(
+                "Shadow policies for schema {} diverge from the enforced decision: {:?}",
+                schema, decision
+            );
+        }
+        decision
+    }
+}
@@ -0,0 +1,22 @@
+use super::*;
+use anyhow::anyhow;
+use test_case::test_case;
+
+fn result(allowed: bool) -> Result<()> {
+    if allowed { Ok(()) } else { Err(anyhow!("denied")) }
+}
+
+#[test_case(true, true => (Decision::Allow, false); "both allow")]
+#[test_case(false, false => (Decision::Deny, false); "both deny")]
+#[test_case(true, false => (Decision::Deny, true); "shadow denies")]
+#[test_case(false, true => (Decision::Allow, true); "shadow allows")]
+fn test_shadow_decision(enforced: bool, shadow: bool) -> (Decision, bool) {
+    let decision = ShadowDecision::new(&result(enforced), &result(shadow));
+    (decision.decision, decision.diverged)
+}
+
+#[test]
+fn test_shadow_decision_keeps_reason() {
+    let decision = ShadowDecision::new(&Ok(()), &Err(anyhow!("No policy permits the request")));
+    assert_eq!(decision.reason.as_deref(), Some("No policy permits the request"));
+}
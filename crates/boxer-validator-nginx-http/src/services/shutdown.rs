@@ -0,0 +1,120 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::api_server::ApiServer;
+use crate::services::backends::kubernetes::ValidatorBackend;
+use crate::services::configuration::models::ShutdownSettings;
+use anyhow::{Result, anyhow};
+use futures::FutureExt;
+use futures::future::BoxFuture;
+use log::{error, info, warn};
+use std::future::Future;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::signal::unix::{SignalKind, signal};
+
+/// Resolves on SIGTERM or Ctrl-C.
+pub async fn shutdown_signal() {
+    let terminate = async {
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                terminate.recv().await;
+            }
+            Err(e) => {
+                warn!("Failed to listen for SIGTERM: {}", e);
+                std::future::pending::<()>().await
+            }
+        }
+    };
+    tokio::select! {
+        _ = tokio::signal::ctrl_c() => {}
+        _ = terminate => {}
+    }
+}
+
+type ShutdownHook = (&'static str, BoxFuture<'static, Result<()>>);
+
+/// Cleanup of services that buffer data, run once the HTTP server and the backend watchers have stopped.
+#[derive(Default)]
+pub struct ShutdownHooks {
+    hooks: Mutex<Vec<ShutdownHook>>,
+}
+
+impl ShutdownHooks {
+    /// Hooks run in reverse order of registration, so services registered first, like telemetry,
+    /// are shut down after the services that report to them.
+    pub fn register(&self, name: &'static str, hook: impl Future<Output = Result<()>> + Send + 'static) {
+        self.hooks.lock().unwrap().push((name, hook.boxed()));
+    }
+
+    /// Runs every hook, even if some of them fail, and collects their errors.
+    async fn run(&self, errors: &mut Vec<anyhow::Error>) {
+        let hooks = std::mem::take(&mut *self.hooks.lock().unwrap());
+        for (name, hook) in hooks.into_iter().rev() {
+            info!("Shutting down {}", name);
+            if let Err(e) = hook.await {
+                errors.push(e.context(format!("Failed to shut down {}", name)));
+            }
+        }
+    }
+}
+
+/// Runs the HTTP server until `shutdown` resolves, then shuts the validator down gracefully:
+/// reports not ready, waits for the drain period, stops the server, stops the backend watchers,
+/// runs the shutdown hooks and flushes the logger. Every step runs even if an earlier one fails.
+pub async fn run_until_shutdown<B>(
+    server: ApiServer,
+    backend: Arc<B>,
+    hooks: &ShutdownHooks,
+    settings: &ShutdownSettings,
+    shutdown: impl Future<Output = ()>,
+) -> Result<()>
+where
+    B: ValidatorBackend + ?Sized,
+{
+    let handle = server.handle();
+    let mut server = tokio::spawn(server);
+    let stopped = tokio::select! {
+        result = &mut server => Some(result),
+        _ = shutdown => None,
+    };
+    let result = match stopped {
+        Some(result) => result,
+        None => {
+            let drain_period: Duration = settings.drain_period.into();
+            info!("Shutdown requested, draining for {:?}", drain_period);
+            backend.readiness().begin_shutdown();
+            tokio::time::sleep(drain_period).await;
+
+            info!("Stopping HTTP server");
+            handle.stop(true).await;
+            server.await
+        }
+    };
+
+    let mut errors = Vec::new();
+    match result {
+        Ok(Ok(())) => {}
+        Ok(Err(e)) => errors.push(anyhow::Error::from(e).context("HTTP server failed")),
+        Err(e) => errors.push(anyhow!("HTTP server task failed: {}", e)),
+    }
+
+    info!("Stopping backend watchers");
+    if let Err(e) = backend.stop() {
+        errors.push(e.context("Failed to stop the backend watchers"));
+    }
+    hooks.run(&mut errors).await;
+
+    for e in &errors {
+        error!("{:#}", e);
+    }
+    log::logger().flush();
+    match errors.len() {
+        0 => Ok(()),
+        1 => Err(errors.remove(0)),
+        _ => {
+            let errors: Vec<String> = errors.iter().map(|e| format!("{:#}", e)).collect();
+            Err(anyhow!("Shutdown failed: {}", errors.join("; ")))
+        }
+    }
+}
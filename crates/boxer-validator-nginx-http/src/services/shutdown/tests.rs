@@ -0,0 +1,110 @@
+use super::*;
+use crate::services::readiness::Readiness;
+use actix_web::dev::Server;
+use actix_web::{App, HttpResponse, HttpServer, web};
+use anyhow::bail;
+use boxer_core::services::backends::Backend;
+
+type Steps = Arc<Mutex<Vec<&'static str>>>;
+
+struct FakeBackend {
+    readiness: Arc<Readiness>,
+    steps: Steps,
+    stop_error: Option<&'static str>,
+}
+
+impl Backend for FakeBackend {}
+
+impl ValidatorBackend for FakeBackend {
+    fn readiness(&self) -> Arc<Readiness> {
+        self.readiness.clone()
+    }
+
+    fn stop(&self) -> Result<()> {
+        self.steps.lock().unwrap().push("backend");
+        match self.stop_error {
+            Some(error) => bail!(error),
+            None => Ok(()),
+        }
+    }
+}
+
+fn backend(steps: &Steps, stop_error: Option<&'static str>) -> Arc<FakeBackend> {
+    Arc::new(FakeBackend {
+        readiness: Arc::new(Readiness::new(Duration::from_secs(60))),
+        steps: steps.clone(),
+        stop_error,
+    })
+}
+
+/// Registers a hook that records when it runs, and fails with `error` if set.
+fn register(hooks: &ShutdownHooks, steps: &Steps, name: &'static str, error: Option<&'static str>) {
+    let steps = steps.clone();
+    hooks.register(name, async move {
+        steps.lock().unwrap().push(name);
+        match error {
+            Some(error) => bail!(error),
+            None => Ok(()),
+        }
+    });
+}
+
+fn server() -> Server {
+    HttpServer::new(|| App::new().route("/", web::get().to(HttpResponse::Ok)))
+        .disable_signals()
+        .workers(1)
+        .bind(("127.0.0.1", 0))
+        .unwrap()
+        .run()
+}
+
+fn settings() -> ShutdownSettings {
+    ShutdownSettings {
+        drain_period: Duration::ZERO.into(),
+        timeout: Duration::from_secs(1).into(),
+    }
+}
+
+#[actix_web::test]
+async fn test_shutdown_drains_and_stops_everything_in_order() {
+    let steps = Steps::default();
+    let backend = backend(&steps, None);
+    let hooks = ShutdownHooks::default();
+    register(&hooks, &steps, "telemetry", None);
+    let readiness = backend.readiness.clone();
+    let drained = Arc::new(Mutex::new(None));
+    hooks.register("decision_log", {
+        let steps = steps.clone();
+        let drained = drained.clone();
+        async move {
+            steps.lock().unwrap().push("decision_log");
+            *drained.lock().unwrap() = Some(readiness.report().shutting_down);
+            Ok(())
+        }
+    });
+
+    run_until_shutdown(server().into(), backend, &hooks, &settings(), async {})
+        .await
+        .unwrap();
+
+    assert_eq!(*drained.lock().unwrap(), Some(true));
+    assert_eq!(*steps.lock().unwrap(), vec!["backend", "decision_log", "telemetry"]);
+}
+
+#[actix_web::test]
+async fn test_shutdown_runs_every_step_and_collects_errors() {
+    let steps = Steps::default();
+    let backend = backend(&steps, Some("watchers are stuck"));
+    let hooks = ShutdownHooks::default();
+    register(&hooks, &steps, "telemetry", None);
+    register(&hooks, &steps, "decision_log", Some("webhook is unreachable"));
+
+    let error = run_until_shutdown(server().into(), backend, &hooks, &settings(), async {})
+        .await
+        .unwrap_err()
+        .to_string();
+
+    assert_eq!(*steps.lock().unwrap(), vec!["backend", "decision_log", "telemetry"]);
+    assert!(error.contains("watchers are stuck"), "{}", error);
+    assert!(error.contains("webhook is unreachable"), "{}", error);
+}
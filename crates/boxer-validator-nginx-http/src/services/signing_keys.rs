@@ -0,0 +1,402 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::{SigningKeySettings, SigningKeySource};
+use crate::services::readiness::WatcherStatus;
+use anyhow::{Result, anyhow, bail};
+use base64::Engine;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use boxer_core::services::token_decryption_service::encryption_keys::EncryptionKeys;
+use futures::StreamExt;
+use k8s_openapi::ByteString;
+use k8s_openapi::api::core::v1::Secret;
+use k8s_openapi::apimachinery::pkg::apis::meta::v1::ObjectMeta;
+use k8s_openapi::chrono::{DateTime, Utc};
+use kube::api::{Patch, PatchParams};
+use kube::runtime::watcher::Event;
+use kube::runtime::{WatchStreamExt, watcher};
+use kube::{Api, Client};
+use log::{info, warn};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+
+/// Key ID of `token_settings.keys` when it holds a single set of keys rather than a key set.
+pub const DEFAULT_KEY_ID: &str = "default";
+
+/// Signing keys by key ID, and the key ID used for tokens that do not name one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeySet {
+    pub primary: String,
+    pub keys: BTreeMap<String, Value>,
+}
+
+impl KeySet {
+    /// Parses a key set, or a single set of keys in the format of `token_settings.keys` as the `default` key.
+    pub fn parse(content: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(content)?;
+        let key_set = serde_json::from_value::<KeySet>(value.clone()).unwrap_or_else(|_| KeySet {
+            primary: DEFAULT_KEY_ID.to_string(),
+            keys: BTreeMap::from([(DEFAULT_KEY_ID.to_string(), value)]),
+        });
+        if !key_set.keys.contains_key(&key_set.primary) {
+            bail!("Primary signing key {} is not in the key set", key_set.primary);
+        }
+        for (key_id, keys) in &key_set.keys {
+            serde_json::from_value::<EncryptionKeys>(keys.clone())
+                .map_err(|e| anyhow!("Invalid signing key {}: {}", key_id, e))?;
+        }
+        Ok(key_set)
+    }
+}
+
+/// The keys accepted for internal tokens at a point in time.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AcceptedKeys {
+    pub primary: String,
+    pub keys: BTreeMap<String, Value>,
+}
+
+/// A key removed from the key set, accepted until its grace period ends.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetiredKey {
+    pub keys: Value,
+    pub retired_at: DateTime<Utc>,
+}
+
+/// The current key set and the retired keys, in the form saved across restarts.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KeyRingState {
+    pub current: KeySet,
+    #[serde(default)]
+    pub retired: BTreeMap<String, RetiredKey>,
+}
+
+/// The current key set, and the keys removed from it until their grace period ends.
+pub struct KeyRing {
+    state: KeyRingState,
+    grace_period: Duration,
+}
+
+impl KeyRing {
+    pub fn new(key_set: KeySet, grace_period: Duration) -> Self {
+        KeyRing {
+            state: KeyRingState {
+                current: key_set,
+                retired: BTreeMap::new(),
+            },
+            grace_period,
+        }
+    }
+
+    /// Continues from a saved state. Keys of the saved key set that `key_set` no longer contains are retired now.
+    pub fn restore(state: KeyRingState, key_set: KeySet, grace_period: Duration, now: DateTime<Utc>) -> Self {
+        let mut ring = KeyRing { state, grace_period };
+        ring.update(key_set, now);
+        ring.expire(now);
+        ring
+    }
+
+    /// Replaces the key set, retiring the keys it no longer contains. Returns true if the accepted keys changed.
+    pub fn update(&mut self, key_set: KeySet, now: DateTime<Utc>) -> bool {
+        if key_set == self.state.current {
+            return false;
+        }
+        for (key_id, keys) in &self.state.current.keys {
+            if !key_set.keys.contains_key(key_id) {
+                let retired = RetiredKey {
+                    keys: keys.clone(),
+                    retired_at: now,
+                };
+                self.state.retired.insert(key_id.clone(), retired);
+            }
+        }
+        self.state
+            .retired
+            .retain(|key_id, _| !key_set.keys.contains_key(key_id));
+        self.state.current = key_set;
+        true
+    }
+
+    /// Drops the retired keys whose grace period has ended. Returns true if any key was dropped.
+    pub fn expire(&mut self, now: DateTime<Utc>) -> bool {
+        let retired = self.state.retired.len();
+        let grace_period = self.grace_period;
+        self.state.retired.retain(|_, key| {
+            // A retirement time in the future, e.g. after a clock change, counts as retired just now
+            (now - key.retired_at).to_std().unwrap_or_default() < grace_period
+        });
+        self.state.retired.len() != retired
+    }
+
+    pub fn state(&self) -> &KeyRingState {
+        &self.state
+    }
+
+    pub fn accepted(&self) -> AcceptedKeys {
+        let mut keys = self.state.current.keys.clone();
+        for (key_id, retired) in &self.state.retired {
+            keys.insert(key_id.clone(), retired.keys.clone());
+        }
+        AcceptedKeys {
+            primary: self.state.current.primary.clone(),
+            keys,
+        }
+    }
+}
+
+/// The `kid` from the protected header of a JWS or JWE token, if it names one.
+pub fn key_id(token: &str) -> Option<String> {
+    let header = token.split('.').next()?;
+    let header = URL_SAFE_NO_PAD.decode(header.trim_end_matches('=')).ok()?;
+    serde_json::from_slice::<Value>(&header)
+        .ok()?
+        .get("kid")?
+        .as_str()
+        .map(str::to_string)
+}
+
+fn secret_entry(secret: &Secret, key: &str) -> Result<String> {
+    let value = secret
+        .data
+        .as_ref()
+        .and_then(|data| data.get(key))
+        .ok_or_else(|| anyhow!("Secret has no entry {}", key))?;
+    Ok(String::from_utf8(value.0.clone())?)
+}
+
+/// Entry of the key ring Secret holding the saved [`KeyRingState`].
+const KEY_RING_ENTRY: &str = "key-ring.json";
+
+/// Saves the key ring to a Secret, so that retired keys stay accepted across restarts.
+pub struct KeyRingStore {
+    api: Api<Secret>,
+    name: String,
+}
+
+impl KeyRingStore {
+    pub fn new(client: Client, namespace: &str, name: &str) -> Self {
+        KeyRingStore {
+            api: Api::namespaced(client, namespace),
+            name: name.to_string(),
+        }
+    }
+
+    /// The saved state, or `None` if nothing was saved yet.
+    pub async fn load(&self) -> Result<Option<KeyRingState>> {
+        let Some(secret) = self.api.get_opt(&self.name).await? else {
+            return Ok(None);
+        };
+        match secret.data.as_ref().and_then(|data| data.get(KEY_RING_ENTRY)) {
+            Some(content) => Ok(Some(serde_json::from_slice(&content.0)?)),
+            None => Ok(None),
+        }
+    }
+
+    /// Creates or replaces the Secret with server-side apply.
+    pub async fn save(&self, state: &KeyRingState) -> Result<()> {
+        let secret = Secret {
+            metadata: ObjectMeta {
+                name: Some(self.name.clone()),
+                ..Default::default()
+            },
+            data: Some(BTreeMap::from([(
+                KEY_RING_ENTRY.to_string(),
+                ByteString(serde_json::to_vec(state)?),
+            )])),
+            ..Default::default()
+        };
+        self.api
+            .patch(
+                &self.name,
+                &PatchParams::apply("boxer-validator").force(),
+                &Patch::Apply(&secret),
+            )
+            .await?;
+        Ok(())
+    }
+}
+
+/// Loads the signing keys from their source and keeps them up to date in the background.
+pub struct SigningKeyWatch {
+    keys: watch::Receiver<AcceptedKeys>,
+    task: JoinHandle<()>,
+}
+
+/// Owned by the background task: the key ring, where it is saved and who is told about changes.
+struct KeyRingUpdates {
+    ring: KeyRing,
+    store: Option<KeyRingStore>,
+    sender: watch::Sender<AcceptedKeys>,
+}
+
+impl KeyRingUpdates {
+    /// Restores the key ring from the store, if there is one, and saves it with the current key set.
+    async fn start(
+        key_set: KeySet,
+        grace_period: Duration,
+        store: Option<KeyRingStore>,
+    ) -> Result<(Self, watch::Receiver<AcceptedKeys>)> {
+        let ring = match &store {
+            Some(store) => match store.load().await? {
+                Some(state) => KeyRing::restore(state, key_set, grace_period, Utc::now()),
+                None => KeyRing::new(key_set, grace_period),
+            },
+            None => KeyRing::new(key_set, grace_period),
+        };
+        let (sender, keys) = watch::channel(ring.accepted());
+        let updates = KeyRingUpdates { ring, store, sender };
+        updates.save().await;
+        Ok((updates, keys))
+    }
+
+    async fn apply(&mut self, key_set: Result<KeySet>) {
+        match key_set {
+            Ok(key_set) => {
+                if self.ring.update(key_set, Utc::now()) {
+                    self.publish().await;
+                }
+            }
+            Err(e) => warn!("Ignoring invalid signing keys, keeping the current keys: {}", e),
+        }
+    }
+
+    async fn expire(&mut self) {
+        if self.ring.expire(Utc::now()) {
+            self.publish().await;
+        }
+    }
+
+    async fn publish(&self) {
+        let accepted = self.ring.accepted();
+        info!(
+            "Signing keys changed, accepting {:?} with primary key {}",
+            accepted.keys.keys().collect::<Vec<_>>(),
+            accepted.primary
+        );
+        self.sender.send_replace(accepted);
+        self.save().await;
+    }
+
+    async fn save(&self) {
+        if let Some(store) = &self.store
+            && let Err(e) = store.save(self.ring.state()).await
+        {
+            warn!("Failed to save the signing key ring: {}", e);
+        }
+    }
+}
+
+impl SigningKeyWatch {
+    /// `inline_keys` are the keys from `token_settings.keys`, used by the inline source.
+    /// Secrets are read from and saved to `namespace`.
+    pub async fn start(
+        settings: &SigningKeySettings,
+        inline_keys: &str,
+        client: Client,
+        namespace: &str,
+        status: Arc<WatcherStatus>,
+    ) -> Result<Self> {
+        let grace_period: Duration = settings.grace_period.into();
+        let store = settings
+            .key_ring_secret
+            .as_ref()
+            .map(|name| KeyRingStore::new(client.clone(), namespace, name));
+        let mut expiry = tokio::time::interval(grace_period.min(Duration::from_secs(60)));
+        match &settings.source {
+            SigningKeySource::Inline => {
+                let (mut updates, keys) =
+                    KeyRingUpdates::start(KeySet::parse(inline_keys)?, grace_period, store).await?;
+                status.mark_synced();
+                let task = tokio::spawn(async move {
+                    loop {
+                        expiry.tick().await;
+                        updates.expire().await;
+                    }
+                });
+                Ok(SigningKeyWatch { keys, task })
+            }
+            SigningKeySource::File { path, poll_interval } => {
+                let mut content = tokio::fs::read_to_string(path).await?;
+                let (mut updates, keys) = KeyRingUpdates::start(KeySet::parse(&content)?, grace_period, store).await?;
+                status.mark_synced();
+                let path = path.clone();
+                let mut interval = tokio::time::interval((*poll_interval).into());
+                let task = tokio::spawn(async move {
+                    loop {
+                        tokio::select! {
+                            _ = interval.tick() => match tokio::fs::read_to_string(&path).await {
+                                Ok(next) if next != content => {
+                                    content = next;
+                                    updates.apply(KeySet::parse(&content)).await;
+                                }
+                                Ok(_) => {}
+                                Err(e) => warn!("Failed to read signing keys from {}: {}", path, e),
+                            },
+                            _ = expiry.tick() => updates.expire().await,
+                        }
+                    }
+                });
+                Ok(SigningKeyWatch { keys, task })
+            }
+            SigningKeySource::Secret { name, key } => {
+                let api: Api<Secret> = Api::namespaced(client, namespace);
+                let secret = api.get(name).await?;
+                let key_set = KeySet::parse(&secret_entry(&secret, key)?)?;
+                let (mut updates, keys) = KeyRingUpdates::start(key_set, grace_period, store).await?;
+                let watcher_config = watcher::Config::default().fields(&format!("metadata.name={}", name));
+                let stream = watcher(api, watcher_config).default_backoff();
+                let key = key.clone();
+                let task = tokio::spawn(async move {
+                    let _running = status.running();
+                    let mut stream = std::pin::pin!(stream);
+                    loop {
+                        tokio::select! {
+                            event = stream.next() => match event {
+                                Some(Ok(event)) => {
+                                    let init_done = matches!(event, Event::InitDone);
+                                    match event {
+                                        Event::Apply(secret) | Event::InitApply(secret) => {
+                                            let key_set = secret_entry(&secret, &key).and_then(|content| KeySet::parse(&content));
+                                            updates.apply(key_set).await;
+                                        }
+                                        Event::Delete(_) => warn!("Signing key Secret was deleted, keeping the current keys"),
+                                        Event::Init | Event::InitDone => {}
+                                    }
+                                    status.applied(init_done);
+                                }
+                                Some(Err(err)) => {
+                                    status.failed(&err);
+                                    warn!("Error while watching signing keys: {:?}", err)
+                                }
+                                None => return,
+                            },
+                            _ = expiry.tick() => updates.expire().await,
+                        }
+                    }
+                });
+                Ok(SigningKeyWatch { keys, task })
+            }
+        }
+    }
+
+    /// Receives the accepted keys, updated whenever they change.
+    pub fn subscribe(&self) -> watch::Receiver<AcceptedKeys> {
+        self.keys.clone()
+    }
+
+    /// Stops watching the key source. Receivers keep the last accepted keys.
+    pub fn stop(&self) {
+        self.task.abort();
+    }
+}
+
+impl Drop for SigningKeyWatch {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
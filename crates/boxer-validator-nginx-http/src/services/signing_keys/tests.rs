@@ -0,0 +1,125 @@
+use super::*;
+use k8s_openapi::chrono::TimeDelta;
+use serde_json::json;
+use test_case::test_case;
+
+fn key_set(primary: &str, key_ids: &[&str]) -> KeySet {
+    KeySet {
+        primary: primary.to_string(),
+        keys: key_ids
+            .iter()
+            .map(|key_id| (key_id.to_string(), json!({ "public": format!("{}-key", key_id) })))
+            .collect(),
+    }
+}
+
+fn accepted_ids(ring: &KeyRing) -> Vec<String> {
+    ring.accepted().keys.into_keys().collect()
+}
+
+#[test]
+fn test_parse_single_keys_as_default_key() {
+    let key_set = KeySet::parse(r#"{"public": "0123456789ABCDEF0123456789ABCDEF"}"#).unwrap();
+
+    assert_eq!(key_set.primary, DEFAULT_KEY_ID);
+    assert_eq!(
+        key_set.keys[DEFAULT_KEY_ID],
+        json!({ "public": "0123456789ABCDEF0123456789ABCDEF" })
+    );
+}
+
+#[test]
+fn test_parse_key_set() {
+    let key_set = KeySet::parse(
+        r#"{"primary": "2026-10", "keys": {"2026-10": {"public": "new"}, "2026-07": {"public": "old"}}}"#,
+    )
+    .unwrap();
+
+    assert_eq!(key_set.primary, "2026-10");
+    assert_eq!(key_set.keys.len(), 2);
+}
+
+#[test_case(r#"{"primary": "2026-10", "keys": {"2026-07": {"public": "old"}}}"#; "missing primary key")]
+#[test_case(r#"{"primary": "2026-10", "keys": {"2026-10": 5}}"#; "invalid key")]
+#[test_case("not json"; "invalid json")]
+fn test_parse_rejects_invalid_key_set(content: &str) {
+    assert!(KeySet::parse(content).is_err());
+}
+
+#[test]
+fn test_removed_key_is_accepted_until_grace_period_ends() {
+    let start = Utc::now();
+    let mut ring = KeyRing::new(key_set("old", &["old"]), Duration::from_secs(60));
+
+    assert!(ring.update(key_set("new", &["new"]), start));
+    assert_eq!(accepted_ids(&ring), vec!["new", "old"]);
+    assert_eq!(ring.accepted().primary, "new");
+
+    assert!(!ring.expire(start + TimeDelta::seconds(30)));
+    assert!(ring.expire(start + TimeDelta::seconds(60)));
+    assert_eq!(accepted_ids(&ring), vec!["new"]);
+}
+
+#[test]
+fn test_restored_key_is_no_longer_retired() {
+    let start = Utc::now();
+    let mut ring = KeyRing::new(key_set("old", &["old"]), Duration::from_secs(60));
+    ring.update(key_set("new", &["new"]), start);
+
+    ring.update(key_set("new", &["new", "old"]), start);
+
+    assert!(!ring.expire(start + TimeDelta::seconds(60)));
+    assert_eq!(accepted_ids(&ring), vec!["new", "old"]);
+}
+
+#[test]
+fn test_unchanged_key_set_is_not_an_update() {
+    let mut ring = KeyRing::new(key_set("old", &["old"]), Duration::from_secs(60));
+
+    assert!(!ring.update(key_set("old", &["old"]), Utc::now()));
+}
+
+#[test]
+fn test_restore_keeps_retirement_times_and_retires_keys_removed_while_stopped() {
+    let start = Utc::now();
+    let mut ring = KeyRing::new(key_set("2026-07", &["2026-07"]), Duration::from_secs(60));
+    ring.update(key_set("2026-10", &["2026-10"]), start);
+    let saved: KeyRingState = serde_json::from_str(&serde_json::to_string(ring.state()).unwrap()).unwrap();
+
+    let restarted = start + TimeDelta::seconds(30);
+    let mut ring = KeyRing::restore(
+        saved,
+        key_set("2027-01", &["2027-01"]),
+        Duration::from_secs(60),
+        restarted,
+    );
+
+    assert_eq!(accepted_ids(&ring), vec!["2026-07", "2026-10", "2027-01"]);
+    assert!(ring.expire(start + TimeDelta::seconds(60)));
+    assert_eq!(accepted_ids(&ring), vec!["2026-10", "2027-01"]);
+    assert!(ring.expire(restarted + TimeDelta::seconds(60)));
+    assert_eq!(accepted_ids(&ring), vec!["2027-01"]);
+}
+
+#[test]
+fn test_restore_drops_expired_keys() {
+    let start = Utc::now();
+    let mut ring = KeyRing::new(key_set("old", &["old"]), Duration::from_secs(60));
+    ring.update(key_set("new", &["new"]), start);
+
+    let ring = KeyRing::restore(
+        ring.state().clone(),
+        key_set("new", &["new"]),
+        Duration::from_secs(60),
+        start + TimeDelta::seconds(90),
+    );
+
+    assert_eq!(accepted_ids(&ring), vec!["new"]);
+}
+
+#[test_case("eyJhbGciOiJkaXIiLCJlbmMiOiJBMjU2R0NNIiwia2lkIjoiMjAyNi0xMCJ9.a.b.c.d" => Some("2026-10".to_string()); "jwe with kid")]
+#[test_case("eyJhbGciOiJIUzI1NiJ9.e30.signature" => None; "jws without kid")]
+#[test_case("opaque" => None; "not a jwt")]
+fn test_key_id(token: &str) -> Option<String> {
+    key_id(token)
+}
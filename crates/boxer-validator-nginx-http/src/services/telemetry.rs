@@ -0,0 +1,87 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::PrometheusSettings;
+use crate::services::prometheus::PrometheusMetrics;
+use anyhow::{Result, anyhow};
+use boxer_core::services::observability::open_telemetry::settings::OpenTelemetrySettings;
+use log::info;
+use opentelemetry::global;
+use opentelemetry_sdk::metrics::SdkMeterProvider;
+use opentelemetry_sdk::propagation::TraceContextPropagator;
+use opentelemetry_sdk::trace::SdkTracerProvider;
+use std::sync::Arc;
+
+/// The OpenTelemetry providers of the validator. They are installed as the global providers,
+/// and kept so that buffered spans and metrics can be exported before the process exits.
+#[derive(Default)]
+pub struct Telemetry {
+    meter_provider: Option<SdkMeterProvider>,
+    tracer_provider: Option<SdkTracerProvider>,
+    prometheus: Option<Arc<PrometheusMetrics>>,
+}
+
+impl Telemetry {
+    /// Installs the providers enabled in the settings. Must be called from within a Tokio runtime,
+    /// before any instrument is created, since instruments bind to the global provider at creation.
+    /// Metrics are pushed over OTLP, exposed for Prometheus to scrape, or both.
+    pub fn install(settings: &OpenTelemetrySettings, prometheus: &PrometheusSettings) -> Result<Self> {
+        let mut telemetry = Telemetry::default();
+        if settings.tracing_settings.enabled {
+            info!("Tracing is enabled, starting tracer...");
+            let exporter = opentelemetry_otlp::SpanExporter::builder().with_tonic().build()?;
+            let provider = SdkTracerProvider::builder().with_batch_exporter(exporter).build();
+            global::set_text_map_propagator(TraceContextPropagator::new());
+            global::set_tracer_provider(provider.clone());
+            telemetry.tracer_provider = Some(provider);
+        }
+        let mut meter_provider = SdkMeterProvider::builder();
+        if settings.metrics_settings.enabled {
+            info!("Metrics is enabled, starting metrics...");
+            let exporter = opentelemetry_otlp::MetricExporter::builder().with_tonic().build()?;
+            meter_provider = meter_provider.with_periodic_exporter(exporter);
+        }
+        if prometheus.enabled {
+            info!("Prometheus metrics are exposed on /metrics");
+            let (metrics, exporter) = PrometheusMetrics::new()?;
+            meter_provider = meter_provider.with_reader(exporter);
+            telemetry.prometheus = Some(Arc::new(metrics));
+        }
+        if settings.metrics_settings.enabled || prometheus.enabled {
+            let meter_provider = meter_provider.build();
+            global::set_meter_provider(meter_provider.clone());
+            telemetry.meter_provider = Some(meter_provider);
+        }
+        Ok(telemetry)
+    }
+
+    /// The registry served on `/metrics`, if Prometheus is enabled.
+    pub fn prometheus(&self) -> Option<Arc<PrometheusMetrics>> {
+        self.prometheus.clone()
+    }
+
+    /// Exports everything that is still buffered and shuts both providers down, even if one of them fails.
+    pub fn shutdown(&self) -> Result<()> {
+        let results = [
+            (
+                "tracer provider",
+                self.tracer_provider.as_ref().map(SdkTracerProvider::shutdown),
+            ),
+            (
+                "meter provider",
+                self.meter_provider.as_ref().map(SdkMeterProvider::shutdown),
+            ),
+        ];
+        let errors: Vec<String> = results
+            .into_iter()
+            .filter_map(|(name, result)| match result {
+                Some(Err(e)) => Some(format!("{}: {}", name, e)),
+                _ => None,
+            })
+            .collect();
+        match errors.is_empty() {
+            true => Ok(()),
+            false => Err(anyhow!("Failed to shut down telemetry: {}", errors.join(", "))),
+        }
+    }
+}
@@ -0,0 +1,45 @@
+use super::*;
+use boxer_core::services::observability::open_telemetry::logging::settings::LogSettings;
+use boxer_core::services::observability::open_telemetry::metrics::settings::MetricsSettings;
+use boxer_core::services::observability::open_telemetry::tracing::settings::TracingSettings;
+
+fn otlp_disabled() -> OpenTelemetrySettings {
+    OpenTelemetrySettings {
+        log_settings: LogSettings { enabled: false },
+        metrics_settings: MetricsSettings { enabled: false },
+        tracing_settings: TracingSettings { enabled: false },
+    }
+}
+
+#[tokio::test]
+async fn test_prometheus_reads_from_the_installed_meter_provider() {
+    let telemetry = Telemetry::install(
+        &otlp_disabled(),
+        &PrometheusSettings {
+            enabled: true,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(telemetry.prometheus().is_some());
+    assert!(telemetry.meter_provider.is_some());
+    telemetry.shutdown().unwrap();
+}
+
+#[tokio::test]
+async fn test_nothing_is_installed_when_disabled() {
+    let telemetry = Telemetry::install(
+        &otlp_disabled(),
+        &PrometheusSettings {
+            enabled: false,
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    assert!(telemetry.prometheus().is_none());
+    assert!(telemetry.meter_provider.is_none());
+    assert!(telemetry.tracer_provider.is_none());
+    telemetry.shutdown().unwrap();
+}
@@ -0,0 +1,280 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::TlsSettings;
+use anyhow::{Result, anyhow};
+use futures::future::join_all;
+use log::{info, warn};
+use rustls::client::danger::HandshakeSignatureValid;
+use rustls::crypto::{CryptoProvider, WebPkiSupportedAlgorithms, verify_tls12_signature, verify_tls13_signature};
+use rustls::pki_types::pem::PemObject;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, UnixTime};
+use rustls::server::danger::{ClientCertVerified, ClientCertVerifier};
+use rustls::server::{ClientHello, ResolvesServerCert, WebPkiClientVerifier};
+use rustls::sign::CertifiedKey;
+use rustls::{
+    CertificateError, DigitallySignedStruct, DistinguishedName, RootCertStore, ServerConfig, SignatureScheme,
+};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+
+/// Client certificates presented on a TLS connection, leaf first.
+#[derive(Debug, Clone)]
+pub struct PeerCertificates(pub Vec<CertificateDer<'static>>);
+
+/// Parts of the API that can require client certificates from their own CA.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClientScope {
+    TokenReview,
+    AdminApi,
+}
+
+fn read_files(paths: &[&str]) -> Result<Vec<Vec<u8>>> {
+    paths
+        .iter()
+        .map(|path| std::fs::read(path).map_err(|e| anyhow!("Failed to read {}: {}", path, e)))
+        .collect()
+}
+
+async fn read_file(path: &str) -> Result<Vec<u8>> {
+    tokio::fs::read(path)
+        .await
+        .map_err(|e| anyhow!("Failed to read {}: {}", path, e))
+}
+
+/// The contents of the files, `None` for a file that cannot be read.
+async fn read_contents(paths: &[String]) -> Vec<Option<Vec<u8>>> {
+    join_all(paths.iter().map(|path| async move { tokio::fs::read(path).await.ok() })).await
+}
+
+fn load_certified_key(certificate: &[u8], private_key: &[u8], provider: &CryptoProvider) -> Result<CertifiedKey> {
+    let chain = CertificateDer::pem_slice_iter(certificate).collect::<Result<Vec<_>, _>>()?;
+    if chain.is_empty() {
+        return Err(anyhow!("No certificate found"));
+    }
+    let key = provider
+        .key_provider
+        .load_private_key(PrivateKeyDer::from_pem_slice(private_key)?)?;
+    Ok(CertifiedKey::new(chain, key))
+}
+
+/// Serves the current certificate, replaced when the certificate files change.
+#[derive(Debug)]
+struct CertificateResolver(RwLock<Arc<CertifiedKey>>);
+
+impl ResolvesServerCert for CertificateResolver {
+    fn resolve(&self, _client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        Some(self.0.read().unwrap().clone())
+    }
+}
+
+/// Verifies client certificates against a CA bundle, replaced when the bundle file changes.
+pub struct ClientCa {
+    path: String,
+    verifier: RwLock<Arc<dyn ClientCertVerifier>>,
+}
+
+impl ClientCa {
+    fn verifier(bundle: &[u8], provider: &Arc<CryptoProvider>) -> Result<Arc<dyn ClientCertVerifier>> {
+        let mut roots = RootCertStore::empty();
+        for certificate in CertificateDer::pem_slice_iter(bundle) {
+            roots.add(certificate?)?;
+        }
+        Ok(WebPkiClientVerifier::builder_with_provider(Arc::new(roots), provider.clone()).build()?)
+    }
+
+    fn load(path: &str, provider: &Arc<CryptoProvider>) -> Result<Self> {
+        let bundle = read_files(&[path])?.remove(0);
+        Ok(ClientCa {
+            path: path.to_string(),
+            verifier: RwLock::new(Self::verifier(&bundle, provider)?),
+        })
+    }
+
+    /// Returns true if the chain, leaf first, is issued by this CA.
+    pub fn verify(&self, chain: &[CertificateDer<'static>]) -> bool {
+        let Some((end_entity, intermediates)) = chain.split_first() else {
+            return false;
+        };
+        self.verify_at(end_entity, intermediates, UnixTime::now()).is_ok()
+    }
+
+    fn verify_at(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        let verifier = self.verifier.read().unwrap().clone();
+        verifier.verify_client_cert(end_entity, intermediates, now)
+    }
+}
+
+impl std::fmt::Debug for ClientCa {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClientCa").field("path", &self.path).finish()
+    }
+}
+
+/// Accepts connections without a client certificate, or with one issued by any configured client CA.
+/// Which CA a scope requires is checked per request.
+#[derive(Debug)]
+struct AnyClientCa {
+    client_cas: Vec<Arc<ClientCa>>,
+    algorithms: WebPkiSupportedAlgorithms,
+}
+
+impl ClientCertVerifier for AnyClientCa {
+    fn offer_client_auth(&self) -> bool {
+        true
+    }
+
+    fn client_auth_mandatory(&self) -> bool {
+        false
+    }
+
+    fn root_hint_subjects(&self) -> &[DistinguishedName] {
+        &[]
+    }
+
+    fn verify_client_cert(
+        &self,
+        end_entity: &CertificateDer<'_>,
+        intermediates: &[CertificateDer<'_>],
+        now: UnixTime,
+    ) -> Result<ClientCertVerified, rustls::Error> {
+        self.client_cas
+            .iter()
+            .find_map(|client_ca| client_ca.verify_at(end_entity, intermediates, now).ok())
+            .ok_or(rustls::Error::InvalidCertificate(CertificateError::UnknownIssuer))
+    }
+
+    fn verify_tls12_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls12_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn verify_tls13_signature(
+        &self,
+        message: &[u8],
+        cert: &CertificateDer<'_>,
+        dss: &DigitallySignedStruct,
+    ) -> Result<HandshakeSignatureValid, rustls::Error> {
+        verify_tls13_signature(message, cert, dss, &self.algorithms)
+    }
+
+    fn supported_verify_schemes(&self) -> Vec<SignatureScheme> {
+        self.algorithms.supported_schemes()
+    }
+}
+
+/// TLS configuration of the listener, with certificates and client CAs reloaded from their files.
+pub struct Tls {
+    settings: TlsSettings,
+    provider: Arc<CryptoProvider>,
+    certificate: Arc<CertificateResolver>,
+    token_review_ca: Option<Arc<ClientCa>>,
+    admin_api_ca: Option<Arc<ClientCa>>,
+}
+
+impl Tls {
+    pub fn load(settings: &TlsSettings) -> Result<Self> {
+        let provider = Arc::new(rustls::crypto::ring::default_provider());
+        let files = read_files(&[&settings.certificate, &settings.private_key])?;
+        let certified_key = load_certified_key(&files[0], &files[1], &provider)?;
+        let client_ca = |path: &Option<String>| -> Result<Option<Arc<ClientCa>>> {
+            path.as_deref()
+                .map(|path| ClientCa::load(path, &provider).map(Arc::new))
+                .transpose()
+        };
+        Ok(Tls {
+            settings: settings.clone(),
+            token_review_ca: client_ca(&settings.client_ca.token_review)?,
+            admin_api_ca: client_ca(&settings.client_ca.admin_api)?,
+            certificate: Arc::new(CertificateResolver(RwLock::new(Arc::new(certified_key)))),
+            provider,
+        })
+    }
+
+    /// The CA that must have issued the client certificate for the scope, if the scope requires one.
+    pub fn client_ca(&self, scope: ClientScope) -> Option<Arc<ClientCa>> {
+        match scope {
+            ClientScope::TokenReview => self.token_review_ca.clone(),
+            ClientScope::AdminApi => self.admin_api_ca.clone(),
+        }
+    }
+
+    fn client_cas(&self) -> Vec<Arc<ClientCa>> {
+        self.token_review_ca
+            .iter()
+            .chain(self.admin_api_ca.iter())
+            .cloned()
+            .collect()
+    }
+
+    pub fn server_config(&self) -> Result<ServerConfig> {
+        let builder =
+            ServerConfig::builder_with_provider(self.provider.clone()).with_safe_default_protocol_versions()?;
+        let client_cas = self.client_cas();
+        let builder = if client_cas.is_empty() {
+            builder.with_no_client_auth()
+        } else {
+            builder.with_client_cert_verifier(Arc::new(AnyClientCa {
+                client_cas,
+                algorithms: self.provider.signature_verification_algorithms,
+            }))
+        };
+        Ok(builder.with_cert_resolver(self.certificate.clone()))
+    }
+
+    /// Reloads the certificate and client CAs from their files. A file that cannot be loaded keeps the current value.
+    pub async fn reload(&self) {
+        let certified_key = async {
+            let certificate = read_file(&self.settings.certificate).await?;
+            let private_key = read_file(&self.settings.private_key).await?;
+            load_certified_key(&certificate, &private_key, &self.provider)
+        };
+        match certified_key.await {
+            Ok(certified_key) => *self.certificate.0.write().unwrap() = Arc::new(certified_key),
+            Err(e) => warn!("Failed to reload the TLS certificate, keeping the current one: {}", e),
+        }
+        for client_ca in self.client_cas() {
+            match read_file(&client_ca.path)
+                .await
+                .and_then(|bundle| ClientCa::verifier(&bundle, &self.provider))
+            {
+                Ok(verifier) => *client_ca.verifier.write().unwrap() = verifier,
+                Err(e) => warn!(
+                    "Failed to reload the client CA {}, keeping the current one: {}",
+                    client_ca.path, e
+                ),
+            }
+        }
+    }
+
+    /// Reloads the files in the background whenever one of them changes, until the returned task is aborted.
+    pub fn watch(self: &Arc<Self>) -> JoinHandle<()> {
+        let tls = self.clone();
+        let reload_interval: Duration = self.settings.reload_interval.into();
+        tokio::spawn(async move {
+            let mut paths = vec![tls.settings.certificate.clone(), tls.settings.private_key.clone()];
+            paths.extend(tls.client_cas().iter().map(|client_ca| client_ca.path.clone()));
+            let mut contents = read_contents(&paths).await;
+            let mut interval = tokio::time::interval(reload_interval);
+            loop {
+                interval.tick().await;
+                let next = read_contents(&paths).await;
+                if next != contents {
+                    contents = next;
+                    info!("TLS files changed, reloading");
+                    tls.reload().await;
+                }
+            }
+        })
+    }
+}
@@ -0,0 +1,122 @@
+use super::*;
+use crate::services::configuration::models::ClientCaSettings;
+use std::path::PathBuf;
+
+fn testdata(name: &str) -> String {
+    format!("{}/src/services/tls/testdata/{}", env!("CARGO_MANIFEST_DIR"), name)
+}
+
+fn settings(token_review: Option<String>, admin_api: Option<String>) -> TlsSettings {
+    TlsSettings {
+        certificate: testdata("server.pem"),
+        private_key: testdata("server.key"),
+        client_ca: ClientCaSettings {
+            token_review,
+            admin_api,
+        },
+        reload_interval: Duration::from_secs(60).into(),
+    }
+}
+
+fn chain(name: &str) -> Vec<CertificateDer<'static>> {
+    CertificateDer::pem_file_iter(testdata(name))
+        .unwrap()
+        .collect::<Result<_, _>>()
+        .unwrap()
+}
+
+fn scratch_copy(name: &str, test: &str) -> String {
+    let dir: PathBuf = std::env::temp_dir().join(format!("boxer-tls-{}-{}", test, std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join(name);
+    std::fs::copy(testdata(name), &path).unwrap();
+    path.to_string_lossy().into_owned()
+}
+
+#[test]
+fn test_server_config_without_client_cas() {
+    let tls = Tls::load(&settings(None, None)).unwrap();
+
+    assert!(tls.server_config().is_ok());
+    assert!(tls.client_ca(ClientScope::TokenReview).is_none());
+    assert!(tls.client_ca(ClientScope::AdminApi).is_none());
+}
+
+#[test]
+fn test_missing_private_key_is_rejected() {
+    let settings = TlsSettings {
+        private_key: testdata("missing.key"),
+        ..settings(None, None)
+    };
+
+    assert!(Tls::load(&settings).is_err());
+}
+
+#[test]
+fn test_client_ca_per_scope() {
+    let tls = Tls::load(&settings(
+        Some(testdata("ingress-ca.pem")),
+        Some(testdata("operators-ca.pem")),
+    ))
+    .unwrap();
+    let token_review = tls.client_ca(ClientScope::TokenReview).unwrap();
+    let admin_api = tls.client_ca(ClientScope::AdminApi).unwrap();
+
+    assert!(tls.server_config().is_ok());
+    assert!(token_review.verify(&chain("ingress-client.pem")));
+    assert!(!token_review.verify(&chain("operator-client.pem")));
+    assert!(admin_api.verify(&chain("operator-client.pem")));
+    assert!(!admin_api.verify(&chain("ingress-client.pem")));
+    assert!(!admin_api.verify(&[]));
+}
+
+#[test]
+fn test_handshake_accepts_any_client_ca() {
+    let tls = Tls::load(&settings(
+        Some(testdata("ingress-ca.pem")),
+        Some(testdata("operators-ca.pem")),
+    ))
+    .unwrap();
+    let verifier = AnyClientCa {
+        client_cas: tls.client_cas(),
+        algorithms: tls.provider.signature_verification_algorithms,
+    };
+    let verify = |name: &str| {
+        verifier
+            .verify_client_cert(&chain(name)[0], &[], UnixTime::now())
+            .is_ok()
+    };
+
+    assert!(verify("ingress-client.pem"));
+    assert!(verify("operator-client.pem"));
+    assert!(!verify("server.pem"));
+    assert!(!verifier.client_auth_mandatory());
+}
+
+#[tokio::test]
+async fn test_reload_replaces_client_ca() {
+    let ca_path = scratch_copy("operators-ca.pem", "reload");
+    let tls = Tls::load(&settings(Some(ca_path.clone()), None)).unwrap();
+    let token_review = tls.client_ca(ClientScope::TokenReview).unwrap();
+    assert!(!token_review.verify(&chain("ingress-client.pem")));
+
+    std::fs::copy(testdata("ingress-ca.pem"), &ca_path).unwrap();
+    tls.reload().await;
+
+    assert!(token_review.verify(&chain("ingress-client.pem")));
+}
+
+#[tokio::test]
+async fn test_reload_keeps_client_ca_when_the_file_is_invalid() {
+    let ca_path = scratch_copy("ingress-ca.pem", "invalid");
+    let tls = Tls::load(&settings(Some(ca_path.clone()), None)).unwrap();
+
+    std::fs::write(&ca_path, "not a certificate").unwrap();
+    tls.reload().await;
+
+    assert!(
+        tls.client_ca(ClientScope::TokenReview)
+            .unwrap()
+            .verify(&chain("ingress-client.pem"))
+    );
+}
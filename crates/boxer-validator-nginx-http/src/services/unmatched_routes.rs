@@ -0,0 +1,230 @@
+#[cfg(test)]
+mod tests;
+
+use crate::services::configuration::models::{UnmatchedRouteBehavior, UnmatchedRouteSettings};
+use crate::services::repositories::lookup_trie::backend::AssociatedRepository;
+use crate::services::route_template::normalize_hostname;
+use anyhow::{Result, anyhow};
+use async_trait::async_trait;
+use boxer_core::services::base::upsert_repository::ReadOnlyRepository;
+use cedar_policy::EntityUid;
+use opentelemetry::KeyValue;
+use opentelemetry::metrics::{Counter, Meter};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use utoipa::ToSchema;
+
+/// Denial reported for unmatched requests rejected by the `deny` fallback.
+pub const UNMATCHED_ROUTE_DENIAL: &str = "No action or resource is registered for the request";
+
+/// The fallback applied to a request, reported in decision records and metrics.
+#[derive(ToSchema, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RouteFallback {
+    Unauthorized,
+    Deny,
+    Allow,
+    Map,
+}
+
+impl RouteFallback {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RouteFallback::Unauthorized => "unauthorized",
+            RouteFallback::Deny => "deny",
+            RouteFallback::Allow => "allow",
+            RouteFallback::Map => "map",
+        }
+    }
+}
+
+/// The UIDs a `map` fallback resolves missing lookups to.
+#[derive(Clone, Debug)]
+pub struct MappedRoute {
+    action: EntityUid,
+    resource: EntityUid,
+}
+
+/// How an unmatched request is reviewed.
+#[derive(Clone, Debug)]
+pub enum UnmatchedRoute {
+    Unauthorized,
+    Deny,
+    Allow,
+    Map(MappedRoute),
+}
+
+impl UnmatchedRoute {
+    pub fn fallback(&self) -> RouteFallback {
+        match self {
+            UnmatchedRoute::Unauthorized => RouteFallback::Unauthorized,
+            UnmatchedRoute::Deny => RouteFallback::Deny,
+            UnmatchedRoute::Allow => RouteFallback::Allow,
+            UnmatchedRoute::Map(_) => RouteFallback::Map,
+        }
+    }
+}
+
+tokio::task_local! {
+    static CURRENT_UNMATCHED: Arc<UnmatchedReview>;
+}
+
+/// The fallback rule of a single token review, and whether one of its lookups missed.
+pub struct UnmatchedReview {
+    rule: UnmatchedRoute,
+    missed: AtomicBool,
+}
+
+impl UnmatchedReview {
+    /// Runs `future` with this review's rule applied to lookup misses of every [`FallbackLookup`].
+    pub async fn run<F: Future>(self: &Arc<Self>, future: F) -> F::Output {
+        CURRENT_UNMATCHED.scope(self.clone(), future).await
+    }
+
+    /// The fallback applied to the request, or `None` if every lookup matched.
+    pub fn fallback(&self) -> Option<RouteFallback> {
+        self.missed.load(Ordering::Relaxed).then(|| self.rule.fallback())
+    }
+
+    /// Turns the validation result into the review result: `deny` and `allow` replace it, `unauthorized` and `map`
+    /// keep it.
+    pub fn apply(&self, result: Result<()>) -> Result<()> {
+        match self.fallback() {
+            Some(RouteFallback::Deny) => Err(anyhow!(UNMATCHED_ROUTE_DENIAL)),
+            Some(RouteFallback::Allow) => Ok(()),
+            Some(RouteFallback::Unauthorized | RouteFallback::Map) | None => result,
+        }
+    }
+
+    fn miss(&self) -> Option<&MappedRoute> {
+        self.missed.store(true, Ordering::Relaxed);
+        match &self.rule {
+            UnmatchedRoute::Map(mapped) => Some(mapped),
+            _ => None,
+        }
+    }
+}
+
+/// Applies the fallback rule of the current token review to lookup misses.
+/// Outside of a review, misses are returned as they are.
+pub struct FallbackLookup<Key> {
+    inner: Arc<AssociatedRepository<Key, EntityUid>>,
+    mapped: fn(&MappedRoute) -> &EntityUid,
+}
+
+impl<Key> FallbackLookup<Key> {
+    pub fn actions(inner: Arc<AssociatedRepository<Key, EntityUid>>) -> Self {
+        FallbackLookup {
+            inner,
+            mapped: |mapped| &mapped.action,
+        }
+    }
+
+    pub fn resources(inner: Arc<AssociatedRepository<Key, EntityUid>>) -> Self {
+        FallbackLookup {
+            inner,
+            mapped: |mapped| &mapped.resource,
+        }
+    }
+}
+
+#[async_trait]
+impl<Key> ReadOnlyRepository<Key, EntityUid> for FallbackLookup<Key>
+where
+    Key: Send + Sync + 'static,
+{
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, key: Key) -> Result<EntityUid, Self::ReadError> {
+        let error = match self.inner.get(key).await {
+            Ok(uid) => return Ok(uid),
+            Err(error) => error,
+        };
+        let mapped = CURRENT_UNMATCHED.try_with(|review| review.miss().map(|mapped| (self.mapped)(mapped).clone()));
+        match mapped {
+            Ok(Some(uid)) => Ok(uid),
+            _ => Err(error),
+        }
+    }
+}
+
+fn parse_uid(uid: &str) -> Result<EntityUid> {
+    EntityUid::from_str(uid).map_err(|e| anyhow!("Invalid fallback UID {}: {}", uid, e))
+}
+
+/// Applies the configured fallback to requests that match no registered action or resource.
+pub struct UnmatchedRoutes {
+    default: UnmatchedRoute,
+    schemas: HashMap<String, UnmatchedRoute>,
+    hostnames: HashMap<String, UnmatchedRoute>,
+    unmatched: Counter<u64>,
+}
+
+impl UnmatchedRoutes {
+    pub fn new(settings: &UnmatchedRouteSettings, meter: &Meter) -> Result<Self> {
+        let build = |behavior: &UnmatchedRouteBehavior| -> Result<UnmatchedRoute> {
+            Ok(match behavior {
+                UnmatchedRouteBehavior::Unauthorized => UnmatchedRoute::Unauthorized,
+                UnmatchedRouteBehavior::Deny => UnmatchedRoute::Deny,
+                UnmatchedRouteBehavior::Allow => UnmatchedRoute::Allow,
+                UnmatchedRouteBehavior::Map { action, resource } => UnmatchedRoute::Map(MappedRoute {
+                    action: parse_uid(action)?,
+                    resource: parse_uid(resource)?,
+                }),
+            })
+        };
+        let schemas = settings
+            .schemas
+            .iter()
+            .map(|(schema, behavior)| Ok((schema.clone(), build(behavior)?)))
+            .collect::<Result<_>>()?;
+        let hostnames = settings
+            .hostnames
+            .iter()
+            .map(|(hostname, behavior)| Ok((normalize_hostname(hostname), build(behavior)?)))
+            .collect::<Result<_>>()?;
+        Ok(UnmatchedRoutes {
+            default: build(&settings.default)?,
+            schemas,
+            hostnames,
+            unmatched: meter
+                .u64_counter("unmatched_routes")
+                .with_description("Token reviews that matched no registered action or resource, by schema and fallback")
+                .build(),
+        })
+    }
+
+    fn rule(&self, schema: &str, hostname: Option<&str>) -> &UnmatchedRoute {
+        hostname
+            .and_then(|hostname| self.hostnames.get(&normalize_hostname(hostname)))
+            .or_else(|| self.schemas.get(schema))
+            .unwrap_or(&self.default)
+    }
+
+    /// Selects the fallback rule for a token review; it applies only if one of the review's lookups misses.
+    pub fn start(&self, schema: &str, hostname: Option<&str>) -> Arc<UnmatchedReview> {
+        Arc::new(UnmatchedReview {
+            rule: self.rule(schema, hostname).clone(),
+            missed: AtomicBool::new(false),
+        })
+    }
+
+    /// Reports the applied fallback in metrics and turns the validation result into the review result,
+    /// see [`UnmatchedReview::apply`].
+    pub fn finish(&self, schema: &str, review: &UnmatchedReview, result: Result<()>) -> Result<()> {
+        if let Some(fallback) = review.fallback() {
+            self.unmatched.add(
+                1,
+                &[
+                    KeyValue::new("schema", schema.to_string()),
+                    KeyValue::new("fallback", fallback.as_str()),
+                ],
+            );
+        }
+        review.apply(result)
+    }
+}
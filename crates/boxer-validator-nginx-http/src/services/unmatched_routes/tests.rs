@@ -0,0 +1,134 @@
+use super::*;
+use test_case::test_case;
+
+struct StaticLookup(Option<&'static str>);
+
+#[async_trait]
+impl ReadOnlyRepository<String, EntityUid> for StaticLookup {
+    type ReadError = anyhow::Error;
+
+    async fn get(&self, _key: String) -> Result<EntityUid, Self::ReadError> {
+        match self.0 {
+            Some(uid) => Ok(EntityUid::from_str(uid)?),
+            None => Err(anyhow!("No route is registered for the request")),
+        }
+    }
+}
+
+fn mapped() -> UnmatchedRouteBehavior {
+    UnmatchedRouteBehavior::Map {
+        action: "PhotoApp::Action::\"unknown\"".to_string(),
+        resource: "PhotoApp::Photo::\"unknown\"".to_string(),
+    }
+}
+
+fn unmatched_routes(default: UnmatchedRouteBehavior) -> UnmatchedRoutes {
+    let settings = UnmatchedRouteSettings {
+        default,
+        ..Default::default()
+    };
+    UnmatchedRoutes::new(&settings, &opentelemetry::global::meter("test")).unwrap()
+}
+
+/// Reviews a request whose action lookup returns `action`, the way the token review does.
+async fn review(routes: &UnmatchedRoutes, action: Option<&'static str>) -> (Result<EntityUid>, Option<RouteFallback>) {
+    let lookup = FallbackLookup::actions(Arc::new(StaticLookup(action)));
+    let review = routes.start("photo-app", Some("www.example.com"));
+    let resolved = review.run(lookup.get("photo-app".to_string())).await;
+    (resolved, review.fallback())
+}
+
+#[tokio::test]
+async fn test_matched_request_has_no_fallback() {
+    let routes = unmatched_routes(UnmatchedRouteBehavior::Deny);
+
+    let (resolved, fallback) = review(&routes, Some("PhotoApp::Action::\"viewPhoto\"")).await;
+
+    assert_eq!(resolved.unwrap().to_string(), "PhotoApp::Action::\"viewPhoto\"");
+    assert_eq!(fallback, None);
+}
+
+#[tokio::test]
+async fn test_unmatched_request_is_unauthorized_by_default() {
+    let routes = unmatched_routes(UnmatchedRouteSettings::default().default);
+
+    let (resolved, fallback) = review(&routes, None).await;
+
+    assert!(resolved.is_err());
+    assert_eq!(fallback, Some(RouteFallback::Unauthorized));
+}
+
+#[tokio::test]
+async fn test_map_resolves_missing_lookups() {
+    let routes = unmatched_routes(mapped());
+
+    let (resolved, fallback) = review(&routes, None).await;
+
+    assert_eq!(resolved.unwrap().to_string(), "PhotoApp::Action::\"unknown\"");
+    assert_eq!(fallback, Some(RouteFallback::Map));
+}
+
+#[tokio::test]
+async fn test_misses_outside_a_review_are_errors() {
+    let lookup = FallbackLookup::resources(Arc::new(StaticLookup(None)));
+
+    assert!(lookup.get("photo-app".to_string()).await.is_err());
+}
+
+#[test_case(UnmatchedRouteBehavior::Unauthorized => Err("No route is registered for the request".to_string()); "unauthorized keeps the denial")]
+#[test_case(UnmatchedRouteBehavior::Deny => Err(UNMATCHED_ROUTE_DENIAL.to_string()); "deny")]
+#[test_case(UnmatchedRouteBehavior::Allow => Ok(()); "allow")]
+#[tokio::test]
+async fn test_finish_applies_fallback(behavior: UnmatchedRouteBehavior) -> Result<(), String> {
+    let routes = unmatched_routes(behavior);
+    let lookup = FallbackLookup::actions(Arc::new(StaticLookup(None)));
+    let review = routes.start("photo-app", None);
+
+    let result = review.run(lookup.get("photo-app".to_string())).await.map(|_| ());
+
+    routes.finish("photo-app", &review, result).map_err(|e| e.to_string())
+}
+
+#[test_case("photo-app", "WWW.Example.com" => Some(RouteFallback::Map); "hostname override")]
+#[test_case("photo-app", "api.example.com" => Some(RouteFallback::Allow); "schema override")]
+#[test_case("other-app", "api.example.com" => Some(RouteFallback::Deny); "default")]
+#[tokio::test]
+async fn test_fallback_precedence(schema: &str, hostname: &str) -> Option<RouteFallback> {
+    let settings = UnmatchedRouteSettings {
+        default: UnmatchedRouteBehavior::Deny,
+        schemas: HashMap::from([("photo-app".to_string(), UnmatchedRouteBehavior::Allow)]),
+        hostnames: HashMap::from([("www.example.com".to_string(), mapped())]),
+    };
+    let routes = UnmatchedRoutes::new(&settings, &opentelemetry::global::meter("test")).unwrap();
+    let lookup = FallbackLookup::resources(Arc::new(StaticLookup(None)));
+
+    let review = routes.start(schema, Some(hostname));
+    let _ = review.run(lookup.get(schema.to_string())).await;
+
+    review.fallback()
+}
+
+#[test]
+fn test_invalid_mapped_uid_is_rejected() {
+    let settings = UnmatchedRouteSettings {
+        default: UnmatchedRouteBehavior::Map {
+            action: "not a uid".to_string(),
+            resource: "PhotoApp::Photo::\"unknown\"".to_string(),
+        },
+        ..Default::default()
+    };
+
+    assert!(UnmatchedRoutes::new(&settings, &opentelemetry::global::meter("test")).is_err());
+}
+
+#[test]
+fn test_deserialize_behavior() {
+    let behavior: UnmatchedRouteBehavior = serde_json::from_value(serde_json::json!({
+        "behavior": "map",
+        "action": "PhotoApp::Action::\"unknown\"",
+        "resource": "PhotoApp::Photo::\"unknown\"",
+    }))
+    .unwrap();
+
+    assert_eq!(behavior, mapped());
+}
@@ -1,4 +1,3 @@
-use actix_web::dev::ServerHandle;
 use anyhow::{Result, anyhow};
 use boxer_core::services::backends::BackendConfiguration;
 use boxer_core::services::observability::open_telemetry::logging::settings::LogSettings;
@@ -7,10 +6,13 @@ use boxer_core::services::observability::open_telemetry::settings::OpenTelemetry
 use boxer_core::services::observability::open_telemetry::tracing::settings::TracingSettings;
 use boxer_core::services::token_decryption_service::token_settings::TokenValidationSettings;
 use boxer_core::testing::get_kubeconfig;
+use boxer_validator_nginx_http::services::api_server::ApiServerHandle;
 use boxer_validator_nginx_http::services::backends;
 use boxer_validator_nginx_http::services::configuration::models::{
-    AppSettings, BackendSettings, KubernetesBackendSettings,
+    AdminApiSettings, AppSettings, BackendSettings, KubernetesBackendSettings, TokenSource,
 };
+use boxer_validator_nginx_http::services::shutdown::ShutdownHooks;
+use boxer_validator_nginx_http::services::telemetry::Telemetry;
 use boxer_validator_nginx_http::start_api_server;
 use k8s_openapi::api::core::v1::Secret;
 use kube::{Api, Client};
@@ -73,7 +75,7 @@ pub async fn external_token() -> String {
     access_token.to_string()
 }
 
-pub type TestServerHandles = (ServerHandle, JoinHandle<std::io::Result<()>>, SocketAddr);
+pub type TestServerHandles = (ApiServerHandle, JoinHandle<std::io::Result<()>>, SocketAddr);
 #[fixture]
 pub async fn with_test_server() -> TestServerHandles {
     let server_address = "127.0.0.1:8080".parse().unwrap();
@@ -84,6 +86,7 @@ pub async fn with_test_server() -> TestServerHandles {
         deploy_environment: "integration-tests".to_string(),
         instance_name: "integration-tests".to_string(),
         listen_address: SocketAddr::from(server_address),
+        workers: None,
         opentelemetry: OpenTelemetrySettings {
             log_settings: LogSettings { enabled: false },
             metrics_settings: MetricsSettings { enabled: false },
@@ -96,6 +99,8 @@ pub async fn with_test_server() -> TestServerHandles {
                 in_cluster: false,
                 namespace: "default".to_string(),
                 operation_timeout: Default::default(),
+                max_watch_error_duration: Default::default(),
+                max_watch_event_age: None,
                 resource_owner_label: "application/boxer-validator-nginx".to_string(),
             },
         },
@@ -104,6 +109,20 @@ pub async fn with_test_server() -> TestServerHandles {
             audience: "boxer.sneaksanddata.com".to_string(),
             keys: format!("{{\"default\": \"{}\"}}", signing_key).to_string(),
         },
+        admin_api: AdminApiSettings {
+            principal_types: vec!["PhotoApp::User".to_string()],
+            listener: Default::default(),
+        },
+        audit: Default::default(),
+        decision_log: Default::default(),
+        enforcement_mode: None,
+        unmatched_routes: Default::default(),
+        shutdown: Default::default(),
+        prometheus: Default::default(),
+        request_headers: Default::default(),
+        token_sources: vec![TokenSource::Authorization],
+        signing_keys: Default::default(),
+        tls: None,
     };
 
     let current_backend = backends::new()
@@ -111,7 +130,15 @@ pub async fn with_test_server() -> TestServerHandles {
         .await
         .expect("Failed to configure Kubernetes backend");
 
-    let server = start_api_server(current_backend, app_settings, "test").expect("Start api server failed");
+    let server = start_api_server(
+        current_backend,
+        app_settings,
+        "test",
+        &Telemetry::default(),
+        &ShutdownHooks::default(),
+    )
+    .await
+    .expect("Start api server failed");
 
     let handle = server.handle();
     let thread = tokio::spawn(server);